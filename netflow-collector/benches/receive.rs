@@ -0,0 +1,109 @@
+//! Throughput of the collector receiving full Netflow v5 packets from local
+//! senders, one `recv_from` per datagram against batches of `recvmmsg`
+//!
+//! Each iteration receives and decodes one datagram of 30 flows, so the
+//! reported throughput is in flows per second. The senders share the machine
+//! with the collector: the gap between both paths only shows when they have
+//! cores of their own and outpace a single `recv_from` loop.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use netflow::v5::raw::FlowSet;
+use netflow::v5::{FlowRecord, PacketBuilder};
+use netflow_collector::{Arena, Collector, Format, MAX_PACKET_LEN};
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+const SENDERS: usize = 2;
+const FLOWS: u32 = 30;
+
+fn packet() -> Vec<u8> {
+  let mut builder = PacketBuilder::new(SystemTime::now() - Duration::from_secs(3600));
+  for i in 0..FLOWS {
+    builder
+      .push(FlowRecord {
+        source_addr: Ipv4Addr::new(192, 0, 2, i as u8),
+        destination_addr: Ipv4Addr::new(198, 51, 100, 1),
+        packets: i + 1,
+        bytes: 1500 * (i + 1),
+        ..FlowRecord::default()
+      })
+      .unwrap();
+  }
+  builder.build(SystemTime::now()).unwrap()
+}
+
+/// Floods `target` with packets until the returned flag is set
+fn flood(target: SocketAddr) -> Arc<AtomicBool> {
+  let stop = Arc::new(AtomicBool::new(false));
+  for _ in 0..SENDERS {
+    let stop = stop.clone();
+    thread::spawn(move || {
+      let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+      let data = packet();
+      while !stop.load(Ordering::Relaxed) {
+        let _ = socket.send_to(&data, target);
+      }
+    });
+  }
+  stop
+}
+
+/// Walks the records of a packet in place, as a collector would
+fn decode(data: &[u8]) -> u64 {
+  let set = FlowSet::new(data).unwrap();
+  set.records().map(|record| u64::from(record.bytes())).sum()
+}
+
+fn single_recv(c: &mut Criterion) {
+  let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+  let stop = flood(socket.local_addr().unwrap());
+  let mut buffer = vec![0; MAX_PACKET_LEN];
+
+  let mut group = c.benchmark_group("receive");
+  group.throughput(Throughput::Elements(u64::from(FLOWS)));
+  group.bench_function("recv_from", |b| {
+    b.iter_custom(|iters| {
+      let start = Instant::now();
+      for _ in 0..iters {
+        let (len, _) = socket.recv_from(&mut buffer).unwrap();
+        black_box(decode(&buffer[..len]));
+      }
+      start.elapsed()
+    })
+  });
+  group.finish();
+  stop.store(true, Ordering::Relaxed);
+}
+
+fn batch_recv(c: &mut Criterion) {
+  let mut group = c.benchmark_group("receive");
+  group.throughput(Throughput::Elements(u64::from(FLOWS)));
+  for &slots in [8, 64].iter() {
+    let mut collector = Collector::bind("127.0.0.1:0", Format::Text).unwrap();
+    let stop = flood(collector.local_addr().unwrap());
+    let mut arena = Arena::new(slots);
+
+    group.bench_function(format!("recvmmsg/{}", slots), |b| {
+      b.iter_custom(|iters| {
+        let start = Instant::now();
+        let mut received = 0;
+        while received < iters {
+          collector.receive_batch(&mut arena).unwrap();
+          for datagram in arena.iter() {
+            black_box(decode(datagram.data));
+          }
+          received += arena.len() as u64;
+        }
+        start.elapsed()
+      })
+    });
+    stop.store(true, Ordering::Relaxed);
+  }
+  group.finish();
+}
+
+criterion_group!(benches, single_recv, batch_recv);
+criterion_main!(benches);
@@ -0,0 +1,373 @@
+use netflow::v5::raw::{FlowSet, Header, Record};
+use netflow::Error;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+
+/// Largest Netflow v5 packet: a header and 30 records
+pub const MAX_PACKET_LEN: usize = Header::LEN + FlowSet::MAX_COUNT as usize * Record::LEN;
+
+/// Pre-allocated slots receiving one datagram each, for batched receives
+///
+/// Datagrams stay in their slot until the next receive, so the `FlowSet` of
+/// each one is decoded in place without copying.
+pub struct Arena {
+  buffer: Vec<u8>,
+  slot_len: usize,
+  /// Slot, sender and length of each datagram held
+  received: Vec<(usize, SocketAddr, usize)>,
+  #[cfg(target_os = "linux")]
+  headers: mmsg::Headers,
+}
+
+impl std::fmt::Debug for Arena {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Arena")
+      .field("slots", &self.slots())
+      .field("slot_len", &self.slot_len)
+      .field("received", &self.received)
+      .finish()
+  }
+}
+
+/// A datagram received into a slot of an `Arena`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Received<'a> {
+  pub exporter: SocketAddr,
+  pub data: &'a [u8],
+}
+
+impl<'a> Received<'a> {
+  /// Decodes the datagram in place with `FlowSet::new`
+  pub fn flow_set(&self) -> Result<FlowSet<'a>, Error> {
+    FlowSet::new(self.data)
+  }
+}
+
+impl Arena {
+  /// Allocates `slots` slots large enough for any Netflow v5 packet
+  pub fn new(slots: usize) -> Arena {
+    Arena::with_slot_len(slots, MAX_PACKET_LEN)
+  }
+
+  /// Allocates `slots` slots of `slot_len` bytes each
+  ///
+  /// A datagram longer than `slot_len` is truncated to it, and then fails to
+  /// decode.
+  ///
+  /// # Panics
+  ///
+  /// If `slots` or `slot_len` is zero
+  pub fn with_slot_len(slots: usize, slot_len: usize) -> Arena {
+    assert!(slots > 0 && slot_len > 0, "empty arena");
+    Arena {
+      buffer: vec![0; slots * slot_len],
+      slot_len,
+      received: Vec::with_capacity(slots),
+      #[cfg(target_os = "linux")]
+      headers: mmsg::Headers::new(slots),
+    }
+  }
+
+  /// Maximum number of datagrams received at once
+  pub fn slots(&self) -> usize {
+    self.buffer.len() / self.slot_len
+  }
+
+  /// Length in bytes of each slot
+  pub fn slot_len(&self) -> usize {
+    self.slot_len
+  }
+
+  /// Number of datagrams held since the last receive
+  pub fn len(&self) -> usize {
+    self.received.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.received.is_empty()
+  }
+
+  /// `i`th datagram held
+  pub fn get(&self, i: usize) -> Option<Received<'_>> {
+    self.received.get(i).map(|&(slot, exporter, len)| Received {
+      exporter,
+      data: &self.buffer[slot * self.slot_len..slot * self.slot_len + len],
+    })
+  }
+
+  /// Datagrams held since the last receive, in the order they arrived
+  pub fn iter(&self) -> impl Iterator<Item = Received<'_>> {
+    (0..self.len()).filter_map(move |i| self.get(i))
+  }
+
+  /// Waits for a datagram on `socket`, then takes every other queued datagram
+  /// that fits, with a single `recvmmsg` call
+  ///
+  /// Datagrams whose sender is neither an IPv4 nor an IPv6 address are
+  /// skipped, so the number held may be less than the number received.
+  #[cfg(target_os = "linux")]
+  pub(crate) fn receive(&mut self, socket: &UdpSocket) -> io::Result<usize> {
+    self.received.clear();
+    mmsg::receive(
+      socket,
+      &mut self.buffer,
+      self.slot_len,
+      &mut self.headers,
+      &mut self.received,
+    )?;
+    Ok(self.received.len())
+  }
+
+  /// Waits for a single datagram on `socket`, as `recvmmsg` is not available
+  #[cfg(not(target_os = "linux"))]
+  pub(crate) fn receive(&mut self, socket: &UdpSocket) -> io::Result<usize> {
+    self.received.clear();
+    let (len, exporter) = socket.recv_from(&mut self.buffer[..self.slot_len])?;
+    self.received.push((0, exporter, len));
+    Ok(1)
+  }
+}
+
+#[cfg(target_os = "linux")]
+mod mmsg {
+  use std::io;
+  use std::mem;
+  use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, UdpSocket};
+  use std::os::unix::io::AsRawFd;
+
+  /// Message headers handed to `recvmmsg`, kept with the arena so that batches
+  /// do not allocate
+  pub(super) struct Headers {
+    messages: Vec<libc::mmsghdr>,
+    iovecs: Vec<libc::iovec>,
+    pub(super) addrs: Vec<libc::sockaddr_storage>,
+  }
+
+  // The raw pointers of the headers are set right before each `recvmmsg` call
+  // and never read otherwise.
+  unsafe impl Send for Headers {}
+  unsafe impl Sync for Headers {}
+
+  impl Headers {
+    pub(super) fn new(slots: usize) -> Headers {
+      // Plain C structures for which all zeroes is a valid value
+      unsafe {
+        Headers {
+          messages: vec![mem::zeroed(); slots],
+          iovecs: vec![mem::zeroed(); slots],
+          addrs: vec![mem::zeroed(); slots],
+        }
+      }
+    }
+  }
+
+  pub(super) fn receive(
+    socket: &UdpSocket,
+    buffer: &mut [u8],
+    slot_len: usize,
+    headers: &mut Headers,
+    received: &mut Vec<(usize, SocketAddr, usize)>,
+  ) -> io::Result<()> {
+    let Headers {
+      messages,
+      iovecs,
+      addrs,
+    } = headers;
+    for (((message, iovec), addr), slot) in messages
+      .iter_mut()
+      .zip(iovecs.iter_mut())
+      .zip(addrs.iter_mut())
+      .zip(buffer.chunks_mut(slot_len))
+    {
+      iovec.iov_base = slot.as_mut_ptr() as *mut libc::c_void;
+      iovec.iov_len = slot.len();
+      message.msg_hdr.msg_name = addr as *mut libc::sockaddr_storage as *mut libc::c_void;
+      message.msg_hdr.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+      message.msg_hdr.msg_iov = iovec;
+      message.msg_hdr.msg_iovlen = 1;
+      message.msg_len = 0;
+    }
+
+    let count = loop {
+      let count = unsafe {
+        libc::recvmmsg(
+          socket.as_raw_fd(),
+          messages.as_mut_ptr(),
+          messages.len() as libc::c_uint,
+          libc::MSG_WAITFORONE as _,
+          std::ptr::null_mut(),
+        )
+      };
+      if count >= 0 {
+        break count as usize;
+      }
+      let error = io::Error::last_os_error();
+      if error.kind() != io::ErrorKind::Interrupted {
+        return Err(error);
+      }
+    };
+
+    collect(headers, count, received);
+    Ok(())
+  }
+
+  /// Appends the first `count` datagrams received into the slots of `headers`
+  /// to `received`, skipping those of a sender of an unexpected address
+  /// family rather than losing the rest of the batch
+  pub(super) fn collect(
+    headers: &Headers,
+    count: usize,
+    received: &mut Vec<(usize, SocketAddr, usize)>,
+  ) {
+    let slots = headers.messages.iter().zip(headers.addrs.iter());
+    for (slot, (message, addr)) in slots.take(count).enumerate() {
+      if let Some(exporter) = socket_addr(addr) {
+        received.push((slot, exporter, message.msg_len as usize));
+      }
+    }
+  }
+
+  fn socket_addr(addr: &libc::sockaddr_storage) -> Option<SocketAddr> {
+    match libc::c_int::from(addr.ss_family) {
+      libc::AF_INET => {
+        let addr = unsafe { &*(addr as *const _ as *const libc::sockaddr_in) };
+        Some(SocketAddr::V4(SocketAddrV4::new(
+          Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)),
+          u16::from_be(addr.sin_port),
+        )))
+      }
+      libc::AF_INET6 => {
+        let addr = unsafe { &*(addr as *const _ as *const libc::sockaddr_in6) };
+        Some(SocketAddr::V6(SocketAddrV6::new(
+          Ipv6Addr::from(addr.sin6_addr.s6_addr),
+          u16::from_be(addr.sin6_port),
+          addr.sin6_flowinfo,
+          addr.sin6_scope_id,
+        )))
+      }
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{packet, records};
+  use netflow::Protocol;
+
+  /// Receives into `arena` until it held `count` datagrams in total
+  fn receive_all(
+    socket: &UdpSocket,
+    arena: &mut Arena,
+    count: usize,
+  ) -> Vec<(SocketAddr, Vec<u8>)> {
+    let mut datagrams = Vec::new();
+    while datagrams.len() < count {
+      arena.receive(socket).unwrap();
+      datagrams.extend(arena.iter().map(|d| (d.exporter, d.data.to_vec())));
+    }
+    datagrams
+  }
+
+  #[test]
+  fn arena_should_receive_datagrams_into_slots() {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let exporter = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let exporter_addr = exporter.local_addr().unwrap();
+    let sent = (1..=5)
+      .map(|count| packet(&records(count)))
+      .collect::<Vec<_>>();
+    for data in &sent {
+      exporter
+        .send_to(data, socket.local_addr().unwrap())
+        .unwrap();
+    }
+
+    let mut arena = Arena::new(4);
+    let received = receive_all(&socket, &mut arena, sent.len());
+
+    assert!(arena.len() <= arena.slots());
+    assert_eq!(
+      received,
+      sent
+        .iter()
+        .map(|data| (exporter_addr, data.clone()))
+        .collect::<Vec<_>>()
+    );
+  }
+
+  #[test]
+  fn arena_should_expose_flow_sets_in_place() {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let exporter = UdpSocket::bind("127.0.0.1:0").unwrap();
+    exporter
+      .send_to(&packet(&records(3)), socket.local_addr().unwrap())
+      .unwrap();
+
+    let mut arena = Arena::new(8);
+    assert_eq!(arena.receive(&socket).unwrap(), 1);
+    let datagram = arena.get(0).unwrap();
+    let set = datagram.flow_set().unwrap();
+
+    assert_eq!(set.len(), 3);
+    assert_eq!(
+      set
+        .records()
+        .map(|record| record.packets())
+        .collect::<Vec<_>>(),
+      vec![0, 1, 2]
+    );
+    assert_eq!(arena.get(1), None);
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn arena_should_skip_datagrams_of_unexpected_senders() {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let exporter = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let sent = (1..=3)
+      .map(|count| packet(&records(count)))
+      .collect::<Vec<_>>();
+    for data in &sent {
+      exporter
+        .send_to(data, socket.local_addr().unwrap())
+        .unwrap();
+    }
+    let mut arena = Arena::new(4);
+    assert_eq!(arena.receive(&socket).unwrap(), 3);
+
+    arena.headers.addrs[1].ss_family = libc::AF_UNIX as libc::sa_family_t;
+    arena.received.clear();
+    mmsg::collect(&arena.headers, 3, &mut arena.received);
+
+    assert_eq!(
+      arena.iter().map(|d| d.data).collect::<Vec<_>>(),
+      vec![&sent[0][..], &sent[2][..]]
+    );
+  }
+
+  #[test]
+  fn arena_should_truncate_datagrams_longer_than_slots() {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let exporter = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let data = packet(&records(2));
+    exporter
+      .send_to(&data, socket.local_addr().unwrap())
+      .unwrap();
+
+    let mut arena = Arena::with_slot_len(2, 100);
+    arena.receive(&socket).unwrap();
+
+    assert_eq!(arena.get(0).unwrap().data, &data[..100]);
+    assert_eq!(
+      arena.get(0).unwrap().flow_set(),
+      Err(Error::Truncated {
+        protocol: Protocol::V5,
+        count: 2,
+        expected: data.len(),
+        actual: 100,
+      })
+    );
+  }
+}
@@ -0,0 +1,265 @@
+use crate::{Arena, ExporterStats, Format, Stats};
+use netflow::v5::raw::FlowSet;
+use netflow::Error;
+use std::io::{self, Write};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+/// Largest payload of a UDP datagram
+pub(crate) const MAX_DATAGRAM_LEN: usize = 65535;
+
+/// Outcome of a datagram handled by the collector
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Datagram {
+  /// The datagram was decoded and its records written
+  Decoded {
+    exporter: SocketAddr,
+    records: usize,
+    stats: ExporterStats,
+  },
+  /// The datagram failed to decode and nothing was written
+  Malformed {
+    exporter: SocketAddr,
+    error: Error,
+    stats: ExporterStats,
+  },
+}
+
+/// Receives Netflow v5 datagrams on a UDP socket and writes their records
+pub struct Collector {
+  socket: UdpSocket,
+  format: Format,
+  stats: Stats,
+  buffer: Vec<u8>,
+}
+
+impl Collector {
+  /// Binds a collector to the given local address
+  pub fn bind<A: ToSocketAddrs>(addr: A, format: Format) -> io::Result<Collector> {
+    Ok(Collector {
+      socket: UdpSocket::bind(addr)?,
+      format,
+      stats: Stats::default(),
+      buffer: vec![0; MAX_DATAGRAM_LEN],
+    })
+  }
+
+  /// Local address the collector listens on
+  pub fn local_addr(&self) -> io::Result<SocketAddr> {
+    self.socket.local_addr()
+  }
+
+  /// Counters of the datagrams received so far
+  pub fn stats(&self) -> &Stats {
+    &self.stats
+  }
+
+  /// Waits for the next datagram and writes its records to `out`, one per line
+  ///
+  /// Only errors of the socket or of `out` are returned as errors, a datagram
+  /// that fails to decode is reported as `Datagram::Malformed`.
+  pub fn receive<W: Write>(&mut self, out: &mut W) -> io::Result<Datagram> {
+    let (len, exporter) = self.socket.recv_from(&mut self.buffer)?;
+    let datagram = handle(
+      &mut self.stats,
+      self.format,
+      exporter,
+      &self.buffer[..len],
+      out,
+    )?;
+    out.flush()?;
+    Ok(datagram)
+  }
+
+  /// Decodes `data` as received from `exporter` and writes its records to `out`
+  pub fn handle<W: Write>(
+    &mut self,
+    exporter: SocketAddr,
+    data: &[u8],
+    out: &mut W,
+  ) -> io::Result<Datagram> {
+    let datagram = handle(&mut self.stats, self.format, exporter, data, out)?;
+    out.flush()?;
+    Ok(datagram)
+  }
+
+  /// Waits for the next datagram, then receives every other queued datagram
+  /// that fits in `arena`, returning their number
+  ///
+  /// On Linux, a whole batch takes a single `recvmmsg` system call. Elsewhere,
+  /// a single datagram is received. Nothing is decoded: see `handle_batch`, or
+  /// `Received::flow_set` to read the records in place.
+  pub fn receive_batch(&mut self, arena: &mut Arena) -> io::Result<usize> {
+    arena.receive(&self.socket)
+  }
+
+  /// Decodes every datagram held by `arena` and writes their records to `out`
+  pub fn handle_batch<W: Write>(
+    &mut self,
+    arena: &Arena,
+    out: &mut W,
+  ) -> io::Result<Vec<Datagram>> {
+    let datagrams = arena
+      .iter()
+      .map(|datagram| {
+        handle(
+          &mut self.stats,
+          self.format,
+          datagram.exporter,
+          datagram.data,
+          out,
+        )
+      })
+      .collect::<io::Result<Vec<_>>>()?;
+    out.flush()?;
+    Ok(datagrams)
+  }
+}
+
+fn handle<W: Write>(
+  stats: &mut Stats,
+  format: Format,
+  exporter: SocketAddr,
+  data: &[u8],
+  out: &mut W,
+) -> io::Result<Datagram> {
+  let set = match FlowSet::new(data) {
+    Ok(set) => set,
+    Err(error) => {
+      return Ok(Datagram::Malformed {
+        exporter,
+        error,
+        stats: *stats.malformed(exporter.ip()),
+      })
+    }
+  };
+
+  let header = set.header();
+  for record in set.records() {
+    format.write(out, exporter.ip(), &header, &record)?;
+  }
+  Ok(Datagram::Decoded {
+    exporter,
+    records: set.len(),
+    stats: *stats.decoded(exporter.ip(), set.len()),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{packet, records};
+  use netflow::Protocol;
+
+  #[test]
+  fn collector_should_receive_datagrams_from_socket() {
+    let mut collector = Collector::bind("127.0.0.1:0", Format::Text).unwrap();
+    let exporter = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let exporter_addr = exporter.local_addr().unwrap();
+    exporter
+      .send_to(&packet(&records(3)), collector.local_addr().unwrap())
+      .unwrap();
+    exporter
+      .send_to(&[0, 5, 0, 1], collector.local_addr().unwrap())
+      .unwrap();
+
+    let mut out = Vec::new();
+    assert_eq!(
+      collector.receive(&mut out).unwrap(),
+      Datagram::Decoded {
+        exporter: exporter_addr,
+        records: 3,
+        stats: ExporterStats {
+          packets: 1,
+          records: 3,
+          malformed: 0,
+        },
+      }
+    );
+    assert_eq!(String::from_utf8(out).unwrap().lines().count(), 3);
+
+    let mut out = Vec::new();
+    assert_eq!(
+      collector.receive(&mut out).unwrap(),
+      Datagram::Malformed {
+        exporter: exporter_addr,
+        error: Error::NotEnoughData {
+          protocol: Protocol::V5,
+          offset: 0,
+          expected: 24,
+          actual: 4,
+        },
+        stats: ExporterStats {
+          packets: 1,
+          records: 3,
+          malformed: 1,
+        },
+      }
+    );
+    assert!(out.is_empty());
+    assert_eq!(collector.stats().len(), 1);
+  }
+
+  #[test]
+  fn collector_should_count_malformed_datagrams_per_exporter() {
+    let mut collector = Collector::bind("127.0.0.1:0", Format::Json).unwrap();
+    let first: SocketAddr = "192.0.2.1:2055".parse().unwrap();
+    let second: SocketAddr = "192.0.2.2:2055".parse().unwrap();
+    let mut truncated = packet(&records(2));
+    truncated.truncate(truncated.len() - 1);
+    let mut out = Vec::new();
+
+    collector
+      .handle(first, &packet(&records(1)), &mut out)
+      .unwrap();
+    collector.handle(first, &truncated, &mut out).unwrap();
+    collector.handle(second, &[0; 24], &mut out).unwrap();
+    collector.handle(second, &truncated, &mut out).unwrap();
+
+    let stats = collector.stats();
+    assert_eq!(stats.get(first.ip()).unwrap().malformed, 1);
+    assert_eq!(stats.get(first.ip()).unwrap().packets, 1);
+    assert_eq!(stats.get(second.ip()).unwrap().malformed, 2);
+    assert_eq!(stats.get(second.ip()).unwrap().packets, 0);
+    assert_eq!(String::from_utf8(out).unwrap().lines().count(), 1);
+  }
+
+  #[test]
+  fn collector_should_handle_batches() {
+    let mut collector = Collector::bind("127.0.0.1:0", Format::Text).unwrap();
+    let exporter = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let exporter_addr = exporter.local_addr().unwrap();
+    for data in [packet(&records(2)), vec![0, 5, 0, 1], packet(&records(3))].iter() {
+      exporter
+        .send_to(data, collector.local_addr().unwrap())
+        .unwrap();
+    }
+
+    let mut arena = Arena::new(16);
+    let mut out = Vec::new();
+    let mut datagrams = Vec::new();
+    while datagrams.len() < 3 {
+      collector.receive_batch(&mut arena).unwrap();
+      datagrams.extend(collector.handle_batch(&arena, &mut out).unwrap());
+    }
+
+    assert_eq!(
+      datagrams
+        .iter()
+        .map(|datagram| match datagram {
+          Datagram::Decoded { records, .. } => Some(*records),
+          Datagram::Malformed { .. } => None,
+        })
+        .collect::<Vec<_>>(),
+      vec![Some(2), None, Some(3)]
+    );
+    assert_eq!(String::from_utf8(out).unwrap().lines().count(), 5);
+    assert_eq!(
+      collector.stats().get(exporter_addr.ip()),
+      Some(&ExporterStats {
+        packets: 2,
+        records: 5,
+        malformed: 1,
+      })
+    );
+  }
+}
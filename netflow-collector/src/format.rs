@@ -0,0 +1,142 @@
+use crate::writer::write_object;
+use crate::{Column, Rendering};
+use netflow::v5::raw::{Header, Record};
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// How flow records are written, one line per record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+  /// Human-readable summary of the flow
+  Text,
+  /// JSON object with every field of the record, the `Column::ALL` of a
+  /// `JsonLinesWriter` with `Rendering::HumanAddresses`
+  Json,
+}
+
+/// Error returned when parsing an unknown `Format` name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFormatError(String);
+
+impl std::fmt::Display for ParseFormatError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "unknown format {:?}, expected text or json", self.0)
+  }
+}
+
+impl std::error::Error for ParseFormatError {}
+
+impl FromStr for Format {
+  type Err = ParseFormatError;
+
+  fn from_str(s: &str) -> Result<Format, ParseFormatError> {
+    match s {
+      "text" => Ok(Format::Text),
+      "json" => Ok(Format::Json),
+      _ => Err(ParseFormatError(s.to_string())),
+    }
+  }
+}
+
+impl Format {
+  /// Writes `record`, exported by `exporter` in the packet of `header`, as one line
+  pub fn write<W: Write>(
+    self,
+    out: &mut W,
+    exporter: IpAddr,
+    header: &Header<'_>,
+    record: &Record<'_>,
+  ) -> io::Result<()> {
+    match self {
+      Format::Text => writeln!(
+        out,
+        "{}.{:09} {} {}:{} > {}:{} proto {} packets {} bytes {}",
+        header.unix_secs(),
+        header.unix_nsecs(),
+        exporter,
+        record.source_addr(),
+        record.source_port(),
+        record.destination_addr(),
+        record.destination_port(),
+        record.protocol(),
+        record.packets(),
+        record.bytes(),
+      ),
+      Format::Json => write_object(
+        out,
+        &Column::ALL,
+        Rendering::HumanAddresses,
+        exporter,
+        header,
+        record,
+      ),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::packet;
+  use netflow::v5::raw::FlowSet;
+  use netflow::v5::FlowRecord;
+  use std::net::Ipv4Addr;
+
+  fn write(format: Format) -> String {
+    let data = packet(&[FlowRecord {
+      source_addr: Ipv4Addr::new(192, 0, 2, 1),
+      destination_addr: Ipv4Addr::new(198, 51, 100, 2),
+      next_hop: Ipv4Addr::new(203, 0, 113, 3),
+      packets: 4,
+      bytes: 1708,
+      source_port: 49233,
+      destination_port: 443,
+      protocol: 6,
+      ..FlowRecord::default()
+    }]);
+    let set = FlowSet::new(&data).unwrap();
+    let record = set.records().next().unwrap();
+    let mut out = Vec::new();
+    let exporter = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    format
+      .write(&mut out, exporter, &set.header(), &record)
+      .unwrap();
+    String::from_utf8(out).unwrap()
+  }
+
+  #[test]
+  fn format_from_str_should_parse_names() {
+    assert_eq!("text".parse(), Ok(Format::Text));
+    assert_eq!("json".parse(), Ok(Format::Json));
+    assert_eq!(
+      "xml".parse::<Format>(),
+      Err(ParseFormatError("xml".to_string()))
+    );
+  }
+
+  #[test]
+  fn format_text_should_write_one_line() {
+    assert_eq!(
+      write(Format::Text),
+      "1544476581.000000005 127.0.0.1 192.0.2.1:49233 > 198.51.100.2:443 proto 6 packets 4 bytes 1708\n"
+    );
+  }
+
+  #[test]
+  fn format_json_should_write_one_object() {
+    assert_eq!(
+      write(Format::Json),
+      concat!(
+        "{\"exporter\":\"127.0.0.1\",\"unix_secs\":1544476581,\"unix_nsecs\":5,",
+        "\"sys_uptime_msecs\":581000,\"sequence_number\":7,\"engine_type\":0,",
+        "\"engine_id\":0,\"sampling\":0,\"source_addr\":\"192.0.2.1\",",
+        "\"destination_addr\":\"198.51.100.2\",\"next_hop\":\"203.0.113.3\",\"input\":0,",
+        "\"output\":0,\"packets\":4,\"bytes\":1708,\"first_packet_sys_uptime\":0,",
+        "\"last_packet_sys_uptime\":0,\"source_port\":49233,\"destination_port\":443,",
+        "\"tcp_flags\":0,\"protocol\":6,\"type_of_service\":0,\"source_as\":0,",
+        "\"destination_as\":0,\"source_mask\":0,\"destination_mask\":0}\n"
+      )
+    );
+  }
+}
@@ -0,0 +1,224 @@
+use crate::{FlowSink, Message};
+use rdkafka::config::ClientConfig;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::producer::{
+  BaseProducer, BaseRecord, DeliveryResult, Producer, ProducerContext, PurgeConfig,
+};
+use rdkafka::ClientContext;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Keeps the first delivery error reported since it was last taken
+#[derive(Default)]
+struct DeliveryContext {
+  error: Mutex<Option<KafkaError>>,
+}
+
+impl ClientContext for DeliveryContext {}
+
+impl ProducerContext for DeliveryContext {
+  type DeliveryOpaque = ();
+
+  fn delivery(&self, result: &DeliveryResult<'_>, _: ()) {
+    if let Err((error, _)) = result {
+      let mut first = self.error.lock().unwrap();
+      if first.is_none() {
+        *first = Some(error.clone());
+      }
+    }
+  }
+}
+
+/// Publishes messages to a Kafka topic, partitioned by their key
+///
+/// A batch is acknowledged once every message of it was acknowledged by the
+/// brokers, as configured by `acks`, which librdkafka defaults to `all`.
+pub struct KafkaSink {
+  producer: BaseProducer<DeliveryContext>,
+  topic: String,
+  timeout: Duration,
+}
+
+impl fmt::Debug for KafkaSink {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("KafkaSink")
+      .field("topic", &self.topic)
+      .field("timeout", &self.timeout)
+      .finish()
+  }
+}
+
+impl KafkaSink {
+  /// A sink publishing to `topic` of the cluster of `brokers`, a
+  /// comma-separated list of `host:port`
+  pub fn new(brokers: &str, topic: &str) -> Result<KafkaSink, KafkaError> {
+    let mut config = ClientConfig::new();
+    config.set("bootstrap.servers", brokers);
+    KafkaSink::from_config(&config, topic)
+  }
+
+  /// A sink publishing to `topic` with a producer of `config`, to set
+  /// compression, authentication and such
+  pub fn from_config(config: &ClientConfig, topic: &str) -> Result<KafkaSink, KafkaError> {
+    Ok(KafkaSink {
+      producer: config.create_with_context(DeliveryContext::default())?,
+      topic: topic.to_string(),
+      timeout: Duration::from_secs(30),
+    })
+  }
+
+  /// Gives up on a batch not acknowledged within `timeout`, 30 seconds by
+  /// default
+  pub fn timeout(mut self, timeout: Duration) -> KafkaSink {
+    self.timeout = timeout;
+    self
+  }
+
+  /// The underlying producer
+  pub fn producer(&self) -> &BaseProducer<impl ProducerContext> {
+    &self.producer
+  }
+
+  fn enqueue(&self, message: &Message) -> Result<(), KafkaError> {
+    loop {
+      let record = BaseRecord::to(&self.topic)
+        .key(&message.key)
+        .payload(&message.payload);
+      match self.producer.send(record) {
+        Ok(()) => return Ok(()),
+        // Waits for deliveries to make room in the queue of the producer
+        Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), _)) => {
+          self.producer.poll(Duration::from_millis(100))
+        }
+        Err((error, _)) => return Err(error),
+      }
+    }
+  }
+}
+
+impl FlowSink for KafkaSink {
+  type Error = KafkaError;
+
+  fn send(&mut self, messages: &[Message]) -> Result<(), KafkaError> {
+    let result = messages
+      .iter()
+      .try_for_each(|message| self.enqueue(message))
+      .and_then(|()| self.producer.flush(self.timeout))
+      .and_then(
+        |()| match self.producer.context().error.lock().unwrap().take() {
+          Some(error) => Err(error),
+          None => Ok(()),
+        },
+      );
+    if result.is_err() {
+      // Drops what is left of the batch, which is sent again as a whole
+      self
+        .producer
+        .purge(PurgeConfig::default().queue().inflight());
+      self.producer.poll(Duration::from_secs(0));
+      self.producer.context().error.lock().unwrap().take();
+    }
+    result
+  }
+
+  fn is_retriable(&self, error: &KafkaError) -> bool {
+    !matches!(
+      error.rdkafka_error_code(),
+      Some(RDKafkaErrorCode::MessageSizeTooLarge)
+        | Some(RDKafkaErrorCode::InvalidMessageSize)
+        | Some(RDKafkaErrorCode::UnknownTopic)
+        | Some(RDKafkaErrorCode::TopicAuthorizationFailed)
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::packet;
+  use crate::{Key, SinkWriter};
+  use netflow::v5::raw::FlowSet;
+  use netflow::v5::FlowRecord;
+  use rdkafka::consumer::{BaseConsumer, Consumer};
+  use rdkafka::mocking::MockCluster;
+  use rdkafka::Message as _;
+  use std::collections::HashMap;
+  use std::net::{IpAddr, Ipv4Addr};
+  use std::time::Instant;
+
+  fn consume(brokers: &str, topic: &str, count: usize) -> Vec<(Vec<u8>, i32)> {
+    let consumer: BaseConsumer = ClientConfig::new()
+      .set("bootstrap.servers", brokers)
+      .set("group.id", "netflow-collector-tests")
+      .set("auto.offset.reset", "earliest")
+      .create()
+      .unwrap();
+    consumer.subscribe(&[topic]).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let mut messages = Vec::new();
+    while messages.len() < count && Instant::now() < deadline {
+      if let Some(message) = consumer.poll(Duration::from_millis(100)) {
+        let message = message.unwrap();
+        messages.push((message.key().unwrap().to_vec(), message.partition()));
+      }
+    }
+    messages
+  }
+
+  #[test]
+  fn kafka_sink_should_publish_keyed_messages() {
+    let cluster = MockCluster::new(3).unwrap();
+    cluster.create_topic("flows", 4, 1).unwrap();
+    let brokers = cluster.bootstrap_servers();
+    let records = (0..20)
+      .map(|port| FlowRecord {
+        source_port: port,
+        ..FlowRecord::default()
+      })
+      .collect::<Vec<_>>();
+    let data = packet(&records);
+    let set = FlowSet::new(&data).unwrap();
+    let exporter = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+    let sink = KafkaSink::new(&brokers, "flows").unwrap();
+    let mut writer = SinkWriter::new(sink).key(Key::FiveTuple).batch_size(8);
+    writer.write_set(exporter, &set).unwrap();
+    writer.write_set(exporter, &set).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.delivered(), 40);
+
+    // Messages of the same flow land in the same partition
+    let messages = consume(&brokers, "flows", 40);
+    assert_eq!(messages.len(), 40);
+    let mut partitions = HashMap::new();
+    for (key, partition) in messages {
+      assert_eq!(*partitions.entry(key).or_insert(partition), partition);
+    }
+    assert_eq!(partitions.len(), 20);
+    let record = set.record(3).unwrap();
+    assert!(partitions.contains_key(&Key::FiveTuple.key(exporter, &record)));
+  }
+
+  #[test]
+  fn kafka_sink_should_report_undelivered_batches() {
+    let cluster = MockCluster::new(1).unwrap();
+    cluster.create_topic("flows", 1, 1).unwrap();
+    cluster.broker_down(1).unwrap();
+    let mut sink = KafkaSink::new(&cluster.bootstrap_servers(), "flows")
+      .unwrap()
+      .timeout(Duration::from_millis(500));
+    let messages = [Message {
+      key: b"192.0.2.1".to_vec(),
+      payload: b"{}".to_vec(),
+    }];
+
+    let error = sink.send(&messages).unwrap_err();
+    assert!(sink.is_retriable(&error));
+    assert_eq!(sink.producer().in_flight_count(), 0);
+
+    cluster.broker_up(1).unwrap();
+    sink = sink.timeout(Duration::from_secs(30));
+    sink.send(&messages).unwrap();
+  }
+}
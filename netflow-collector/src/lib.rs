@@ -0,0 +1,47 @@
+//! A Netflow collector listening for export datagrams over UDP
+//!
+//! Each datagram is decoded as a Netflow v5 packet and every flow record is
+//! written as one line, either as text or as JSON. Datagrams that fail to
+//! decode are counted per exporter.
+//!
+//! `CsvWriter` and `JsonLinesWriter` write records with a chosen set of
+//! columns, rendering addresses, protocols and TCP flags as numbers or in
+//! human-readable form.
+//!
+//! `SinkWriter` sends records by batches to a `FlowSink`, retrying failed
+//! batches and applying backpressure when the sink falls behind.
+//!
+//! At high rates, `Collector::receive_batch` receives many datagrams into an
+//! `Arena` at once, with a single `recvmmsg` system call on Linux.
+//!
+//! # Features
+//!
+//! * `async`: adds `FlowStream`, a `futures::Stream` of decoded packets
+//!   received on a tokio UDP socket
+//! * `kafka`: adds `KafkaSink`, a `FlowSink` publishing to a Kafka topic
+
+extern crate netflow;
+
+mod batch;
+mod collector;
+mod format;
+#[cfg(feature = "kafka")]
+mod kafka;
+mod sink;
+mod stats;
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(test)]
+mod testing;
+mod writer;
+
+pub use batch::{Arena, Received, MAX_PACKET_LEN};
+pub use collector::{Collector, Datagram};
+pub use format::{Format, ParseFormatError};
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaSink;
+pub use sink::{Backpressure, FlowSink, Key, Message, SinkError, SinkWriter};
+pub use stats::{ExporterStats, Stats};
+#[cfg(feature = "async")]
+pub use stream::{FlowPacket, FlowStream, StreamError};
+pub use writer::{Column, CsvWriter, JsonLinesWriter, ParseColumnError, Rendering};
@@ -0,0 +1,85 @@
+extern crate netflow_collector;
+
+use netflow_collector::{Arena, Collector, Datagram, Format};
+use std::process;
+
+const USAGE: &str = "Usage: netflow-collector [--listen ADDR] [--format text|json] [--batch SLOTS]
+
+Listens for Netflow v5 datagrams and prints one line per flow record.
+
+Options:
+  --listen ADDR    UDP address to listen on [default: 0.0.0.0:2055]
+  --format FORMAT  Output format, text or json [default: text]
+  --batch SLOTS    Datagrams received per system call, on Linux [default: 64]
+  --help           Print this message";
+
+struct Options {
+  listen: String,
+  format: Format,
+  batch: usize,
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+  let mut options = Options {
+    listen: String::from("0.0.0.0:2055"),
+    format: Format::Text,
+    batch: 64,
+  };
+  while let Some(arg) = args.next() {
+    let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+    match arg.as_str() {
+      "--listen" => options.listen = value()?,
+      "--format" => options.format = value()?.parse().map_err(|e| format!("{}", e))?,
+      "--batch" => {
+        options.batch = match value()?.parse() {
+          Ok(0) | Err(_) => return Err(String::from("--batch expects a positive number")),
+          Ok(batch) => batch,
+        }
+      }
+      "--help" => {
+        println!("{}", USAGE);
+        process::exit(0);
+      }
+      _ => return Err(format!("unexpected argument {}", arg)),
+    }
+  }
+  Ok(options)
+}
+
+fn main() {
+  let options = parse_args(std::env::args().skip(1)).unwrap_or_else(|error| {
+    eprintln!("{}\n\n{}", error, USAGE);
+    process::exit(2);
+  });
+
+  let mut collector = Collector::bind(&options.listen, options.format).unwrap_or_else(|error| {
+    eprintln!("cannot listen on {}: {}", options.listen, error);
+    process::exit(1);
+  });
+
+  let mut arena = Arena::new(options.batch);
+  let stdout = std::io::stdout();
+  let mut out = stdout.lock();
+  loop {
+    let datagrams = collector
+      .receive_batch(&mut arena)
+      .and_then(|_| collector.handle_batch(&arena, &mut out))
+      .unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+      });
+    for datagram in datagrams {
+      if let Datagram::Malformed {
+        exporter,
+        error,
+        stats,
+      } = datagram
+      {
+        eprintln!(
+          "malformed datagram from {} ({} so far): {}",
+          exporter, stats.malformed, error
+        );
+      }
+    }
+  }
+}
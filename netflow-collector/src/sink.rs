@@ -0,0 +1,676 @@
+use crate::Format;
+use netflow::v5::raw::{FlowSet, Header, Record};
+use std::fmt;
+use std::net::IpAddr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest wait between two attempts at sending a batch
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// A serialized flow record, with the key its destination partitions by
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+  pub key: Vec<u8>,
+  pub payload: Vec<u8>,
+}
+
+/// A destination of serialized flow records, such as a message broker
+pub trait FlowSink {
+  type Error: std::error::Error + 'static;
+
+  /// Delivers `messages`, only returning once the destination acknowledged
+  /// every one of them
+  ///
+  /// After an error, some messages may have been delivered anyway: they are
+  /// sent again with the rest of the batch, so that each is delivered at
+  /// least once.
+  fn send(&mut self, messages: &[Message]) -> Result<(), Self::Error>;
+
+  /// Whether sending again may succeed after `error`, as after a timeout
+  fn is_retriable(&self, error: &Self::Error) -> bool {
+    let _ = error;
+    true
+  }
+}
+
+/// What messages are keyed by, so that those of the same key go to the same
+/// partition and stay in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Key {
+  /// Address of the exporter, as text
+  #[default]
+  Exporter,
+  /// FNV-1a hash of the addresses, ports and protocol of the flow, as 8 big
+  /// endian bytes, the same whichever exporter reported it
+  FiveTuple,
+}
+
+impl Key {
+  /// Key of `record`, exported by `exporter`
+  pub fn key(self, exporter: IpAddr, record: &Record<'_>) -> Vec<u8> {
+    match self {
+      Key::Exporter => exporter.to_string().into_bytes(),
+      Key::FiveTuple => {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut write = |bytes: &[u8]| {
+          for &byte in bytes {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+          }
+        };
+        for addr in [record.source_addr(), record.destination_addr()].iter() {
+          match addr {
+            IpAddr::V4(addr) => write(&addr.octets()),
+            IpAddr::V6(addr) => write(&addr.octets()),
+          }
+        }
+        write(&record.source_port().to_be_bytes());
+        write(&record.destination_port().to_be_bytes());
+        write(&[record.protocol()]);
+        hash.to_be_bytes().to_vec()
+      }
+    }
+  }
+}
+
+/// What `SinkWriter` does with new records when its buffer is full, which
+/// only happens while the sink fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backpressure {
+  /// Waits for the oldest batch to be delivered, retrying for as long as it
+  /// takes, which stalls the caller and lets the socket buffer absorb new
+  /// datagrams
+  #[default]
+  Block,
+  /// Drops the new records and returns `SinkError::Full`
+  Reject,
+}
+
+/// Errors returned by `SinkWriter`
+#[derive(Debug)]
+pub enum SinkError<E> {
+  /// The buffer was full, and `dropped` records were dropped
+  Full { dropped: usize },
+  /// The sink failed to deliver a batch, which stays buffered and is sent
+  /// again by the next call
+  Send(E),
+}
+
+impl<E: fmt::Display> fmt::Display for SinkError<E> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SinkError::Full { dropped } => {
+        write!(f, "sink buffer is full, dropped {} records", dropped)
+      }
+      SinkError::Send(error) => write!(f, "cannot send flows: {}", error),
+    }
+  }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for SinkError<E> {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      SinkError::Full { .. } => None,
+      SinkError::Send(error) => Some(error),
+    }
+  }
+}
+
+/// Hook receiving the messages a sink refused for good
+type DeadLetter<E> = Box<dyn FnMut(Message, &E)>;
+
+/// Serializes flow records into messages and sends them to a `FlowSink` by
+/// batches
+///
+/// A batch is sent once `batch_size` messages are buffered, or by `poll` once
+/// the oldest one waited for `linger`. A failed send is retried up to
+/// `retries` times, waiting twice as long before each attempt; messages of a
+/// batch that still fails stay buffered, and are sent again by the next call.
+/// A batch failing with an error that is not retriable is sent again one
+/// message at a time, and the messages the sink refuses are dropped, counted
+/// by `failed` and handed to the `dead_letter` hook if any, so that they do
+/// not hold back the others. Up to `capacity` messages are buffered, beyond
+/// which `backpressure` applies. Nothing is sent when the writer is dropped:
+/// call `flush` first.
+pub struct SinkWriter<S: FlowSink> {
+  sink: S,
+  format: Format,
+  key: Key,
+  batch_size: usize,
+  linger: Duration,
+  capacity: usize,
+  retries: u32,
+  backoff: Duration,
+  backpressure: Backpressure,
+  buffer: Vec<Message>,
+  oldest: Option<Instant>,
+  /// Number of messages at the head of the buffer to send one at a time
+  isolated: usize,
+  dead_letter: Option<DeadLetter<S::Error>>,
+  delivered: u64,
+  dropped: u64,
+  failed: u64,
+}
+
+impl<S: FlowSink + fmt::Debug> fmt::Debug for SinkWriter<S> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("SinkWriter")
+      .field("sink", &self.sink)
+      .field("format", &self.format)
+      .field("key", &self.key)
+      .field("buffered", &self.buffer.len())
+      .field("delivered", &self.delivered)
+      .field("dropped", &self.dropped)
+      .field("failed", &self.failed)
+      .finish()
+  }
+}
+
+impl<S: FlowSink> SinkWriter<S> {
+  /// A writer of JSON records keyed by exporter, by batches of 1000 lingering
+  /// up to 500 milliseconds, retried 5 times from 100 milliseconds on, and
+  /// blocking beyond 100000 buffered records
+  pub fn new(sink: S) -> SinkWriter<S> {
+    SinkWriter {
+      sink,
+      format: Format::Json,
+      key: Key::Exporter,
+      batch_size: 1000,
+      linger: Duration::from_millis(500),
+      capacity: 100_000,
+      retries: 5,
+      backoff: Duration::from_millis(100),
+      backpressure: Backpressure::Block,
+      buffer: Vec::new(),
+      oldest: None,
+      isolated: 0,
+      dead_letter: None,
+      delivered: 0,
+      dropped: 0,
+      failed: 0,
+    }
+  }
+
+  /// Serializes records as `format`, without the trailing newline
+  pub fn format(mut self, format: Format) -> SinkWriter<S> {
+    self.format = format;
+    self
+  }
+
+  /// Keys messages by `key`
+  pub fn key(mut self, key: Key) -> SinkWriter<S> {
+    self.key = key;
+    self
+  }
+
+  /// Sends messages by batches of `batch_size`, at least one
+  pub fn batch_size(mut self, batch_size: usize) -> SinkWriter<S> {
+    self.batch_size = batch_size.max(1);
+    self
+  }
+
+  /// Longest time a message waits for its batch to fill before `poll` sends it
+  pub fn linger(mut self, linger: Duration) -> SinkWriter<S> {
+    self.linger = linger;
+    self
+  }
+
+  /// Buffers up to `capacity` messages
+  pub fn capacity(mut self, capacity: usize) -> SinkWriter<S> {
+    self.capacity = capacity;
+    self
+  }
+
+  /// Retries a failed send `retries` times, waiting `backoff` before the
+  /// first retry and twice as long before each next one, up to 30 seconds
+  pub fn retries(mut self, retries: u32, backoff: Duration) -> SinkWriter<S> {
+    self.retries = retries;
+    self.backoff = backoff;
+    self
+  }
+
+  /// Applies `backpressure` when the buffer is full
+  pub fn backpressure(mut self, backpressure: Backpressure) -> SinkWriter<S> {
+    self.backpressure = backpressure;
+    self
+  }
+
+  /// Hands the messages the sink refuses for good to `dead_letter`, with the
+  /// error it refused them with
+  pub fn dead_letter<F: FnMut(Message, &S::Error) + 'static>(
+    mut self,
+    dead_letter: F,
+  ) -> SinkWriter<S> {
+    self.dead_letter = Some(Box::new(dead_letter));
+    self
+  }
+
+  /// The sink messages are sent to
+  pub fn sink(&self) -> &S {
+    &self.sink
+  }
+
+  /// Number of messages waiting to be sent
+  pub fn buffered(&self) -> usize {
+    self.buffer.len()
+  }
+
+  /// Number of messages the sink acknowledged so far
+  pub fn delivered(&self) -> u64 {
+    self.delivered
+  }
+
+  /// Number of records dropped so far because the buffer was full
+  pub fn dropped(&self) -> u64 {
+    self.dropped
+  }
+
+  /// Number of messages dropped so far because the sink refused them with an
+  /// error that is not retriable
+  pub fn failed(&self) -> u64 {
+    self.failed
+  }
+
+  /// Buffers every record of `set`, exported by `exporter`, and sends the
+  /// batches filled meanwhile
+  ///
+  /// When the buffer cannot hold them all, either none is buffered or the
+  /// call waits for enough room, depending on `backpressure`.
+  pub fn write_set(
+    &mut self,
+    exporter: IpAddr,
+    set: &FlowSet<'_>,
+  ) -> Result<(), SinkError<S::Error>> {
+    self.reserve(set.len())?;
+    let header = set.header();
+    for record in set {
+      let message = self.message(exporter, &header, &record);
+      self.buffer(message);
+    }
+    self.send_full_batches()
+  }
+
+  /// Buffers `record`, exported by `exporter` in the packet of `header`, and
+  /// sends the batch it fills
+  pub fn write_record(
+    &mut self,
+    exporter: IpAddr,
+    header: &Header<'_>,
+    record: &Record<'_>,
+  ) -> Result<(), SinkError<S::Error>> {
+    self.reserve(1)?;
+    let message = self.message(exporter, header, record);
+    self.buffer(message);
+    self.send_full_batches()
+  }
+
+  /// Sends the buffered messages if the oldest one waited for `linger`, to
+  /// be called regularly, such as whenever the socket is idle
+  pub fn poll(&mut self) -> Result<(), SinkError<S::Error>> {
+    match self.oldest {
+      Some(oldest) if oldest.elapsed() >= self.linger => self.flush(),
+      _ => Ok(()),
+    }
+  }
+
+  /// Sends every buffered message
+  pub fn flush(&mut self) -> Result<(), SinkError<S::Error>> {
+    while !self.buffer.is_empty() {
+      self
+        .send_batch(Some(self.retries))
+        .map_err(SinkError::Send)?;
+    }
+    Ok(())
+  }
+
+  fn message(&self, exporter: IpAddr, header: &Header<'_>, record: &Record<'_>) -> Message {
+    let mut payload = Vec::new();
+    // Writing to a vector cannot fail
+    let _ = self.format.write(&mut payload, exporter, header, record);
+    payload.pop();
+    Message {
+      key: self.key.key(exporter, record),
+      payload,
+    }
+  }
+
+  fn buffer(&mut self, message: Message) {
+    if self.buffer.is_empty() {
+      self.oldest = Some(Instant::now());
+    }
+    self.buffer.push(message);
+  }
+
+  /// Makes room for `count` messages, or drops them
+  fn reserve(&mut self, count: usize) -> Result<(), SinkError<S::Error>> {
+    while !self.buffer.is_empty() && self.buffer.len() + count > self.capacity {
+      let result = match self.backpressure {
+        Backpressure::Block => self.send_batch(None).map_err(SinkError::Send),
+        Backpressure::Reject => Err(SinkError::Full { dropped: count }),
+      };
+      if let Err(error) = result {
+        self.dropped += count as u64;
+        return Err(error);
+      }
+    }
+    Ok(())
+  }
+
+  fn send_full_batches(&mut self) -> Result<(), SinkError<S::Error>> {
+    while self.buffer.len() >= self.batch_size {
+      self
+        .send_batch(Some(self.retries))
+        .map_err(SinkError::Send)?;
+    }
+    Ok(())
+  }
+
+  /// Sends the oldest batch, retrying up to `retries` times, or for as long
+  /// as errors are retriable without limit
+  fn send_batch(&mut self, retries: Option<u32>) -> Result<(), S::Error> {
+    let mut backoff = self.backoff;
+    let mut attempts = 0;
+    loop {
+      let len = match self.isolated {
+        0 => self.batch_size.min(self.buffer.len()),
+        _ => 1,
+      };
+      let error = match self.sink.send(&self.buffer[..len]) {
+        Ok(()) => {
+          self.buffer.drain(..len);
+          self.delivered += len as u64;
+          self.isolated = self.isolated.saturating_sub(len);
+          break;
+        }
+        Err(error) => error,
+      };
+      if !self.sink.is_retriable(&error) {
+        if len > 1 {
+          // Finds the messages the sink refuses by sending them one by one
+          self.isolated = len;
+          continue;
+        }
+        let message = self.buffer.remove(0);
+        self.failed += 1;
+        self.isolated = self.isolated.saturating_sub(1);
+        if let Some(dead_letter) = self.dead_letter.as_mut() {
+          dead_letter(message, &error);
+        }
+        break;
+      }
+      if retries.is_some_and(|retries| attempts >= retries) {
+        return Err(error);
+      }
+      attempts += 1;
+      thread::sleep(backoff);
+      backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+
+    if self.buffer.is_empty() {
+      self.oldest = None;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::packet;
+  use netflow::v5::FlowRecord;
+  use std::net::Ipv4Addr;
+
+  const EXPORTER: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+  #[derive(Debug, PartialEq)]
+  struct MockError {
+    retriable: bool,
+  }
+
+  impl fmt::Display for MockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      write!(f, "mock failure")
+    }
+  }
+
+  impl std::error::Error for MockError {}
+
+  /// Sink failing the next `failures` sends, and refusing for good batches
+  /// with a payload containing `refused`
+  #[derive(Debug, Default)]
+  struct MockSink {
+    batches: Vec<Vec<Message>>,
+    attempts: usize,
+    failures: usize,
+    retriable: bool,
+    refused: Option<&'static str>,
+  }
+
+  impl FlowSink for MockSink {
+    type Error = MockError;
+
+    fn send(&mut self, messages: &[Message]) -> Result<(), MockError> {
+      self.attempts += 1;
+      if let Some(refused) = self.refused {
+        let refused = refused.as_bytes();
+        let poisoned = messages.iter().any(|message| {
+          message
+            .payload
+            .windows(refused.len())
+            .any(|part| part == refused)
+        });
+        if poisoned {
+          return Err(MockError { retriable: false });
+        }
+      }
+      if self.failures > 0 {
+        self.failures -= 1;
+        return Err(MockError {
+          retriable: self.retriable,
+        });
+      }
+      self.batches.push(messages.to_vec());
+      Ok(())
+    }
+
+    fn is_retriable(&self, error: &MockError) -> bool {
+      error.retriable
+    }
+  }
+
+  fn failing(failures: usize, retriable: bool) -> MockSink {
+    MockSink {
+      failures,
+      retriable,
+      ..MockSink::default()
+    }
+  }
+
+  /// `count` DNS queries, from source ports 1000 on
+  fn dns_records(count: u16) -> Vec<FlowRecord> {
+    (0..count)
+      .map(|i| FlowRecord {
+        source_addr: Ipv4Addr::new(198, 51, 100, 1),
+        destination_addr: Ipv4Addr::new(203, 0, 113, 2),
+        source_port: 1000 + i,
+        destination_port: 53,
+        protocol: 17,
+        ..FlowRecord::default()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn writer_should_send_full_batches() {
+    let data = packet(&dns_records(5));
+    let set = FlowSet::new(&data).unwrap();
+    let mut writer = SinkWriter::new(MockSink::default())
+      .format(Format::Text)
+      .batch_size(2);
+    writer.write_set(EXPORTER, &set).unwrap();
+
+    let sizes: Vec<usize> = writer.sink().batches.iter().map(Vec::len).collect();
+    assert_eq!(sizes, vec![2, 2]);
+    assert_eq!(writer.buffered(), 1);
+    writer.flush().unwrap();
+    assert_eq!(writer.delivered(), 5);
+    let message = &writer.sink().batches[0][0];
+    assert_eq!(message.key, b"192.0.2.1");
+    assert_eq!(
+      message.payload,
+      b"1544476581.000000005 192.0.2.1 198.51.100.1:1000 > 203.0.113.2:53 proto 17 packets 0 bytes 0"
+    );
+  }
+
+  #[test]
+  fn writer_should_send_lingering_messages_when_polled() {
+    let data = packet(&dns_records(1));
+    let set = FlowSet::new(&data).unwrap();
+    let mut writer = SinkWriter::new(MockSink::default()).linger(Duration::from_secs(3600));
+    writer.write_set(EXPORTER, &set).unwrap();
+    writer.poll().unwrap();
+    assert_eq!(writer.buffered(), 1);
+
+    let mut writer = SinkWriter::new(MockSink::default()).linger(Duration::from_secs(0));
+    writer.write_set(EXPORTER, &set).unwrap();
+    writer.poll().unwrap();
+    assert_eq!(writer.buffered(), 0);
+    assert_eq!(writer.sink().batches.len(), 1);
+  }
+
+  #[test]
+  fn writer_should_retry_failed_sends() {
+    let data = packet(&dns_records(2));
+    let set = FlowSet::new(&data).unwrap();
+    let mut writer = SinkWriter::new(failing(3, true))
+      .batch_size(2)
+      .retries(3, Duration::from_millis(0));
+    writer.write_set(EXPORTER, &set).unwrap();
+
+    assert_eq!(writer.sink().attempts, 4);
+    assert_eq!(writer.delivered(), 2);
+  }
+
+  #[test]
+  fn writer_should_keep_messages_of_failed_batches() {
+    let data = packet(&dns_records(2));
+    let set = FlowSet::new(&data).unwrap();
+    let mut writer = SinkWriter::new(failing(3, true))
+      .batch_size(2)
+      .retries(1, Duration::from_millis(0));
+    assert_eq!(
+      writer.write_set(EXPORTER, &set).unwrap_err().to_string(),
+      "cannot send flows: mock failure"
+    );
+    assert_eq!(writer.buffered(), 2);
+
+    writer.flush().unwrap();
+    assert_eq!(writer.delivered(), 2);
+    assert_eq!(writer.sink().batches[0].len(), 2);
+  }
+
+  #[test]
+  fn writer_should_not_retry_permanent_errors() {
+    let data = packet(&dns_records(1));
+    let set = FlowSet::new(&data).unwrap();
+    let mut writer = SinkWriter::new(failing(1, false)).retries(5, Duration::from_millis(0));
+    writer.write_set(EXPORTER, &set).unwrap();
+    writer.flush().unwrap();
+
+    assert_eq!(writer.sink().attempts, 1);
+    assert_eq!(writer.buffered(), 0);
+    assert_eq!(writer.failed(), 1);
+  }
+
+  #[test]
+  fn writer_should_drop_refused_messages_only() {
+    let data = packet(&dns_records(5));
+    let set = FlowSet::new(&data).unwrap();
+    let sink = MockSink {
+      refused: Some(":1001 "),
+      ..MockSink::default()
+    };
+    let refused = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let dead_letters = refused.clone();
+    let mut writer = SinkWriter::new(sink)
+      .format(Format::Text)
+      .batch_size(4)
+      .dead_letter(move |message, error: &MockError| {
+        assert!(!error.retriable);
+        dead_letters.borrow_mut().push(message);
+      });
+    writer.write_set(EXPORTER, &set).unwrap();
+    writer.write_set(EXPORTER, &set).unwrap();
+    writer.flush().unwrap();
+
+    assert_eq!(writer.buffered(), 0);
+    assert_eq!(writer.delivered(), 8);
+    assert_eq!(writer.failed(), 2);
+    assert_eq!(refused.borrow().len(), 2);
+    for message in refused.borrow().iter() {
+      assert!(String::from_utf8_lossy(&message.payload).contains(":1001 "));
+    }
+    // Messages are sent by batches again once the refused ones are dropped
+    assert_eq!(writer.sink().batches.last().unwrap().len(), 2);
+  }
+
+  #[test]
+  fn writer_should_reject_records_when_full() {
+    let data = packet(&dns_records(3));
+    let set = FlowSet::new(&data).unwrap();
+    let mut writer = SinkWriter::new(failing(usize::MAX, true))
+      .batch_size(10)
+      .capacity(4)
+      .backpressure(Backpressure::Reject);
+    writer.write_set(EXPORTER, &set).unwrap();
+    assert!(matches!(
+      writer.write_set(EXPORTER, &set),
+      Err(SinkError::Full { dropped: 3 })
+    ));
+
+    assert_eq!(writer.buffered(), 3);
+    assert_eq!(writer.dropped(), 3);
+    assert_eq!(writer.sink().attempts, 0);
+  }
+
+  #[test]
+  fn writer_should_block_until_room_when_full() {
+    let data = packet(&dns_records(3));
+    let set = FlowSet::new(&data).unwrap();
+    let mut writer = SinkWriter::new(failing(10, true))
+      .batch_size(10)
+      .capacity(4)
+      .retries(0, Duration::from_millis(0));
+    writer.write_set(EXPORTER, &set).unwrap();
+    writer.write_set(EXPORTER, &set).unwrap();
+
+    // Blocking retries beyond the configured retries
+    assert_eq!(writer.sink().attempts, 11);
+    assert_eq!(writer.delivered(), 3);
+    assert_eq!(writer.buffered(), 3);
+    assert_eq!(writer.dropped(), 0);
+  }
+
+  #[test]
+  fn five_tuple_keys_should_ignore_exporter() {
+    let data = packet(&dns_records(2));
+    let set = FlowSet::new(&data).unwrap();
+    let first = set.record(0).unwrap();
+    let other = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2));
+
+    assert_eq!(
+      Key::FiveTuple.key(EXPORTER, &first),
+      Key::FiveTuple.key(other, &first)
+    );
+    assert_ne!(
+      Key::FiveTuple.key(EXPORTER, &first),
+      Key::FiveTuple.key(EXPORTER, &set.record(1).unwrap())
+    );
+    assert_eq!(Key::FiveTuple.key(EXPORTER, &first).len(), 8);
+    assert_ne!(
+      Key::Exporter.key(EXPORTER, &first),
+      Key::Exporter.key(other, &first)
+    );
+  }
+}
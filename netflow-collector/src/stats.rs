@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+/// Counters of the datagrams received from one exporter
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExporterStats {
+  /// Datagrams decoded successfully
+  pub packets: u64,
+  /// Flow records of the decoded datagrams
+  pub records: u64,
+  /// Datagrams that failed to decode
+  pub malformed: u64,
+}
+
+/// Counters of the datagrams received, per exporter address
+#[derive(Debug, Default, Clone)]
+pub struct Stats {
+  exporters: HashMap<IpAddr, ExporterStats>,
+}
+
+impl Stats {
+  /// Counters of the given exporter, if anything was received from it
+  pub fn get(&self, exporter: IpAddr) -> Option<&ExporterStats> {
+    self.exporters.get(&exporter)
+  }
+
+  /// Counters of every exporter anything was received from
+  pub fn iter(&self) -> impl Iterator<Item = (&IpAddr, &ExporterStats)> {
+    self.exporters.iter()
+  }
+
+  /// Number of exporters anything was received from
+  pub fn len(&self) -> usize {
+    self.exporters.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.exporters.is_empty()
+  }
+
+  pub(crate) fn decoded(&mut self, exporter: IpAddr, records: usize) -> &ExporterStats {
+    let stats = self.exporters.entry(exporter).or_default();
+    stats.packets += 1;
+    stats.records += records as u64;
+    stats
+  }
+
+  pub(crate) fn malformed(&mut self, exporter: IpAddr) -> &ExporterStats {
+    let stats = self.exporters.entry(exporter).or_default();
+    stats.malformed += 1;
+    stats
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::net::Ipv4Addr;
+
+  #[test]
+  fn stats_should_count_per_exporter() {
+    let first = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+    let second = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2));
+    let mut stats = Stats::default();
+
+    stats.decoded(first, 29);
+    stats.decoded(first, 1);
+    stats.malformed(first);
+    stats.malformed(second);
+
+    assert_eq!(stats.len(), 2);
+    assert_eq!(
+      stats.get(first),
+      Some(&ExporterStats {
+        packets: 2,
+        records: 30,
+        malformed: 1,
+      })
+    );
+    assert_eq!(
+      stats.get(second),
+      Some(&ExporterStats {
+        packets: 0,
+        records: 0,
+        malformed: 1,
+      })
+    );
+  }
+}
@@ -0,0 +1,204 @@
+use crate::collector::MAX_DATAGRAM_LEN;
+use crate::Stats;
+use futures_core::Stream;
+use netflow::v5::raw::FlowSet;
+use netflow::v5::{FlowRecord, PacketHeader};
+use netflow::Error;
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::ReadBuf;
+use tokio::net::{ToSocketAddrs, UdpSocket};
+
+/// A Netflow v5 packet decoded by a `FlowStream`, with owned records
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowPacket {
+  pub exporter: SocketAddr,
+  pub header: PacketHeader,
+  pub records: Vec<FlowRecord>,
+}
+
+/// Errors yielded by a `FlowStream`, none of which ends the stream
+#[derive(Debug)]
+pub enum StreamError {
+  /// Receiving from the socket failed
+  Io(io::Error),
+  /// A datagram failed to decode
+  Malformed { exporter: SocketAddr, error: Error },
+}
+
+impl fmt::Display for StreamError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      StreamError::Io(error) => write!(f, "receive failed: {}", error),
+      StreamError::Malformed { exporter, error } => {
+        write!(f, "malformed datagram from {}: {}", exporter, error)
+      }
+    }
+  }
+}
+
+impl std::error::Error for StreamError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      StreamError::Io(error) => Some(error),
+      StreamError::Malformed { error, .. } => Some(error),
+    }
+  }
+}
+
+impl From<io::Error> for StreamError {
+  fn from(error: io::Error) -> StreamError {
+    StreamError::Io(error)
+  }
+}
+
+/// Receives Netflow v5 datagrams on a tokio UDP socket and yields them as
+/// decoded packets
+///
+/// Every datagram is received into the same buffer and decoded in place with
+/// `FlowSet::new`, only its records are copied out. The stream never ends:
+/// malformed datagrams and socket errors are yielded as errors. Decoded and
+/// malformed datagrams are counted in `stats`, per exporter; socket errors
+/// are not, as they have no exporter.
+pub struct FlowStream {
+  socket: UdpSocket,
+  stats: Stats,
+  buffer: Vec<u8>,
+}
+
+impl fmt::Debug for FlowStream {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("FlowStream")
+      .field("socket", &self.socket)
+      .field("stats", &self.stats)
+      .finish()
+  }
+}
+
+impl FlowStream {
+  /// Binds a stream to the given local address
+  pub async fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<FlowStream> {
+    Ok(FlowStream::new(UdpSocket::bind(addr).await?))
+  }
+
+  /// Receives from an already bound socket
+  pub fn new(socket: UdpSocket) -> FlowStream {
+    FlowStream {
+      socket,
+      stats: Stats::default(),
+      buffer: vec![0; MAX_DATAGRAM_LEN],
+    }
+  }
+
+  /// Local address the stream listens on
+  pub fn local_addr(&self) -> io::Result<SocketAddr> {
+    self.socket.local_addr()
+  }
+
+  /// Counters of the datagrams received so far
+  pub fn stats(&self) -> &Stats {
+    &self.stats
+  }
+
+  /// Gives the socket back
+  pub fn into_inner(self) -> UdpSocket {
+    self.socket
+  }
+}
+
+impl Stream for FlowStream {
+  type Item = Result<FlowPacket, StreamError>;
+
+  fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let this = self.get_mut();
+    let mut buffer = ReadBuf::new(&mut this.buffer);
+    let exporter = match this.socket.poll_recv_from(cx, &mut buffer) {
+      Poll::Pending => return Poll::Pending,
+      // Not counted in `stats`, there is no exporter to count it against
+      Poll::Ready(Err(error)) => return Poll::Ready(Some(Err(error.into()))),
+      Poll::Ready(Ok(exporter)) => exporter,
+    };
+
+    let packet = match FlowSet::new(buffer.filled()) {
+      Ok(set) => {
+        this.stats.decoded(exporter.ip(), set.len());
+        Ok(FlowPacket {
+          exporter,
+          header: PacketHeader::from(&set.header()),
+          records: set
+            .records()
+            .map(|record| FlowRecord::from(&record))
+            .collect(),
+        })
+      }
+      Err(error) => {
+        this.stats.malformed(exporter.ip());
+        Err(StreamError::Malformed { exporter, error })
+      }
+    };
+    Poll::Ready(Some(packet))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{packet, records};
+  use futures_util::StreamExt;
+  use netflow::Protocol;
+
+  #[tokio::test]
+  async fn flow_stream_should_yield_decoded_packets() {
+    let mut stream = FlowStream::bind("127.0.0.1:0").await.unwrap();
+    let exporter = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let exporter_addr = exporter.local_addr().unwrap();
+    let collector_addr = stream.local_addr().unwrap();
+    let big = records(30);
+    let small = records(2);
+    exporter
+      .send_to(&packet(&big), collector_addr)
+      .await
+      .unwrap();
+    exporter
+      .send_to(&[0, 5, 0, 1], collector_addr)
+      .await
+      .unwrap();
+    exporter
+      .send_to(&packet(&small), collector_addr)
+      .await
+      .unwrap();
+
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(first.exporter, exporter_addr);
+    assert_eq!(first.header.count, 30);
+    assert_eq!(first.records, big);
+
+    match stream.next().await.unwrap() {
+      Err(StreamError::Malformed { exporter, error }) => {
+        assert_eq!(exporter, exporter_addr);
+        assert_eq!(
+          error,
+          Error::NotEnoughData {
+            protocol: Protocol::V5,
+            offset: 0,
+            expected: 24,
+            actual: 4,
+          }
+        );
+      }
+      item => panic!("unexpected item {:?}", item),
+    }
+
+    // Decoding the shorter packet must not see leftovers of the longer one
+    let third = stream.next().await.unwrap().unwrap();
+    assert_eq!(third.header.count, 2);
+    assert_eq!(third.header.sequence_number, 7);
+    assert_eq!(third.records, small);
+
+    let stats = stream.stats().get(exporter_addr.ip()).unwrap();
+    assert_eq!((stats.packets, stats.records, stats.malformed), (2, 32, 1));
+  }
+}
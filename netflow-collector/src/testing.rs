@@ -0,0 +1,29 @@
+//! Fixtures shared by the tests of every module
+
+use netflow::v5::{FlowRecord, PacketBuilder};
+use std::net::Ipv4Addr;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Netflow v5 packet of `records` with sequence number 7, exported at
+/// 1544476581.000000005 by a device up since 1544476000
+pub fn packet(records: &[FlowRecord]) -> Vec<u8> {
+  let mut builder =
+    PacketBuilder::new(UNIX_EPOCH + Duration::from_secs(1544476000)).sequence_number(7);
+  for record in records {
+    builder.push(*record).unwrap();
+  }
+  builder
+    .build(UNIX_EPOCH + Duration::new(1544476581, 5))
+    .unwrap()
+}
+
+/// `count` records from 192.0.2.0 on, the `i`th one of `i` packets
+pub fn records(count: u32) -> Vec<FlowRecord> {
+  (0..count)
+    .map(|i| FlowRecord {
+      source_addr: Ipv4Addr::new(192, 0, 2, i as u8),
+      packets: i,
+      ..FlowRecord::default()
+    })
+    .collect()
+}
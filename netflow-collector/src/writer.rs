@@ -0,0 +1,585 @@
+use netflow::v5::raw::{FlowSet, Header, Record};
+use std::fmt;
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// A field of a flow record or of the packet header it came with, written as
+/// one column by `CsvWriter` and as one key by `JsonLinesWriter`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Column {
+  Exporter,
+  UnixSecs,
+  UnixNsecs,
+  SysUptimeMsecs,
+  SequenceNumber,
+  EngineType,
+  EngineId,
+  Sampling,
+  SourceAddr,
+  DestinationAddr,
+  NextHop,
+  Input,
+  Output,
+  Packets,
+  Bytes,
+  FirstPacketSysUptime,
+  LastPacketSysUptime,
+  SourcePort,
+  DestinationPort,
+  TcpFlags,
+  Protocol,
+  TypeOfService,
+  SourceAs,
+  DestinationAs,
+  SourceMask,
+  DestinationMask,
+}
+
+impl Column {
+  /// Every column, in the order of `Format::Json`
+  pub const ALL: [Column; 26] = [
+    Column::Exporter,
+    Column::UnixSecs,
+    Column::UnixNsecs,
+    Column::SysUptimeMsecs,
+    Column::SequenceNumber,
+    Column::EngineType,
+    Column::EngineId,
+    Column::Sampling,
+    Column::SourceAddr,
+    Column::DestinationAddr,
+    Column::NextHop,
+    Column::Input,
+    Column::Output,
+    Column::Packets,
+    Column::Bytes,
+    Column::FirstPacketSysUptime,
+    Column::LastPacketSysUptime,
+    Column::SourcePort,
+    Column::DestinationPort,
+    Column::TcpFlags,
+    Column::Protocol,
+    Column::TypeOfService,
+    Column::SourceAs,
+    Column::DestinationAs,
+    Column::SourceMask,
+    Column::DestinationMask,
+  ];
+
+  /// Name of the column in header rows and JSON keys, the name of the
+  /// accessor it is read with
+  pub fn name(self) -> &'static str {
+    match self {
+      Column::Exporter => "exporter",
+      Column::UnixSecs => "unix_secs",
+      Column::UnixNsecs => "unix_nsecs",
+      Column::SysUptimeMsecs => "sys_uptime_msecs",
+      Column::SequenceNumber => "sequence_number",
+      Column::EngineType => "engine_type",
+      Column::EngineId => "engine_id",
+      Column::Sampling => "sampling",
+      Column::SourceAddr => "source_addr",
+      Column::DestinationAddr => "destination_addr",
+      Column::NextHop => "next_hop",
+      Column::Input => "input",
+      Column::Output => "output",
+      Column::Packets => "packets",
+      Column::Bytes => "bytes",
+      Column::FirstPacketSysUptime => "first_packet_sys_uptime",
+      Column::LastPacketSysUptime => "last_packet_sys_uptime",
+      Column::SourcePort => "source_port",
+      Column::DestinationPort => "destination_port",
+      Column::TcpFlags => "tcp_flags",
+      Column::Protocol => "protocol",
+      Column::TypeOfService => "type_of_service",
+      Column::SourceAs => "source_as",
+      Column::DestinationAs => "destination_as",
+      Column::SourceMask => "source_mask",
+      Column::DestinationMask => "destination_mask",
+    }
+  }
+
+  /// Parses a comma-separated list of column names, such as
+  /// `"exporter,source_addr,bytes"`
+  pub fn parse_list(s: &str) -> Result<Vec<Column>, ParseColumnError> {
+    s.split(',').map(|name| name.trim().parse()).collect()
+  }
+
+  fn value(
+    self,
+    rendering: Rendering,
+    exporter: IpAddr,
+    header: &Header<'_>,
+    record: &Record<'_>,
+  ) -> Value {
+    let number = |value: u32| Value::Number(u128::from(value));
+    match self {
+      Column::Exporter => Value::address(exporter, rendering),
+      Column::UnixSecs => number(header.unix_secs()),
+      Column::UnixNsecs => number(header.unix_nsecs()),
+      Column::SysUptimeMsecs => number(header.sys_uptime_msecs()),
+      Column::SequenceNumber => number(header.sequence_number()),
+      Column::EngineType => number(u32::from(header.engine_type())),
+      Column::EngineId => number(u32::from(header.engine_id())),
+      Column::Sampling => number(u32::from(header.sampling())),
+      Column::SourceAddr => Value::address(record.source_addr(), rendering),
+      Column::DestinationAddr => Value::address(record.destination_addr(), rendering),
+      Column::NextHop => Value::address(record.next_hop(), rendering),
+      Column::Input => number(u32::from(record.input())),
+      Column::Output => number(u32::from(record.output())),
+      Column::Packets => number(record.packets()),
+      Column::Bytes => number(record.bytes()),
+      Column::FirstPacketSysUptime => number(record.first_packet_sys_uptime()),
+      Column::LastPacketSysUptime => number(record.last_packet_sys_uptime()),
+      Column::SourcePort => number(u32::from(record.source_port())),
+      Column::DestinationPort => number(u32::from(record.destination_port())),
+      Column::TcpFlags => match rendering {
+        Rendering::Human => Value::TcpFlags(record.tcp_flags()),
+        _ => number(u32::from(record.tcp_flags())),
+      },
+      Column::Protocol => match (rendering, protocol_name(record.protocol())) {
+        (Rendering::Human, Some(name)) => Value::Name(name),
+        _ => number(u32::from(record.protocol())),
+      },
+      Column::TypeOfService => number(u32::from(record.type_of_service())),
+      Column::SourceAs => number(u32::from(record.source_as())),
+      Column::DestinationAs => number(u32::from(record.destination_as())),
+      Column::SourceMask => number(u32::from(record.source_mask())),
+      Column::DestinationMask => number(u32::from(record.destination_mask())),
+    }
+  }
+}
+
+/// Error returned when parsing an unknown `Column` name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColumnError(String);
+
+impl fmt::Display for ParseColumnError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "unknown column {:?}", self.0)
+  }
+}
+
+impl std::error::Error for ParseColumnError {}
+
+impl FromStr for Column {
+  type Err = ParseColumnError;
+
+  fn from_str(s: &str) -> Result<Column, ParseColumnError> {
+    Column::ALL
+      .iter()
+      .find(|column| column.name() == s)
+      .cloned()
+      .ok_or_else(|| ParseColumnError(s.to_string()))
+  }
+}
+
+/// How addresses, protocols and TCP flags are rendered
+///
+/// Other columns are always written as numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rendering {
+  /// Addresses as integers, protocols and TCP flags as their numbers
+  Numeric,
+  /// Addresses in their usual notation, protocols by name when they have a
+  /// well-known one and TCP flags as letters, such as `.AP.S.`
+  Human,
+  /// Addresses in their usual notation, protocols and TCP flags as their
+  /// numbers, as written by `Format::Json`
+  HumanAddresses,
+}
+
+/// Name of the IP protocols analysts filter on most
+fn protocol_name(protocol: u8) -> Option<&'static str> {
+  match protocol {
+    1 => Some("icmp"),
+    2 => Some("igmp"),
+    6 => Some("tcp"),
+    17 => Some("udp"),
+    47 => Some("gre"),
+    50 => Some("esp"),
+    51 => Some("ah"),
+    58 => Some("ipv6-icmp"),
+    89 => Some("ospf"),
+    132 => Some("sctp"),
+    _ => None,
+  }
+}
+
+/// A rendered field, written unquoted if it is a number
+enum Value {
+  Number(u128),
+  Address(IpAddr),
+  Name(&'static str),
+  TcpFlags(u8),
+}
+
+impl Value {
+  fn address(addr: IpAddr, rendering: Rendering) -> Value {
+    match (rendering, addr) {
+      (Rendering::Numeric, IpAddr::V4(addr)) => Value::Number(u128::from(u32::from(addr))),
+      (Rendering::Numeric, IpAddr::V6(addr)) => Value::Number(u128::from(addr)),
+      (_, addr) => Value::Address(addr),
+    }
+  }
+
+  fn is_number(&self) -> bool {
+    matches!(self, Value::Number(_))
+  }
+}
+
+impl fmt::Display for Value {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Value::Number(value) => write!(f, "{}", value),
+      Value::Address(addr) => write!(f, "{}", addr),
+      Value::Name(name) => f.write_str(name),
+      Value::TcpFlags(flags) => {
+        // URG, ACK, PSH, RST, SYN and FIN from the most significant bit, as
+        // printed by nfdump
+        for (i, letter) in "UAPRSF".chars().enumerate() {
+          let set = flags & (0x20 >> i) != 0;
+          write!(f, "{}", if set { letter } else { '.' })?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+/// Writes flow records as CSV, one row per record after an optional header
+/// row of column names
+///
+/// Rendered values never hold commas or quotes, so none is quoted.
+#[derive(Debug)]
+pub struct CsvWriter<W: Write> {
+  out: W,
+  columns: Vec<Column>,
+  rendering: Rendering,
+  header_row: bool,
+}
+
+impl<W: Write> CsvWriter<W> {
+  /// A writer of every column with numeric renderings, starting with a
+  /// header row
+  pub fn new(out: W) -> CsvWriter<W> {
+    CsvWriter {
+      out,
+      columns: Column::ALL.to_vec(),
+      rendering: Rendering::Numeric,
+      header_row: true,
+    }
+  }
+
+  /// Writes only `columns`, in their order
+  pub fn columns(mut self, columns: &[Column]) -> CsvWriter<W> {
+    self.columns = columns.to_vec();
+    self
+  }
+
+  /// Renders addresses, protocols and TCP flags as `rendering`
+  pub fn rendering(mut self, rendering: Rendering) -> CsvWriter<W> {
+    self.rendering = rendering;
+    self
+  }
+
+  /// Whether to write a header row before the first record
+  pub fn header_row(mut self, header_row: bool) -> CsvWriter<W> {
+    self.header_row = header_row;
+    self
+  }
+
+  /// Writes the header row unless it was already written or is disabled, so
+  /// that an output without any record still names its columns
+  ///
+  /// Writing a set or a record starts with it.
+  pub fn write_header(&mut self) -> io::Result<()> {
+    if self.header_row {
+      self.header_row = false;
+      let names = self.columns.iter().map(|column| column.name());
+      write_row(&mut self.out, names)?;
+    }
+    Ok(())
+  }
+
+  /// Writes every record of `set`, exported by `exporter`
+  pub fn write_set(&mut self, exporter: IpAddr, set: &FlowSet<'_>) -> io::Result<()> {
+    self.write_header()?;
+    let header = set.header();
+    for record in set {
+      self.write_record(exporter, &header, &record)?;
+    }
+    Ok(())
+  }
+
+  /// Writes `record`, exported by `exporter` in the packet of `header`
+  pub fn write_record(
+    &mut self,
+    exporter: IpAddr,
+    header: &Header<'_>,
+    record: &Record<'_>,
+  ) -> io::Result<()> {
+    self.write_header()?;
+    let rendering = self.rendering;
+    let values = self
+      .columns
+      .iter()
+      .map(|column| column.value(rendering, exporter, header, record));
+    write_row(&mut self.out, values)
+  }
+
+  /// Returns the underlying writer
+  pub fn into_inner(self) -> W {
+    self.out
+  }
+}
+
+fn write_row<W: Write, T: fmt::Display, I: Iterator<Item = T>>(
+  out: &mut W,
+  fields: I,
+) -> io::Result<()> {
+  for (i, field) in fields.enumerate() {
+    if i > 0 {
+      out.write_all(b",")?;
+    }
+    write!(out, "{}", field)?;
+  }
+  out.write_all(b"\n")
+}
+
+/// Writes flow records as JSON Lines, one object per record
+#[derive(Debug)]
+pub struct JsonLinesWriter<W: Write> {
+  out: W,
+  columns: Vec<Column>,
+  rendering: Rendering,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+  /// A writer of every column with human-readable renderings, as strings
+  pub fn new(out: W) -> JsonLinesWriter<W> {
+    JsonLinesWriter {
+      out,
+      columns: Column::ALL.to_vec(),
+      rendering: Rendering::Human,
+    }
+  }
+
+  /// Writes only `columns`, in their order
+  pub fn columns(mut self, columns: &[Column]) -> JsonLinesWriter<W> {
+    self.columns = columns.to_vec();
+    self
+  }
+
+  /// Renders addresses, protocols and TCP flags as `rendering`
+  pub fn rendering(mut self, rendering: Rendering) -> JsonLinesWriter<W> {
+    self.rendering = rendering;
+    self
+  }
+
+  /// Writes every record of `set`, exported by `exporter`
+  pub fn write_set(&mut self, exporter: IpAddr, set: &FlowSet<'_>) -> io::Result<()> {
+    let header = set.header();
+    for record in set {
+      self.write_record(exporter, &header, &record)?;
+    }
+    Ok(())
+  }
+
+  /// Writes `record`, exported by `exporter` in the packet of `header`
+  pub fn write_record(
+    &mut self,
+    exporter: IpAddr,
+    header: &Header<'_>,
+    record: &Record<'_>,
+  ) -> io::Result<()> {
+    write_object(
+      &mut self.out,
+      &self.columns,
+      self.rendering,
+      exporter,
+      header,
+      record,
+    )
+  }
+
+  /// Returns the underlying writer
+  pub fn into_inner(self) -> W {
+    self.out
+  }
+}
+
+/// Writes `record` as one line holding a JSON object of `columns`
+pub(crate) fn write_object<W: Write>(
+  out: &mut W,
+  columns: &[Column],
+  rendering: Rendering,
+  exporter: IpAddr,
+  header: &Header<'_>,
+  record: &Record<'_>,
+) -> io::Result<()> {
+  out.write_all(b"{")?;
+  for (i, column) in columns.iter().enumerate() {
+    let value = column.value(rendering, exporter, header, record);
+    let separator = if i > 0 { "," } else { "" };
+    // Names and rendered values need no escaping
+    if value.is_number() {
+      write!(out, "{}\"{}\":{}", separator, column.name(), value)?;
+    } else {
+      write!(out, "{}\"{}\":\"{}\"", separator, column.name(), value)?;
+    }
+  }
+  out.write_all(b"}\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::packet;
+  use netflow::v5::FlowRecord;
+  use std::net::Ipv4Addr;
+
+  const EXPORTER: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+  /// A TCP record, then a record of protocol 112 which has no name
+  fn protocol_records() -> Vec<FlowRecord> {
+    [(6, 0x1a), (112, 0)]
+      .iter()
+      .map(|&(protocol, tcp_flags)| FlowRecord {
+        source_addr: Ipv4Addr::new(192, 0, 2, 1),
+        destination_addr: Ipv4Addr::new(198, 51, 100, 2),
+        packets: 4,
+        bytes: 1708,
+        source_port: 49233,
+        destination_port: 443,
+        protocol,
+        tcp_flags,
+        ..FlowRecord::default()
+      })
+      .collect()
+  }
+
+  const COLUMNS: [Column; 6] = [
+    Column::UnixSecs,
+    Column::SequenceNumber,
+    Column::SourceAddr,
+    Column::Protocol,
+    Column::TcpFlags,
+    Column::Bytes,
+  ];
+
+  #[test]
+  fn csv_writer_should_write_selected_columns() {
+    let data = packet(&protocol_records());
+    let set = FlowSet::new(&data).unwrap();
+    let mut writer = CsvWriter::new(Vec::new()).columns(&COLUMNS);
+    writer.write_set(EXPORTER, &set).unwrap();
+
+    assert_eq!(
+      String::from_utf8(writer.into_inner()).unwrap(),
+      concat!(
+        "unix_secs,sequence_number,source_addr,protocol,tcp_flags,bytes\n",
+        "1544476581,7,3221225985,6,26,1708\n",
+        "1544476581,7,3221225985,112,0,1708\n",
+      )
+    );
+  }
+
+  #[test]
+  fn csv_writer_should_write_header_row_once_without_records() {
+    let mut writer = CsvWriter::new(Vec::new()).columns(&COLUMNS[..2]);
+    writer.write_header().unwrap();
+    writer.write_header().unwrap();
+    // A set whose records were all cut off
+    let mut data = packet(&protocol_records());
+    data.truncate(Header::LEN);
+    writer
+      .write_set(EXPORTER, &FlowSet::new_lenient(&data).unwrap())
+      .unwrap();
+
+    assert_eq!(
+      String::from_utf8(writer.into_inner()).unwrap(),
+      "unix_secs,sequence_number\n"
+    );
+  }
+
+  #[test]
+  fn csv_writer_should_render_human_readable_values() {
+    let data = packet(&protocol_records());
+    let set = FlowSet::new(&data).unwrap();
+    let mut writer = CsvWriter::new(Vec::new())
+      .columns(&COLUMNS)
+      .rendering(Rendering::Human)
+      .header_row(false);
+    writer.write_set(EXPORTER, &set).unwrap();
+    writer.write_set(EXPORTER, &set).unwrap();
+
+    assert_eq!(
+      String::from_utf8(writer.into_inner()).unwrap(),
+      concat!(
+        "1544476581,7,192.0.2.1,tcp,.AP.S.,1708\n",
+        "1544476581,7,192.0.2.1,112,......,1708\n",
+        "1544476581,7,192.0.2.1,tcp,.AP.S.,1708\n",
+        "1544476581,7,192.0.2.1,112,......,1708\n",
+      )
+    );
+  }
+
+  #[test]
+  fn json_lines_writer_should_write_one_object_per_record() {
+    let data = packet(&protocol_records());
+    let set = FlowSet::new(&data).unwrap();
+    let mut writer = JsonLinesWriter::new(Vec::new()).columns(&COLUMNS);
+    writer.write_set(EXPORTER, &set).unwrap();
+    let mut numeric = JsonLinesWriter::new(Vec::new())
+      .columns(&[Column::Exporter, Column::Protocol])
+      .rendering(Rendering::Numeric);
+    numeric.write_set(EXPORTER, &set).unwrap();
+
+    assert_eq!(
+      String::from_utf8(writer.into_inner()).unwrap(),
+      concat!(
+        "{\"unix_secs\":1544476581,\"sequence_number\":7,\"source_addr\":\"192.0.2.1\",",
+        "\"protocol\":\"tcp\",\"tcp_flags\":\".AP.S.\",\"bytes\":1708}\n",
+        "{\"unix_secs\":1544476581,\"sequence_number\":7,\"source_addr\":\"192.0.2.1\",",
+        "\"protocol\":112,\"tcp_flags\":\"......\",\"bytes\":1708}\n",
+      )
+    );
+    assert_eq!(
+      String::from_utf8(numeric.into_inner()).unwrap(),
+      "{\"exporter\":2130706433,\"protocol\":6}\n{\"exporter\":2130706433,\"protocol\":112}\n"
+    );
+  }
+
+  #[test]
+  fn json_lines_writer_should_write_every_column_by_default() {
+    let data = packet(&protocol_records());
+    let set = FlowSet::new(&data).unwrap();
+    let mut writer = JsonLinesWriter::new(Vec::new());
+    writer
+      .write_record(EXPORTER, &set.header(), &set.record(0).unwrap())
+      .unwrap();
+    let line = String::from_utf8(writer.into_inner()).unwrap();
+
+    for column in Column::ALL.iter() {
+      assert!(line.contains(&format!("\"{}\":", column.name())));
+    }
+    assert!(line.starts_with("{\"exporter\":\"127.0.0.1\","));
+  }
+
+  #[test]
+  fn columns_should_parse_names() {
+    assert_eq!(
+      Column::parse_list("exporter, bytes,tcp_flags"),
+      Ok(vec![Column::Exporter, Column::Bytes, Column::TcpFlags])
+    );
+    assert_eq!(
+      Column::parse_list("exporter,flags"),
+      Err(ParseColumnError("flags".to_string()))
+    );
+    for column in Column::ALL.iter() {
+      assert_eq!(column.name().parse(), Ok(*column));
+    }
+  }
+}
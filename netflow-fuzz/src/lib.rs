@@ -0,0 +1,313 @@
+//! Harnesses shared by the fuzz targets of `netflow`
+//!
+//! Each function feeds arbitrary bytes to one parser entry point and touches
+//! everything reachable from what it returns, so that any out-of-bounds read
+//! panics under the fuzzer. Run a target with, from the workspace root:
+//!
+//! ```text
+//! cargo fuzz run --fuzz-dir netflow-fuzz v5_flow_set
+//! ```
+//!
+//! The seed corpus of each target lives in `corpus/<target>`.
+
+extern crate netflow;
+
+use netflow::sflow::raw::{Datagram, FlowRecord, Sample};
+use netflow::v5::raw::{FlowSet, Header, Record};
+use netflow::v5::PacketHeader;
+use netflow::{ipfix, v9, Packet};
+use std::fmt::Debug;
+use std::net::{IpAddr, Ipv4Addr};
+
+/// Exporter the templates of v9 packets and IPFIX messages are learnt from
+const EXPORTER: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+/// Parses `data` with `v5::raw::Header::new` and reads every field
+pub fn v5_header(data: &[u8]) {
+  if let Ok(header) = Header::new(data) {
+    read_header(&header);
+  }
+}
+
+/// Parses `data` with `v5::raw::FlowSet::new`, `FlowSet::new_strict` and
+/// `FlowSet::new_lenient`, then reads every field of the header and of each
+/// record
+pub fn v5_flow_set(data: &[u8]) {
+  if let Ok(set) = FlowSet::new(data) {
+    read_flow_set(&set);
+  }
+  if let Ok(set) = FlowSet::new_strict(data) {
+    assert_eq!(FlowSet::new(data).as_ref(), Ok(&set));
+    read_flow_set(&set);
+  }
+  if let Ok(set) = FlowSet::new_lenient(data) {
+    let _ = set.validate();
+    read_flow_set(&set);
+  }
+}
+
+/// Parses `data` with the version-sniffing `netflow::parse`, then walks the
+/// records of v1, v5 and v7 packets. Templates of v9 packets and IPFIX
+/// messages are learnt first, then every field of the data records they
+/// describe is read.
+pub fn parse(data: &[u8]) {
+  match netflow::parse(data) {
+    Ok(Packet::V1(set)) => read_fixed_flow_set(
+      1,
+      (set.header().version(), set.header().count()),
+      set.records(),
+      |i| set.record(i),
+      set.len(),
+      set.is_truncated(),
+    ),
+    Ok(Packet::V5(set)) => read_flow_set(&set),
+    Ok(Packet::V7(set)) => read_fixed_flow_set(
+      7,
+      (set.header().version(), set.header().count()),
+      set.records(),
+      |i| set.record(i),
+      set.len(),
+      set.is_truncated(),
+    ),
+    Ok(Packet::V9(packet)) => read_v9_packet(&packet),
+    Ok(Packet::Ipfix(message)) => read_ipfix_message(&message),
+    Err(_) => {}
+  }
+}
+
+/// Parses `data` with `v5::PacketHeader::parse`, checking that it agrees with
+/// `v5::raw::Header::new` and writes back the bytes it read
+pub fn v5_packet_header(data: &[u8]) {
+  match (PacketHeader::parse(data), Header::new(data)) {
+    (Ok((header, rest)), Ok(raw)) => {
+      assert_eq!(rest.len(), data.len() - Header::LEN);
+      assert_eq!(header, PacketHeader::from(&raw));
+      assert_eq!(&header.to_bytes()[..], &data[..Header::LEN]);
+    }
+    (Err(a), Err(b)) => assert_eq!(a, b),
+    (a, b) => panic!(
+      "PacketHeader::parse {:?} disagrees with Header::new {:?}",
+      a, b
+    ),
+  }
+}
+
+/// Parses `data` with `sflow::raw::Datagram::new`, then reads every field of
+/// the header, of each sample and of each record through their `Debug`
+/// implementations
+pub fn sflow_datagram(data: &[u8]) {
+  if let Ok(datagram) = Datagram::new(data) {
+    let _ = format!("{:?}", datagram);
+    for sample in datagram.samples() {
+      if let Ok(Sample::Flow(sample)) = sample {
+        for record in sample.records() {
+          if let Ok(FlowRecord::RawPacketHeader(record)) = record {
+            assert_eq!(record.header().len(), record.header_length() as usize);
+          }
+        }
+      }
+    }
+  }
+}
+
+fn read_flow_set(set: &FlowSet<'_>) {
+  read_header(&set.header());
+  let mut count = 0;
+  for record in set.records() {
+    read_record(&record);
+    assert_eq!(set.record(count), Some(record));
+    count += 1;
+  }
+  assert_eq!(count, set.len());
+  assert_eq!(set.records().len(), set.len());
+  assert_eq!(set.records().rev().count(), set.len());
+  assert_eq!(set.record(count), None);
+  assert!(set.len() <= set.header().count() as usize);
+  assert_eq!(
+    set.is_truncated(),
+    set.len() < set.header().count() as usize
+  );
+}
+
+/// Walks the records of a v1 or v7 packet whose header announces `version`
+/// and `count` flows, checking `version` against `expected`
+fn read_fixed_flow_set<R, I, F>(
+  expected: u16,
+  (version, count): (u16, u16),
+  records: I,
+  record: F,
+  len: usize,
+  is_truncated: bool,
+) where
+  R: Debug + PartialEq,
+  I: Iterator<Item = R>,
+  F: Fn(usize) -> Option<R>,
+{
+  assert_eq!(version, expected);
+  let mut index = 0;
+  for r in records {
+    let _ = format!("{:?}", r);
+    assert_eq!(record(index), Some(r));
+    index += 1;
+  }
+  assert_eq!(index, len);
+  assert_eq!(record(index), None);
+  assert_eq!(is_truncated, len < count as usize);
+}
+
+fn read_v9_packet(packet: &v9::raw::Packet<'_>) {
+  let mut cache = v9::TemplateCache::new();
+  let _ = cache.learn(EXPORTER, packet);
+  let source_id = packet.header().source_id();
+  for set in packet.flowsets().filter_map(Result::ok) {
+    let template = match cache.get(EXPORTER, source_id, set.id()) {
+      Some(template) if set.is_data() => template,
+      _ => continue,
+    };
+    for record in set.records(template) {
+      let _ = format!("{:?}", record);
+      let fields = record.scope_fields().chain(record.fields());
+      let mut count = 0;
+      for (field, spec) in fields.zip(template.scope_fields.iter().chain(&template.fields)) {
+        assert_eq!(field.data().len(), spec.length as usize);
+        field.as_u64();
+        field.as_ip_addr();
+        field.as_mac_addr();
+        field.as_mpls_label();
+        count += 1;
+      }
+      assert_eq!(count, template.scope_fields.len() + template.fields.len());
+      record.mpls_labels();
+    }
+  }
+}
+
+fn read_ipfix_message(message: &ipfix::raw::Message<'_>) {
+  let mut cache = ipfix::TemplateCache::new();
+  let _ = cache.learn(EXPORTER, message);
+  let domain = message.header().observation_domain_id();
+  for set in message.sets().filter_map(Result::ok) {
+    let template = match cache.get(EXPORTER, domain, set.id()) {
+      Some(template) if set.is_data() => template,
+      _ => continue,
+    };
+    for record in set.records(template).filter_map(Result::ok) {
+      let _ = format!("{:?}", record);
+      let fields = record.scope_fields().chain(record.fields());
+      let mut count = 0;
+      for (field, spec) in fields.zip(template.scope_fields.iter().chain(&template.fields)) {
+        assert_eq!(field.spec(), *spec);
+        if !spec.is_variable_length() {
+          assert_eq!(field.data().len(), spec.length as usize);
+        }
+        field.as_u64();
+        field.as_ip_addr();
+        field.as_mac_addr();
+        field.as_mpls_label();
+        field.as_str();
+        count += 1;
+      }
+      assert_eq!(count, template.scope_fields.len() + template.fields.len());
+      record.mpls_labels();
+    }
+  }
+}
+
+fn read_header(header: &Header<'_>) {
+  assert_eq!(header.version(), 5);
+  header.count();
+  header.sys_uptime_msecs();
+  header.unix_secs();
+  header.unix_nsecs();
+  header.sequence_number();
+  header.engine_type();
+  header.engine_id();
+  header.sampling();
+}
+
+fn read_record(record: &Record<'_>) {
+  record.source_addr();
+  record.destination_addr();
+  record.next_hop();
+  record.input();
+  record.output();
+  record.packets();
+  record.bytes();
+  record.first_packet_sys_uptime();
+  record.last_packet_sys_uptime();
+  record.source_port();
+  record.destination_port();
+  record.tcp_flags();
+  record.protocol();
+  record.type_of_service();
+  record.source_as();
+  record.destination_as();
+  record.source_mask();
+  record.destination_mask();
+  record.pad1();
+  record.pad2();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::path::Path;
+
+  /// Seeds of the corpus of `target`, as committed in the repository
+  fn corpus(target: &str) -> Vec<Vec<u8>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+      .join("corpus")
+      .join(target);
+    let mut seeds = std::fs::read_dir(dir)
+      .unwrap()
+      .map(|entry| std::fs::read(entry.unwrap().path()).unwrap())
+      .collect::<Vec<_>>();
+    assert!(!seeds.is_empty());
+    seeds.sort();
+    seeds
+  }
+
+  /// Runs `harness` on each seed, every truncation of it and every single
+  /// byte overwrite in its first `offsets` bytes, those that drive parsing
+  fn replay(target: &str, offsets: usize, harness: fn(&[u8])) {
+    for seed in corpus(target) {
+      for len in 0..=seed.len() {
+        harness(&seed[..len]);
+      }
+      for offset in 0..seed.len().min(offsets) {
+        for byte in [0x00, 0x01, 0x1e, 0x1f, 0x7f, 0xff].iter() {
+          let mut data = seed.clone();
+          data[offset] = *byte;
+          harness(&data);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn v5_header_should_survive_corpus() {
+    replay("v5_header", Header::LEN, v5_header);
+  }
+
+  #[test]
+  fn v5_flow_set_should_survive_corpus() {
+    replay("v5_flow_set", Header::LEN, v5_flow_set);
+  }
+
+  #[test]
+  fn parse_should_survive_corpus() {
+    // Template and set lengths of v9 and IPFIX are spread over the whole packet
+    replay("parse", usize::MAX, parse);
+  }
+
+  #[test]
+  fn v5_packet_header_should_survive_corpus() {
+    replay("v5_packet_header", Header::LEN, v5_packet_header);
+  }
+
+  #[test]
+  fn sflow_datagram_should_survive_corpus() {
+    // Lengths and counts are spread over the whole datagram
+    replay("sflow_datagram", usize::MAX, sflow_datagram);
+  }
+}
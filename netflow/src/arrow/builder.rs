@@ -0,0 +1,321 @@
+use super::schema;
+use crate::v5::raw::{FlowSet, Header, Record};
+use arrow_array::builder::{
+  StringBuilder, TimestampMillisecondBuilder, TimestampNanosecondBuilder, UInt16Builder,
+  UInt32Builder, UInt8Builder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::SchemaRef;
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Nanoseconds since the Unix epoch, negative before it
+fn nanoseconds(time: SystemTime) -> i64 {
+  match time.duration_since(UNIX_EPOCH) {
+    Ok(duration) => duration.as_nanos() as i64,
+    Err(error) => -(error.duration().as_nanos() as i64),
+  }
+}
+
+/// Milliseconds since the Unix epoch, negative before it
+fn milliseconds(time: SystemTime) -> i64 {
+  match time.duration_since(UNIX_EPOCH) {
+    Ok(duration) => duration.as_millis() as i64,
+    Err(error) => -(error.duration().as_millis() as i64),
+  }
+}
+
+fn ipv4(addr: IpAddr) -> u32 {
+  match addr {
+    IpAddr::V4(addr) => u32::from(addr),
+    // v5 records only hold IPv4 addresses
+    IpAddr::V6(_) => unreachable!(),
+  }
+}
+
+/// Appends v5 records to the columns of a record batch of `schema()`
+///
+/// ```
+/// use netflow::arrow::FlowBatchBuilder;
+/// use netflow::v5::raw::FlowSet;
+/// use std::net::{IpAddr, Ipv4Addr};
+///
+/// # use netflow::v5::{FlowRecord, PacketBuilder};
+/// # use std::time::{Duration, UNIX_EPOCH};
+/// # let mut packet = PacketBuilder::new(UNIX_EPOCH);
+/// # packet.push(FlowRecord::default()).unwrap();
+/// # let data = packet.build(UNIX_EPOCH + Duration::from_secs(60)).unwrap();
+/// let set = FlowSet::new(&data).unwrap();
+/// let mut builder = FlowBatchBuilder::new();
+/// builder.append_set(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), &set);
+/// let batch = builder.finish();
+/// assert_eq!(batch.num_rows(), 1);
+/// ```
+pub struct FlowBatchBuilder {
+  schema: SchemaRef,
+  len: usize,
+  exporter: StringBuilder,
+  export_time: TimestampNanosecondBuilder,
+  sequence_number: UInt32Builder,
+  engine_type: UInt8Builder,
+  engine_id: UInt8Builder,
+  sampling: UInt16Builder,
+  source_addr: UInt32Builder,
+  destination_addr: UInt32Builder,
+  next_hop: UInt32Builder,
+  input: UInt16Builder,
+  output: UInt16Builder,
+  packets: UInt32Builder,
+  bytes: UInt32Builder,
+  first_packet_time: TimestampMillisecondBuilder,
+  last_packet_time: TimestampMillisecondBuilder,
+  source_port: UInt16Builder,
+  destination_port: UInt16Builder,
+  tcp_flags: UInt8Builder,
+  protocol: UInt8Builder,
+  type_of_service: UInt8Builder,
+  source_as: UInt16Builder,
+  destination_as: UInt16Builder,
+  source_mask: UInt8Builder,
+  destination_mask: UInt8Builder,
+}
+
+impl fmt::Debug for FlowBatchBuilder {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("FlowBatchBuilder")
+      .field("len", &self.len)
+      .finish()
+  }
+}
+
+impl Default for FlowBatchBuilder {
+  fn default() -> FlowBatchBuilder {
+    FlowBatchBuilder::new()
+  }
+}
+
+impl FlowBatchBuilder {
+  /// An empty builder
+  pub fn new() -> FlowBatchBuilder {
+    FlowBatchBuilder {
+      schema: schema(),
+      len: 0,
+      exporter: StringBuilder::new(),
+      export_time: TimestampNanosecondBuilder::new().with_timezone("UTC"),
+      sequence_number: UInt32Builder::new(),
+      engine_type: UInt8Builder::new(),
+      engine_id: UInt8Builder::new(),
+      sampling: UInt16Builder::new(),
+      source_addr: UInt32Builder::new(),
+      destination_addr: UInt32Builder::new(),
+      next_hop: UInt32Builder::new(),
+      input: UInt16Builder::new(),
+      output: UInt16Builder::new(),
+      packets: UInt32Builder::new(),
+      bytes: UInt32Builder::new(),
+      first_packet_time: TimestampMillisecondBuilder::new().with_timezone("UTC"),
+      last_packet_time: TimestampMillisecondBuilder::new().with_timezone("UTC"),
+      source_port: UInt16Builder::new(),
+      destination_port: UInt16Builder::new(),
+      tcp_flags: UInt8Builder::new(),
+      protocol: UInt8Builder::new(),
+      type_of_service: UInt8Builder::new(),
+      source_as: UInt16Builder::new(),
+      destination_as: UInt16Builder::new(),
+      source_mask: UInt8Builder::new(),
+      destination_mask: UInt8Builder::new(),
+    }
+  }
+
+  /// Number of rows appended since the last `finish`
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  /// Whether no row was appended since the last `finish`
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Appends `record`, exported by `exporter` in the packet of `header`
+  pub fn append(&mut self, exporter: IpAddr, header: &Header<'_>, record: &Record<'_>) {
+    self.exporter.append_value(exporter.to_string());
+    self
+      .export_time
+      .append_value(nanoseconds(header.export_time()));
+    self.sequence_number.append_value(header.sequence_number());
+    self.engine_type.append_value(header.engine_type());
+    self.engine_id.append_value(header.engine_id());
+    self.sampling.append_value(header.sampling());
+    self.source_addr.append_value(ipv4(record.source_addr()));
+    self
+      .destination_addr
+      .append_value(ipv4(record.destination_addr()));
+    self.next_hop.append_value(ipv4(record.next_hop()));
+    self.input.append_value(record.input());
+    self.output.append_value(record.output());
+    self.packets.append_value(record.packets());
+    self.bytes.append_value(record.bytes());
+    self
+      .first_packet_time
+      .append_value(milliseconds(record.first_packet_time(header)));
+    self
+      .last_packet_time
+      .append_value(milliseconds(record.last_packet_time(header)));
+    self.source_port.append_value(record.source_port());
+    self
+      .destination_port
+      .append_value(record.destination_port());
+    self.tcp_flags.append_value(record.tcp_flags());
+    self.protocol.append_value(record.protocol());
+    self.type_of_service.append_value(record.type_of_service());
+    self.source_as.append_value(record.source_as());
+    self.destination_as.append_value(record.destination_as());
+    self.source_mask.append_value(record.source_mask());
+    self
+      .destination_mask
+      .append_value(record.destination_mask());
+    self.len += 1;
+  }
+
+  /// Appends every record of `set`, exported by `exporter`
+  pub fn append_set(&mut self, exporter: IpAddr, set: &FlowSet<'_>) {
+    let header = set.header();
+    for record in set {
+      self.append(exporter, &header, &record);
+    }
+  }
+
+  /// Returns the rows appended so far as a batch, and starts a new one
+  pub fn finish(&mut self) -> RecordBatch {
+    let columns: Vec<ArrayRef> = vec![
+      Arc::new(self.exporter.finish()),
+      Arc::new(self.export_time.finish()),
+      Arc::new(self.sequence_number.finish()),
+      Arc::new(self.engine_type.finish()),
+      Arc::new(self.engine_id.finish()),
+      Arc::new(self.sampling.finish()),
+      Arc::new(self.source_addr.finish()),
+      Arc::new(self.destination_addr.finish()),
+      Arc::new(self.next_hop.finish()),
+      Arc::new(self.input.finish()),
+      Arc::new(self.output.finish()),
+      Arc::new(self.packets.finish()),
+      Arc::new(self.bytes.finish()),
+      Arc::new(self.first_packet_time.finish()),
+      Arc::new(self.last_packet_time.finish()),
+      Arc::new(self.source_port.finish()),
+      Arc::new(self.destination_port.finish()),
+      Arc::new(self.tcp_flags.finish()),
+      Arc::new(self.protocol.finish()),
+      Arc::new(self.type_of_service.finish()),
+      Arc::new(self.source_as.finish()),
+      Arc::new(self.destination_as.finish()),
+      Arc::new(self.source_mask.finish()),
+      Arc::new(self.destination_mask.finish()),
+    ];
+    self.len = 0;
+    RecordBatch::try_new(self.schema.clone(), columns)
+      .expect("columns are built in the order and types of the schema")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::v5;
+  use arrow_array::cast::AsArray;
+  use arrow_array::types::{
+    TimestampMillisecondType, TimestampNanosecondType, UInt16Type, UInt32Type, UInt8Type,
+  };
+  use std::net::{Ipv4Addr, Ipv6Addr};
+
+  #[test]
+  fn builder_should_append_every_record() {
+    let set = FlowSet::new(&v5::tests::FLOW_PACKET_1).unwrap();
+    let header = set.header();
+    let exporter = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+    let mut builder = FlowBatchBuilder::new();
+    builder.append_set(exporter, &set);
+    assert_eq!(builder.len(), 29);
+    let batch = builder.finish();
+
+    assert!(builder.is_empty());
+    assert_eq!(batch.schema(), schema());
+    assert_eq!(batch.num_rows(), 29);
+    let column = |name| batch.column_by_name(name).unwrap();
+    assert_eq!(
+      column("exporter").as_string::<i32>().value(28),
+      "2001:db8::1"
+    );
+    assert_eq!(
+      column("export_time")
+        .as_primitive::<TimestampNanosecondType>()
+        .value(0),
+      nanoseconds(header.export_time())
+    );
+    assert_eq!(
+      column("sequence_number")
+        .as_primitive::<UInt32Type>()
+        .value(0),
+      header.sequence_number()
+    );
+    for (i, record) in set.records().enumerate() {
+      assert_eq!(
+        column("source_addr").as_primitive::<UInt32Type>().value(i),
+        ipv4(record.source_addr())
+      );
+      assert_eq!(
+        column("bytes").as_primitive::<UInt32Type>().value(i),
+        record.bytes()
+      );
+      assert_eq!(
+        column("last_packet_time")
+          .as_primitive::<TimestampMillisecondType>()
+          .value(i),
+        milliseconds(record.last_packet_time(&header))
+      );
+      assert_eq!(
+        column("destination_port")
+          .as_primitive::<UInt16Type>()
+          .value(i),
+        record.destination_port()
+      );
+      assert_eq!(
+        column("protocol").as_primitive::<UInt8Type>().value(i),
+        record.protocol()
+      );
+    }
+  }
+
+  #[test]
+  fn builder_should_store_addresses_as_integers() {
+    let mut builder = FlowBatchBuilder::new();
+    let set = FlowSet::new(&v5::tests::FLOW_PACKET_1).unwrap();
+    let record = set.record(0).unwrap();
+    builder.append(IpAddr::V4(Ipv4Addr::LOCALHOST), &set.header(), &record);
+    let batch = builder.finish();
+
+    let IpAddr::V4(addr) = record.destination_addr() else {
+      panic!("v5 records hold IPv4 addresses");
+    };
+    assert_eq!(
+      batch
+        .column_by_name("destination_addr")
+        .unwrap()
+        .as_primitive::<UInt32Type>()
+        .value(0),
+      u32::from(addr)
+    );
+  }
+
+  #[test]
+  fn milliseconds_should_be_negative_before_epoch() {
+    let time = UNIX_EPOCH - std::time::Duration::from_millis(1500);
+
+    assert_eq!(milliseconds(time), -1500);
+    assert_eq!(nanoseconds(time), -1_500_000_000);
+  }
+}
@@ -0,0 +1,117 @@
+//! Builds Arrow record batches of decoded Netflow v5 records and writes them
+//! to rolling Parquet files, to be queried by DataFusion, Spark and other
+//! columnar engines
+//!
+//! Every batch has the schema returned by `schema`: one row per record, with
+//! the exporter address, the export time, the sequence number of its packet
+//! and the wall-clock times of its first and last packets.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use netflow::arrow::ParquetWriter;
+//! use netflow::v5::raw::FlowSet;
+//! use std::net::{IpAddr, Ipv4Addr};
+//! use std::time::Duration;
+//!
+//! let mut writer = ParquetWriter::new("flows")
+//!   .max_file_size(128 << 20)
+//!   .window(Duration::from_secs(300));
+//!
+//! # let data = [0; 24];
+//! let set = FlowSet::new(&data)?;
+//! for path in writer.write_set(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), &set)? {
+//!   println!("completed {}", path.display());
+//! }
+//! writer.close()?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod builder;
+mod parquet;
+
+pub use self::builder::FlowBatchBuilder;
+pub use self::parquet::ParquetWriter;
+
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+/// Schema of the batches of `FlowBatchBuilder` and of the files of
+/// `ParquetWriter`
+///
+/// Columns are named after the accessors of `v5::raw::Header` and
+/// `v5::raw::Record`, and none is nullable. New columns are only ever added
+/// at the end. IPv4 addresses of records are stored as integers, so that
+/// prefixes are ranges; the exporter, which may be an IPv6 host, is stored as
+/// text. Times are UTC timestamps.
+pub fn schema() -> SchemaRef {
+  let timestamp = |unit| DataType::Timestamp(unit, Some("UTC".into()));
+  let field = |name, data_type| Field::new(name, data_type, false);
+  Arc::new(Schema::new(vec![
+    field("exporter", DataType::Utf8),
+    field("export_time", timestamp(TimeUnit::Nanosecond)),
+    field("sequence_number", DataType::UInt32),
+    field("engine_type", DataType::UInt8),
+    field("engine_id", DataType::UInt8),
+    field("sampling", DataType::UInt16),
+    field("source_addr", DataType::UInt32),
+    field("destination_addr", DataType::UInt32),
+    field("next_hop", DataType::UInt32),
+    field("input", DataType::UInt16),
+    field("output", DataType::UInt16),
+    field("packets", DataType::UInt32),
+    field("bytes", DataType::UInt32),
+    field("first_packet_time", timestamp(TimeUnit::Millisecond)),
+    field("last_packet_time", timestamp(TimeUnit::Millisecond)),
+    field("source_port", DataType::UInt16),
+    field("destination_port", DataType::UInt16),
+    field("tcp_flags", DataType::UInt8),
+    field("protocol", DataType::UInt8),
+    field("type_of_service", DataType::UInt8),
+    field("source_as", DataType::UInt16),
+    field("destination_as", DataType::UInt16),
+    field("source_mask", DataType::UInt8),
+    field("destination_mask", DataType::UInt8),
+  ]))
+}
+
+/// Errors returned while writing Parquet files
+#[derive(Debug)]
+pub enum Error {
+  /// Creating, renaming or writing a file failed
+  Io(io::Error),
+  /// Encoding a batch as Parquet failed
+  Parquet(::parquet::errors::ParquetError),
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::Io(error) => write!(f, "cannot write Parquet file: {}", error),
+      Error::Parquet(error) => write!(f, "cannot encode Parquet file: {}", error),
+    }
+  }
+}
+
+impl std::error::Error for Error {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Error::Io(error) => Some(error),
+      Error::Parquet(error) => Some(error),
+    }
+  }
+}
+
+impl From<io::Error> for Error {
+  fn from(error: io::Error) -> Error {
+    Error::Io(error)
+  }
+}
+
+impl From<::parquet::errors::ParquetError> for Error {
+  fn from(error: ::parquet::errors::ParquetError) -> Error {
+    Error::Parquet(error)
+  }
+}
@@ -0,0 +1,421 @@
+use super::{schema, Error, FlowBatchBuilder};
+use crate::v5::raw::{FlowSet, Header, Record};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::fmt;
+use std::fs::{self, File};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+struct OpenFile {
+  writer: ArrowWriter<File>,
+  /// Hidden name the file is written under, ignored by query engines
+  partial: PathBuf,
+  path: PathBuf,
+  /// Start of the window of the file in seconds since the Unix epoch, or the
+  /// export time of its first record without windows
+  start: u64,
+}
+
+/// Writes v5 records to Parquet files of `schema()`, rolling over to a new
+/// file once one reaches a size or a time window ends
+///
+/// Files are named `<prefix>-<start>.parquet` after the Unix time their
+/// window starts at, with a `-<n>` suffix when a file of the window already
+/// exists. While being written, a file is hidden as
+/// `.<prefix>-<start>.parquet.inprogress`, and only renamed once complete,
+/// so that readers of the directory never see a partial file.
+///
+/// Windows follow the export time of the packets rather than the clock of
+/// the host, and are aligned on multiples of their duration. Records of a
+/// window earlier than the one of the current file, such as those of late
+/// packets, are written to the current file. A window otherwise only ends
+/// when a record of a later one arrives: call `close_expired` periodically
+/// so that the file of an exporter gone quiet is completed too.
+///
+/// Records are buffered into batches of `batch_size` rows before being
+/// encoded, and sizes are only checked then, so files may exceed
+/// `max_file_size` by about a batch. Dropping the writer closes the current
+/// file, ignoring errors; `close` reports them.
+pub struct ParquetWriter {
+  directory: PathBuf,
+  prefix: String,
+  max_file_size: Option<u64>,
+  window: Option<Duration>,
+  batch_size: usize,
+  properties: WriterProperties,
+  builder: FlowBatchBuilder,
+  file: Option<OpenFile>,
+}
+
+impl fmt::Debug for ParquetWriter {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("ParquetWriter")
+      .field("directory", &self.directory)
+      .field("prefix", &self.prefix)
+      .field("max_file_size", &self.max_file_size)
+      .field("window", &self.window)
+      .field("batch_size", &self.batch_size)
+      .field("buffered", &self.builder.len())
+      .field("path", &self.file.as_ref().map(|file| &file.path))
+      .finish()
+  }
+}
+
+impl ParquetWriter {
+  /// A writer of files named `flows-<start>.parquet` in `directory`, created
+  /// when missing, compressed with Snappy and rolled over by neither size
+  /// nor time
+  pub fn new<P: AsRef<Path>>(directory: P) -> ParquetWriter {
+    ParquetWriter {
+      directory: directory.as_ref().to_path_buf(),
+      prefix: String::from("flows"),
+      max_file_size: None,
+      window: None,
+      batch_size: 8192,
+      properties: WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build(),
+      builder: FlowBatchBuilder::new(),
+      file: None,
+    }
+  }
+
+  /// Names files `<prefix>-<start>.parquet`
+  pub fn prefix(mut self, prefix: &str) -> ParquetWriter {
+    self.prefix = prefix.to_string();
+    self
+  }
+
+  /// Rolls over to a new file once one holds about `max_file_size` bytes
+  pub fn max_file_size(mut self, max_file_size: u64) -> ParquetWriter {
+    self.max_file_size = Some(max_file_size);
+    self
+  }
+
+  /// Rolls over to a new file for each `window` of export time, rounded to
+  /// whole seconds and at least one
+  pub fn window(mut self, window: Duration) -> ParquetWriter {
+    self.window = Some(window.max(Duration::from_secs(1)));
+    self
+  }
+
+  /// Encodes records by batches of `batch_size` rows, at least one
+  pub fn batch_size(mut self, batch_size: usize) -> ParquetWriter {
+    self.batch_size = batch_size.max(1);
+    self
+  }
+
+  /// Encodes files with `properties`, such as another compression codec
+  pub fn properties(mut self, properties: WriterProperties) -> ParquetWriter {
+    self.properties = properties;
+    self
+  }
+
+  /// Path the current file will have once complete, `None` before the first
+  /// record or after `close`
+  pub fn path(&self) -> Option<&Path> {
+    self.file.as_ref().map(|file| file.path.as_path())
+  }
+
+  /// Writes every record of `set`, exported by `exporter`, and returns the
+  /// paths of the files completed meanwhile
+  pub fn write_set(&mut self, exporter: IpAddr, set: &FlowSet<'_>) -> Result<Vec<PathBuf>, Error> {
+    let header = set.header();
+    let mut completed = Vec::new();
+    for record in set {
+      self.append(exporter, &header, &record, &mut completed)?;
+    }
+    Ok(completed)
+  }
+
+  /// Writes `record`, exported by `exporter` in the packet of `header`, and
+  /// returns the paths of the files completed meanwhile
+  pub fn write_record(
+    &mut self,
+    exporter: IpAddr,
+    header: &Header<'_>,
+    record: &Record<'_>,
+  ) -> Result<Vec<PathBuf>, Error> {
+    let mut completed = Vec::new();
+    self.append(exporter, header, record, &mut completed)?;
+    Ok(completed)
+  }
+
+  /// Encodes the buffered records into the current file
+  ///
+  /// The file stays hidden until complete; flushing only bounds the records
+  /// held in memory, while `close_expired` completes the file once its window
+  /// ended.
+  pub fn flush(&mut self) -> Result<(), Error> {
+    if let Some(file) = self.file.as_mut() {
+      if !self.builder.is_empty() {
+        file.writer.write(&self.builder.finish())?;
+      }
+      file.writer.flush()?;
+    }
+    Ok(())
+  }
+
+  /// Completes the current file if its window ended before `now`, and returns
+  /// its path
+  ///
+  /// Meant to be called periodically, with the current time minus however
+  /// long late packets are waited for, so that files are completed even when
+  /// no later record arrives. Without windows, files are never expired.
+  pub fn close_expired(&mut self, now: SystemTime) -> Result<Option<PathBuf>, Error> {
+    let secs = now
+      .duration_since(UNIX_EPOCH)
+      .map_or(0, |elapsed| elapsed.as_secs());
+    match (&self.file, self.window) {
+      (Some(file), Some(window)) if secs >= file.start + window.as_secs() => self.close(),
+      _ => Ok(None),
+    }
+  }
+
+  /// Completes the current file and returns its path, `None` if no record was
+  /// written since the last file was completed
+  pub fn close(&mut self) -> Result<Option<PathBuf>, Error> {
+    let mut file = match self.file.take() {
+      Some(file) => file,
+      None => return Ok(None),
+    };
+    if !self.builder.is_empty() {
+      file.writer.write(&self.builder.finish())?;
+    }
+    file.writer.close()?;
+    fs::rename(&file.partial, &file.path)?;
+    Ok(Some(file.path))
+  }
+
+  fn append(
+    &mut self,
+    exporter: IpAddr,
+    header: &Header<'_>,
+    record: &Record<'_>,
+    completed: &mut Vec<PathBuf>,
+  ) -> Result<(), Error> {
+    let secs = u64::from(header.unix_secs());
+    let start = match self.window {
+      Some(window) => secs - secs % window.as_secs(),
+      None => secs,
+    };
+    let ended = match (&self.file, self.window) {
+      (Some(file), Some(_)) => start > file.start,
+      _ => false,
+    };
+    if ended {
+      completed.extend(self.close()?);
+    }
+    if self.file.is_none() {
+      self.file = Some(self.open(start)?);
+    }
+
+    self.builder.append(exporter, header, record);
+    if self.builder.len() >= self.batch_size {
+      if let Some(file) = self.file.as_mut() {
+        file.writer.write(&self.builder.finish())?;
+        let size = (file.writer.bytes_written() + file.writer.in_progress_size()) as u64;
+        if self
+          .max_file_size
+          .is_some_and(|max_file_size| size >= max_file_size)
+        {
+          completed.extend(self.close()?);
+        }
+      }
+    }
+    Ok(())
+  }
+
+  fn open(&self, start: u64) -> Result<OpenFile, Error> {
+    fs::create_dir_all(&self.directory)?;
+    let mut suffix = 0;
+    let (path, partial) = loop {
+      let name = match suffix {
+        0 => format!("{}-{}.parquet", self.prefix, start),
+        _ => format!("{}-{}-{}.parquet", self.prefix, start, suffix),
+      };
+      let path = self.directory.join(&name);
+      let partial = self.directory.join(format!(".{}.inprogress", name));
+      if !path.exists() && !partial.exists() {
+        break (path, partial);
+      }
+      suffix += 1;
+    };
+    let writer = ArrowWriter::try_new(
+      File::create(&partial)?,
+      schema(),
+      Some(self.properties.clone()),
+    )?;
+
+    Ok(OpenFile {
+      writer,
+      partial,
+      path,
+      start,
+    })
+  }
+}
+
+impl Drop for ParquetWriter {
+  fn drop(&mut self) {
+    let _ = self.close();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::v5;
+  use arrow_array::cast::AsArray;
+  use arrow_array::types::UInt32Type;
+  use byteorder::{ByteOrder, NetworkEndian};
+  use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+  use std::net::Ipv4Addr;
+
+  const EXPORTER: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+  fn directory(name: &str) -> PathBuf {
+    let directory =
+      std::env::temp_dir().join(format!("netflow-parquet-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    directory
+  }
+
+  /// `FLOW_PACKET_1` exported at `unix_secs`
+  fn packet(unix_secs: u32) -> Vec<u8> {
+    let mut data = v5::tests::FLOW_PACKET_1.to_vec();
+    NetworkEndian::write_u32(&mut data[8..12], unix_secs);
+    data
+  }
+
+  fn read(path: &Path) -> Vec<u32> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+      .unwrap()
+      .build()
+      .unwrap();
+    let mut bytes = Vec::new();
+    for batch in reader {
+      let batch = batch.unwrap();
+      assert_eq!(batch.schema(), schema());
+      let column = batch.column_by_name("bytes").unwrap();
+      bytes.extend(column.as_primitive::<UInt32Type>().values().iter());
+    }
+    bytes
+  }
+
+  #[test]
+  fn writer_should_write_readable_files() {
+    let directory = directory("readable");
+    let data = packet(1_544_476_581);
+    let set = FlowSet::new(&data).unwrap();
+    let mut writer = ParquetWriter::new(&directory).batch_size(10);
+    assert!(writer.write_set(EXPORTER, &set).unwrap().is_empty());
+    writer.flush().unwrap();
+
+    let path = directory.join("flows-1544476581.parquet");
+    assert_eq!(writer.path(), Some(path.as_path()));
+    assert!(!path.exists());
+    assert_eq!(writer.close().unwrap(), Some(path.clone()));
+    assert_eq!(writer.close().unwrap(), None);
+    let expected: Vec<u32> = set.records().map(|record| record.bytes()).collect();
+    assert_eq!(read(&path), expected);
+    assert_eq!(fs::read_dir(&directory).unwrap().count(), 1);
+    fs::remove_dir_all(&directory).unwrap();
+  }
+
+  #[test]
+  fn writer_should_roll_over_windows() {
+    let directory = directory("window");
+    let mut writer = ParquetWriter::new(&directory)
+      .prefix("v5")
+      .window(Duration::from_secs(300));
+    let mut completed = Vec::new();
+    for &unix_secs in [1_544_476_500, 1_544_476_799, 1_544_476_800, 1_544_476_700].iter() {
+      let data = packet(unix_secs);
+      completed.extend(
+        writer
+          .write_set(EXPORTER, &FlowSet::new(&data).unwrap())
+          .unwrap(),
+      );
+    }
+    completed.extend(writer.close().unwrap());
+
+    assert_eq!(
+      completed,
+      vec![
+        directory.join("v5-1544476500.parquet"),
+        directory.join("v5-1544476800.parquet"),
+      ]
+    );
+    assert_eq!(read(&completed[0]).len(), 58);
+    // The late packet is written with the current window
+    assert_eq!(read(&completed[1]).len(), 58);
+    fs::remove_dir_all(&directory).unwrap();
+  }
+
+  #[test]
+  fn writer_should_close_expired_windows() {
+    let directory = directory("expired");
+    let data = packet(1_544_476_581);
+    let mut writer = ParquetWriter::new(&directory).window(Duration::from_secs(300));
+    writer
+      .write_set(EXPORTER, &FlowSet::new(&data).unwrap())
+      .unwrap();
+
+    let end = UNIX_EPOCH + Duration::from_secs(1_544_476_800);
+    assert_eq!(
+      writer.close_expired(end - Duration::from_secs(1)).unwrap(),
+      None
+    );
+    let path = directory.join("flows-1544476500.parquet");
+    assert_eq!(writer.close_expired(end).unwrap(), Some(path.clone()));
+    assert_eq!(writer.close_expired(end).unwrap(), None);
+    assert_eq!(read(&path).len(), 29);
+    fs::remove_dir_all(&directory).unwrap();
+  }
+
+  #[test]
+  fn writer_should_roll_over_sizes() {
+    let directory = directory("size");
+    let data = packet(1_544_476_581);
+    let set = FlowSet::new(&data).unwrap();
+    let mut writer = ParquetWriter::new(&directory)
+      .max_file_size(1)
+      .batch_size(29);
+    let mut completed = Vec::new();
+    for _ in 0..3 {
+      completed.extend(writer.write_set(EXPORTER, &set).unwrap());
+    }
+
+    assert_eq!(writer.close().unwrap(), None);
+    assert_eq!(
+      completed,
+      vec![
+        directory.join("flows-1544476581.parquet"),
+        directory.join("flows-1544476581-1.parquet"),
+        directory.join("flows-1544476581-2.parquet"),
+      ]
+    );
+    for path in completed.iter() {
+      assert_eq!(read(path).len(), 29);
+    }
+    fs::remove_dir_all(&directory).unwrap();
+  }
+
+  #[test]
+  fn writer_should_close_files_when_dropped() {
+    let directory = directory("drop");
+    let data = packet(1_544_476_581);
+    let mut writer = ParquetWriter::new(&directory);
+    writer
+      .write_set(EXPORTER, &FlowSet::new(&data).unwrap())
+      .unwrap();
+    drop(writer);
+
+    assert_eq!(read(&directory.join("flows-1544476581.parquet")).len(), 29);
+    fs::remove_dir_all(&directory).unwrap();
+  }
+}
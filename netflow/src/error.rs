@@ -0,0 +1,303 @@
+use std::fmt;
+
+/// Flow export protocol a decoding error relates to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protocol {
+  /// Netflow v1
+  V1,
+  /// Netflow v5
+  V5,
+  /// Netflow v7 (Catalyst switches)
+  V7,
+  /// Netflow v9 (RFC 3954)
+  V9,
+  /// IPFIX (RFC 7011)
+  Ipfix,
+  /// sFlow version 5
+  Sflow,
+}
+
+impl fmt::Display for Protocol {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Protocol::V1 => write!(f, "Netflow v1"),
+      Protocol::V5 => write!(f, "Netflow v5"),
+      Protocol::V7 => write!(f, "Netflow v7"),
+      Protocol::V9 => write!(f, "Netflow v9"),
+      Protocol::Ipfix => write!(f, "IPFIX"),
+      Protocol::Sflow => write!(f, "sFlow v5"),
+    }
+  }
+}
+
+/// Errors returned while decoding flow export packets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+  /// The data is too short for the structure starting at `offset`
+  NotEnoughData {
+    protocol: Protocol,
+    /// Offset of the structure within the decoded data
+    offset: usize,
+    /// Number of bytes the structure needs
+    expected: usize,
+    /// Number of bytes available from `offset`
+    actual: usize,
+  },
+  /// A length field of the structure at `offset` holds an impossible value
+  InvalidLength {
+    protocol: Protocol,
+    offset: usize,
+    length: usize,
+  },
+  /// The export format version number is not the one being decoded
+  InvalidVersion { protocol: Protocol, version: u16 },
+  /// The data is too short to hold an export format version number
+  MissingVersion { actual: usize },
+  /// The export format version number is not one of a supported protocol
+  UnsupportedVersion { version: u16 },
+  /// A count field (of flows, of scope fields...) is outside the range allowed by the protocol
+  InvalidCount { protocol: Protocol, count: u16 },
+  /// The data is shorter than the number of flows announced by the header
+  Truncated {
+    protocol: Protocol,
+    /// Number of flows announced by the header
+    count: u16,
+    /// Number of bytes needed to hold every announced flow
+    expected: usize,
+    /// Number of bytes available
+    actual: usize,
+  },
+  /// Padding or reserved bytes at `offset` are not zero
+  InvalidPadding { protocol: Protocol, offset: usize },
+  /// A source or destination address prefix mask at `offset` exceeds the
+  /// address length
+  InvalidMask {
+    protocol: Protocol,
+    offset: usize,
+    mask: u8,
+  },
+  /// The flow at `offset` ends before it starts
+  InvalidUptime {
+    protocol: Protocol,
+    offset: usize,
+    /// SysUptime at the first packet of the flow
+    first: u32,
+    /// SysUptime at the last packet of the flow
+    last: u32,
+  },
+  /// Bytes follow the last flow announced by the header
+  TrailingData {
+    protocol: Protocol,
+    /// Offset of the first byte after the last flow
+    offset: usize,
+    /// Number of bytes after the last flow
+    length: usize,
+  },
+  /// The address type at `offset` is neither IPv4 nor IPv6
+  InvalidAddressType {
+    protocol: Protocol,
+    offset: usize,
+    address_type: u32,
+  },
+}
+
+impl Error {
+  /// Shifts the offset of an error raised while decoding a structure found at `base`
+  pub(crate) fn offset_by(self, base: usize) -> Error {
+    match self {
+      Error::NotEnoughData {
+        protocol,
+        offset,
+        expected,
+        actual,
+      } => Error::NotEnoughData {
+        protocol,
+        offset: base + offset,
+        expected,
+        actual,
+      },
+      Error::InvalidLength {
+        protocol,
+        offset,
+        length,
+      } => Error::InvalidLength {
+        protocol,
+        offset: base + offset,
+        length,
+      },
+      Error::InvalidPadding { protocol, offset } => Error::InvalidPadding {
+        protocol,
+        offset: base + offset,
+      },
+      Error::InvalidMask {
+        protocol,
+        offset,
+        mask,
+      } => Error::InvalidMask {
+        protocol,
+        offset: base + offset,
+        mask,
+      },
+      Error::InvalidUptime {
+        protocol,
+        offset,
+        first,
+        last,
+      } => Error::InvalidUptime {
+        protocol,
+        offset: base + offset,
+        first,
+        last,
+      },
+      Error::TrailingData {
+        protocol,
+        offset,
+        length,
+      } => Error::TrailingData {
+        protocol,
+        offset: base + offset,
+        length,
+      },
+      Error::InvalidAddressType {
+        protocol,
+        offset,
+        address_type,
+      } => Error::InvalidAddressType {
+        protocol,
+        offset: base + offset,
+        address_type,
+      },
+      error => error,
+    }
+  }
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::NotEnoughData {
+        protocol,
+        offset,
+        expected,
+        actual,
+      } => write!(
+        f,
+        "{}: not enough data at offset {} (expected {} bytes, got {})",
+        protocol, offset, expected, actual
+      ),
+      Error::InvalidLength {
+        protocol,
+        offset,
+        length,
+      } => write!(
+        f,
+        "{}: invalid length {} at offset {}",
+        protocol, length, offset
+      ),
+      Error::InvalidVersion { protocol, version } => write!(
+        f,
+        "{}: invalid export format version number {}",
+        protocol, version
+      ),
+      Error::MissingVersion { actual } => write!(
+        f,
+        "not enough data for an export format version number (got {} bytes)",
+        actual
+      ),
+      Error::UnsupportedVersion { version } => {
+        write!(f, "unsupported export format version number {}", version)
+      }
+      Error::InvalidCount { protocol, count } => {
+        write!(f, "{}: invalid count {}", protocol, count)
+      }
+      Error::Truncated {
+        protocol,
+        count,
+        expected,
+        actual,
+      } => write!(
+        f,
+        "{}: packet announces {} flows in {} bytes but only holds {}",
+        protocol, count, expected, actual
+      ),
+      Error::InvalidPadding { protocol, offset } => {
+        write!(f, "{}: non-zero padding at offset {}", protocol, offset)
+      }
+      Error::InvalidMask {
+        protocol,
+        offset,
+        mask,
+      } => write!(
+        f,
+        "{}: invalid prefix mask {} at offset {}",
+        protocol, mask, offset
+      ),
+      Error::InvalidUptime {
+        protocol,
+        offset,
+        first,
+        last,
+      } => write!(
+        f,
+        "{}: flow at offset {} starts at uptime {} after it ends at {}",
+        protocol, offset, first, last
+      ),
+      Error::TrailingData {
+        protocol,
+        offset,
+        length,
+      } => write!(
+        f,
+        "{}: {} bytes of trailing data at offset {}",
+        protocol, length, offset
+      ),
+      Error::InvalidAddressType {
+        protocol,
+        offset,
+        address_type,
+      } => write!(
+        f,
+        "{}: invalid address type {} at offset {}",
+        protocol, address_type, offset
+      ),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn error_implements_display() {
+    let error = Error::NotEnoughData {
+      protocol: Protocol::V5,
+      offset: 24,
+      expected: 48,
+      actual: 10,
+    };
+
+    assert_eq!(
+      error.to_string(),
+      "Netflow v5: not enough data at offset 24 (expected 48 bytes, got 10)"
+    );
+  }
+
+  #[test]
+  fn error_converts_into_boxed_error() {
+    fn parse() -> Result<(), Box<dyn std::error::Error>> {
+      Err(Error::InvalidVersion {
+        protocol: Protocol::V5,
+        version: 9,
+      })?;
+      Ok(())
+    }
+
+    assert_eq!(
+      parse().unwrap_err().to_string(),
+      "Netflow v5: invalid export format version number 9"
+    );
+  }
+}
@@ -0,0 +1,115 @@
+//! Layout shared by the v1, v5 and v7 packets: a header announcing how many
+//! records of a fixed length follow it
+
+use crate::{Error, Protocol};
+use std::iter::FusedIterator;
+
+/// Lengths and bounds of the flow count of the packets of one protocol
+pub(crate) struct Layout {
+  pub(crate) protocol: Protocol,
+  pub(crate) header_len: usize,
+  pub(crate) record_len: usize,
+  pub(crate) min_count: u16,
+  pub(crate) max_count: u16,
+}
+
+impl Layout {
+  /// Length of a packet of `count` records
+  pub(crate) fn packet_len(&self, count: u16) -> usize {
+    self.header_len + count as usize * self.record_len
+  }
+
+  /// Checks that `count`, announced by the header of `data`, is within bounds
+  /// and that `data` is long enough to hold that many records
+  pub(crate) fn check_count(&self, data: &[u8], count: u16) -> Result<usize, Error> {
+    if !(self.min_count..=self.max_count).contains(&count) {
+      return Err(Error::InvalidCount {
+        protocol: self.protocol,
+        count,
+      });
+    }
+
+    let expected = self.packet_len(count);
+    if data.len() < expected {
+      return Err(Error::Truncated {
+        protocol: self.protocol,
+        count,
+        expected,
+        actual: data.len(),
+      });
+    }
+    Ok(count as usize)
+  }
+
+  /// Number of complete records `data` holds, at most `count`
+  pub(crate) fn lenient_count(&self, data: &[u8], count: u16) -> usize {
+    let available = (data.len() - self.header_len) / self.record_len;
+    available.min(count as usize)
+  }
+
+  /// The first `count` records following the header of `data`
+  pub(crate) fn records<'a>(&self, data: &'a [u8], count: usize) -> Records<'a> {
+    Records {
+      data: &data[self.header_len..self.header_len + count * self.record_len],
+      record_len: self.record_len,
+    }
+  }
+
+  /// Record at `index` following the header of `data`, if below `count`
+  pub(crate) fn record<'a>(&self, data: &'a [u8], count: usize, index: usize) -> Option<&'a [u8]> {
+    if index >= count {
+      return None;
+    }
+    let start = self.header_len + index * self.record_len;
+    Some(&data[start..start + self.record_len])
+  }
+}
+
+/// Iterator over the data of fixed-length records, from both ends
+#[derive(Clone)]
+pub(crate) struct Records<'a> {
+  /// The records not yet returned from either end
+  data: &'a [u8],
+  record_len: usize,
+}
+
+impl<'a> Iterator for Records<'a> {
+  type Item = &'a [u8];
+
+  fn next(&mut self) -> Option<&'a [u8]> {
+    if self.data.len() < self.record_len {
+      return None;
+    }
+
+    let (data, rest) = self.data.split_at(self.record_len);
+    self.data = rest;
+    Some(data)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let remainder = self.data.len() / self.record_len;
+    (remainder, Some(remainder))
+  }
+
+  fn nth(&mut self, n: usize) -> Option<&'a [u8]> {
+    let skipped = n.saturating_mul(self.record_len).min(self.data.len());
+    self.data = &self.data[skipped..];
+    self.next()
+  }
+}
+
+impl<'a> DoubleEndedIterator for Records<'a> {
+  fn next_back(&mut self) -> Option<&'a [u8]> {
+    if self.data.len() < self.record_len {
+      return None;
+    }
+
+    let (rest, data) = self.data.split_at(self.data.len() - self.record_len);
+    self.data = rest;
+    Some(data)
+  }
+}
+
+impl<'a> ExactSizeIterator for Records<'a> {}
+
+impl<'a> FusedIterator for Records<'a> {}
@@ -0,0 +1,198 @@
+use std::net::IpAddr;
+use std::time::SystemTime;
+
+/// Fields of a flow record common to every protocol, so that code consuming
+/// flows does not depend on how they were exported
+///
+/// Fields a record does not carry, such as AS numbers in a v9 record whose
+/// template omits them, are `None`. Integers are widened to the largest size
+/// any protocol uses.
+pub trait Flow {
+  /// Source IPv4 or IPv6 address
+  fn source_addr(&self) -> Option<IpAddr>;
+  /// Destination IPv4 or IPv6 address
+  fn destination_addr(&self) -> Option<IpAddr>;
+  /// TCP or UDP source port number or equivalent
+  fn source_port(&self) -> Option<u16>;
+  /// TCP or UDP destination port number or equivalent
+  fn destination_port(&self) -> Option<u16>;
+  /// IP protocol type (for example, TCP = 6, UDP = 17, ...)
+  fn protocol(&self) -> Option<u8>;
+
+  /// Packets in the flow
+  fn packets(&self) -> Option<u64>;
+  /// Total number of Layer 3 bytes in the packets of the flow
+  fn bytes(&self) -> Option<u64>;
+
+  /// Index of input interface
+  fn input(&self) -> Option<u32>;
+  /// Index of output interface
+  fn output(&self) -> Option<u32>;
+  /// IPv4 or IPv6 address of next hop router
+  fn next_hop(&self) -> Option<IpAddr>;
+
+  /// Autonomous system number of the source
+  fn source_as(&self) -> Option<u32>;
+  /// Autonomous system number of the destination
+  fn destination_as(&self) -> Option<u32>;
+  /// Source address prefix mask bits
+  fn source_mask(&self) -> Option<u8>;
+  /// Destination address prefix mask bits
+  fn destination_mask(&self) -> Option<u8>;
+
+  /// IP type of service (ToS)
+  fn type_of_service(&self) -> Option<u8>;
+  /// Cumulative OR of TCP flags
+  fn tcp_flags(&self) -> Option<u8>;
+
+  /// SysUptime of the export device at start of flow
+  fn first_packet_sys_uptime(&self) -> Option<u32>;
+  /// SysUptime of the export device at the time the last packet of the flow was received
+  fn last_packet_sys_uptime(&self) -> Option<u32>;
+
+  /// Wall-clock time at start of flow, for records carrying absolute timestamps
+  ///
+  /// Records timestamped with the SysUptime of the export device need the
+  /// header of their packet instead, for example with
+  /// `v5::raw::Header::system_time`.
+  fn start_time(&self) -> Option<SystemTime> {
+    None
+  }
+
+  /// Wall-clock time at end of flow, for records carrying absolute timestamps
+  fn end_time(&self) -> Option<SystemTime> {
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{ipfix, v1, v5, v7, v9};
+  use std::net::Ipv4Addr;
+  use std::time::{Duration, UNIX_EPOCH};
+
+  /// Protocol-independent summary, as analytics code would compute it
+  fn summary(flow: &dyn Flow) -> (Option<IpAddr>, Option<u16>, u64, Option<u32>) {
+    (
+      flow.source_addr(),
+      flow.destination_port(),
+      flow.bytes().unwrap_or(0),
+      flow.source_as(),
+    )
+  }
+
+  #[test]
+  fn flow_should_abstract_v1_records() {
+    let set = v1::raw::FlowSet::new(&v1::tests::FLOW_PACKET_1).unwrap();
+    let record = set.records().next().unwrap();
+
+    assert_eq!(
+      summary(&record),
+      (
+        Some(IpAddr::V4(Ipv4Addr::new(10, 1, 4, 21))),
+        Some(80),
+        4520,
+        None
+      )
+    );
+    assert_eq!(Flow::destination_mask(&record), None);
+    assert_eq!(Flow::first_packet_sys_uptime(&record), Some(183698211));
+  }
+
+  #[test]
+  fn flow_should_abstract_v5_records() {
+    let set = v5::raw::FlowSet::new(&v5::tests::FLOW_PACKET_1).unwrap();
+    let record = set.records().next().unwrap();
+    let owned = v5::FlowRecord::from(&record);
+    let expected = (
+      Some(IpAddr::V4(Ipv4Addr::new(125, 238, 46, 48))),
+      Some(443),
+      1708,
+      Some(4771),
+    );
+
+    assert_eq!(summary(&record), expected);
+    assert_eq!(summary(&owned), expected);
+    assert_eq!(Flow::first_packet_sys_uptime(&record), Some(51402145));
+    assert_eq!(Flow::start_time(&record), None);
+  }
+
+  #[test]
+  fn flow_should_abstract_v7_records() {
+    let set = v7::raw::FlowSet::new(&v7::tests::FLOW_PACKET_1).unwrap();
+    let record = set.records().next().unwrap();
+
+    assert_eq!(
+      summary(&record),
+      (
+        Some(IpAddr::V4(Ipv4Addr::new(172, 16, 5, 10))),
+        Some(60112),
+        81240,
+        Some(0)
+      )
+    );
+    assert_eq!(Flow::destination_mask(&record), Some(24));
+    assert_eq!(Flow::last_packet_sys_uptime(&record), Some(3021721));
+  }
+
+  #[test]
+  fn flow_should_abstract_v9_records() {
+    let exporter = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+    let packet = v9::raw::Packet::new(&v9::tests::FLOW_PACKET_1).unwrap();
+    let mut cache = v9::TemplateCache::new();
+    cache.learn(exporter, &packet).unwrap();
+    let set = packet
+      .flowsets()
+      .map(|set| set.unwrap())
+      .find(|set| set.id() == 256)
+      .unwrap();
+    let template = cache.get(exporter, 1, 256).unwrap();
+    let record = set.records(template).next().unwrap();
+
+    assert_eq!(
+      summary(&record),
+      (
+        Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))),
+        Some(443),
+        1500,
+        None
+      )
+    );
+    assert_eq!(Flow::first_packet_sys_uptime(&record), Some(60000));
+  }
+
+  #[test]
+  fn flow_should_abstract_ipfix_records() {
+    let exporter = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+    let message = ipfix::raw::Message::new(&ipfix::tests::MESSAGE_1).unwrap();
+    let mut cache = ipfix::TemplateCache::new();
+    cache.learn(exporter, &message).unwrap();
+    let domain = message.header().observation_domain_id();
+    let set = message
+      .sets()
+      .map(|set| set.unwrap())
+      .find(|set| cache.get(exporter, domain, set.id()).is_some() && set.is_data())
+      .unwrap();
+    let template = cache.get(exporter, domain, set.id()).unwrap();
+    let record = set.records(template).next().unwrap().unwrap();
+
+    assert_eq!(
+      summary(&record),
+      (
+        Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))),
+        Some(443),
+        1500,
+        None
+      )
+    );
+    assert_eq!(
+      Flow::start_time(&record),
+      Some(UNIX_EPOCH + Duration::from_millis(1544476581000))
+    );
+    assert_eq!(
+      Flow::end_time(&record),
+      Some(UNIX_EPOCH + Duration::from_millis(1544476581500))
+    );
+  }
+}
@@ -0,0 +1,130 @@
+use super::Endianness;
+use crate::v5::{FlowRecord, PacketHeader};
+use std::net::Ipv4Addr;
+
+/// A v5 record as stored in a flow-tools file, with the fields of the packet
+/// header and the exporter it came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FlowEntry {
+  /// Address of the exporting device
+  pub exporter: Ipv4Addr,
+  /// Seconds since the epoch at which the packet was exported
+  pub unix_secs: u32,
+  /// Residual nanoseconds of `unix_secs`
+  pub unix_nsecs: u32,
+  /// Uptime of the exporting device when the packet was exported
+  pub sys_uptime_msecs: u32,
+  /// Type of flow-switching engine
+  pub engine_type: u8,
+  /// Slot number of the flow-switching engine
+  pub engine_id: u8,
+  /// The record itself
+  pub record: FlowRecord,
+}
+
+impl FlowEntry {
+  /// Length of a stored record, the `fts3rec_v5` structure of flow-tools
+  pub const LEN: usize = 64;
+
+  /// An entry for a record of a packet sent by `exporter`
+  pub fn new(exporter: Ipv4Addr, header: &PacketHeader, record: FlowRecord) -> FlowEntry {
+    FlowEntry {
+      exporter,
+      unix_secs: header.unix_secs,
+      unix_nsecs: header.unix_nsecs,
+      sys_uptime_msecs: header.sys_uptime_msecs,
+      engine_type: header.engine_type,
+      engine_id: header.engine_id,
+      record,
+    }
+  }
+
+  pub(crate) fn write(&self, order: Endianness, data: &mut [u8; FlowEntry::LEN]) {
+    let record = &self.record;
+    order.write_u32(&mut data[0..4], self.unix_secs);
+    order.write_u32(&mut data[4..8], self.unix_nsecs);
+    order.write_u32(&mut data[8..12], self.sys_uptime_msecs);
+    order.write_u32(&mut data[12..16], u32::from(self.exporter));
+    order.write_u32(&mut data[16..20], u32::from(record.source_addr));
+    order.write_u32(&mut data[20..24], u32::from(record.destination_addr));
+    order.write_u32(&mut data[24..28], u32::from(record.next_hop));
+    order.write_u16(&mut data[28..30], record.input);
+    order.write_u16(&mut data[30..32], record.output);
+    order.write_u32(&mut data[32..36], record.packets);
+    order.write_u32(&mut data[36..40], record.bytes);
+    order.write_u32(&mut data[40..44], record.first_packet_sys_uptime);
+    order.write_u32(&mut data[44..48], record.last_packet_sys_uptime);
+    order.write_u16(&mut data[48..50], record.source_port);
+    order.write_u16(&mut data[50..52], record.destination_port);
+    data[52] = record.protocol;
+    data[53] = record.type_of_service;
+    data[54] = record.tcp_flags;
+    data[55] = 0;
+    data[56] = self.engine_type;
+    data[57] = self.engine_id;
+    data[58] = record.source_mask;
+    data[59] = record.destination_mask;
+    order.write_u16(&mut data[60..62], record.source_as);
+    order.write_u16(&mut data[62..64], record.destination_as);
+  }
+
+  pub(crate) fn read(order: Endianness, data: &[u8; FlowEntry::LEN]) -> FlowEntry {
+    FlowEntry {
+      exporter: Ipv4Addr::from(order.read_u32(&data[12..16])),
+      unix_secs: order.read_u32(&data[0..4]),
+      unix_nsecs: order.read_u32(&data[4..8]),
+      sys_uptime_msecs: order.read_u32(&data[8..12]),
+      engine_type: data[56],
+      engine_id: data[57],
+      record: FlowRecord {
+        source_addr: Ipv4Addr::from(order.read_u32(&data[16..20])),
+        destination_addr: Ipv4Addr::from(order.read_u32(&data[20..24])),
+        next_hop: Ipv4Addr::from(order.read_u32(&data[24..28])),
+        input: order.read_u16(&data[28..30]),
+        output: order.read_u16(&data[30..32]),
+        packets: order.read_u32(&data[32..36]),
+        bytes: order.read_u32(&data[36..40]),
+        first_packet_sys_uptime: order.read_u32(&data[40..44]),
+        last_packet_sys_uptime: order.read_u32(&data[44..48]),
+        source_port: order.read_u16(&data[48..50]),
+        destination_port: order.read_u16(&data[50..52]),
+        tcp_flags: data[54],
+        protocol: data[52],
+        type_of_service: data[53],
+        source_as: order.read_u16(&data[60..62]),
+        destination_as: order.read_u16(&data[62..64]),
+        source_mask: data[58],
+        destination_mask: data[59],
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::entries;
+  use super::*;
+
+  #[test]
+  fn entry_should_be_laid_out_as_flow_tools() {
+    let entry = entries()[0];
+    let mut data = [0; FlowEntry::LEN];
+    entry.write(Endianness::Big, &mut data);
+
+    // The packet header fields, then the exporter
+    assert_eq!(&data[0..4], &[0x5c, 0x0e, 0xd7, 0xa5]);
+    assert_eq!(&data[8..12], &[0x03, 0x11, 0x5d, 0xd8]);
+    assert_eq!(&data[12..16], &[192, 0, 2, 1]);
+    // Addresses and counters in their wire order
+    assert_eq!(&data[16..20], &[0x7d, 0xee, 0x2e, 0x30]);
+    assert_eq!(&data[32..40], &[0, 0, 0, 4, 0, 0, 0x06, 0xac]);
+    // Protocol, ToS and TCP flags, then the engine
+    assert_eq!(&data[52..56], &[0x06, 0x00, 0x10, 0x00]);
+    assert_eq!(FlowEntry::read(Endianness::Big, &data), entry);
+
+    entry.write(Endianness::Little, &mut data);
+    assert_eq!(&data[0..4], &[0xa5, 0xd7, 0x0e, 0x5c]);
+    assert_eq!(&data[12..16], &[1, 2, 0, 192]);
+    assert_eq!(FlowEntry::read(Endianness::Little, &data), entry);
+  }
+}
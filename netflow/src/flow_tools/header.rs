@@ -0,0 +1,323 @@
+use super::{Endianness, Error};
+use std::io::{self, Read};
+use std::net::Ipv4Addr;
+
+const MAGIC: u16 = 0xcf10;
+const STREAM_VERSION: u8 = 3;
+const EXPORT_VERSION: u16 = 5;
+const VENDOR_CISCO: u8 = 1;
+/// Largest header flow-tools reads, `FT_IO_MAXHEADER`
+const MAX_LEN: usize = 16384;
+/// Magic number, byte order, stream version and header length
+const FIXED_LEN: usize = 8;
+
+// Header field types, `FT_TLV_*`
+const TLV_VENDOR: u16 = 0x1;
+const TLV_EX_VER: u16 = 0x2;
+const TLV_EXPORTER_IP: u16 = 0x5;
+const TLV_CAP_START: u16 = 0x6;
+const TLV_CAP_END: u16 = 0x7;
+const TLV_HEADER_FLAGS: u16 = 0x8;
+const TLV_FLOW_COUNT: u16 = 0xa;
+const TLV_FLOW_LOST: u16 = 0xb;
+const TLV_FLOW_MISORDERED: u16 = 0xc;
+const TLV_PKT_CORRUPT: u16 = 0xd;
+const TLV_SEQ_RESET: u16 = 0xe;
+const TLV_CAP_HOSTNAME: u16 = 0xf;
+const TLV_COMMENTS: u16 = 0x10;
+
+// Header flags, `FT_HEADER_FLAG_*`
+const FLAG_DONE: u32 = 0x1;
+const FLAG_COMPRESS: u32 = 0x2;
+
+/// The header of a flow-tools file
+///
+/// The counters and capture times are those of the whole file: a writer
+/// fills them in when it is finished, and `complete` tells whether it was.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileHeader {
+  /// Byte order of the header and records
+  pub byte_order: Endianness,
+  /// Whether the records are compressed with zlib, set by the writer from
+  /// its `Compression`
+  pub compressed: bool,
+  /// Whether the writer was finished, set by the writer
+  pub complete: bool,
+  /// Address of the device that exported every record, if there is only one
+  pub exporter: Option<Ipv4Addr>,
+  /// Seconds since the epoch at which the capture started
+  pub capture_start: u32,
+  /// Seconds since the epoch at which the capture ended
+  pub capture_end: u32,
+  /// Number of records in the file
+  pub flow_count: u32,
+  /// Flows the exporters sent but the collector missed, going by sequence
+  /// numbers
+  pub flows_lost: u32,
+  /// Flows received out of order
+  pub flows_misordered: u32,
+  /// Packets that could not be decoded
+  pub packets_corrupt: u32,
+  /// Times an exporter restarted its sequence numbers
+  pub sequence_resets: u32,
+  /// Name of the collecting host
+  pub hostname: Option<String>,
+  /// Free text describing the capture
+  pub comments: Option<String>,
+}
+
+impl Default for FileHeader {
+  fn default() -> FileHeader {
+    FileHeader {
+      byte_order: Endianness::Little,
+      compressed: false,
+      complete: false,
+      exporter: None,
+      capture_start: 0,
+      capture_end: 0,
+      flow_count: 0,
+      flows_lost: 0,
+      flows_misordered: 0,
+      packets_corrupt: 0,
+      sequence_resets: 0,
+      hostname: None,
+      comments: None,
+    }
+  }
+}
+
+impl FileHeader {
+  /// The header in its file format
+  ///
+  /// Its length only depends on `exporter`, `hostname` and `comments`, so a
+  /// writer can rewrite it in place once the counters are known.
+  pub(crate) fn to_bytes(&self) -> io::Result<Vec<u8>> {
+    let order = self.byte_order;
+    let mut data = Vec::with_capacity(128);
+    data.extend_from_slice(&MAGIC.to_be_bytes());
+    data.extend_from_slice(&[order.id(), STREAM_VERSION, 0, 0, 0, 0]);
+
+    let mut field = |field_type: u16, value: &[u8]| {
+      let mut tl = [0; 4];
+      order.write_u16(&mut tl[0..2], field_type);
+      order.write_u16(&mut tl[2..4], value.len() as u16);
+      data.extend_from_slice(&tl);
+      data.extend_from_slice(value);
+    };
+    let u32 = |value: u32| {
+      let mut data = [0; 4];
+      order.write_u32(&mut data, value);
+      data
+    };
+    let mut u16 = [0; 2];
+    order.write_u16(&mut u16, EXPORT_VERSION);
+
+    let mut flags = 0;
+    if self.complete {
+      flags |= FLAG_DONE;
+    }
+    if self.compressed {
+      flags |= FLAG_COMPRESS;
+    }
+
+    field(TLV_VENDOR, &[VENDOR_CISCO]);
+    field(TLV_EX_VER, &u16);
+    if let Some(exporter) = self.exporter {
+      field(TLV_EXPORTER_IP, &u32(u32::from(exporter)));
+    }
+    field(TLV_CAP_START, &u32(self.capture_start));
+    field(TLV_CAP_END, &u32(self.capture_end));
+    field(TLV_HEADER_FLAGS, &u32(flags));
+    field(TLV_FLOW_COUNT, &u32(self.flow_count));
+    field(TLV_FLOW_LOST, &u32(self.flows_lost));
+    field(TLV_FLOW_MISORDERED, &u32(self.flows_misordered));
+    field(TLV_PKT_CORRUPT, &u32(self.packets_corrupt));
+    field(TLV_SEQ_RESET, &u32(self.sequence_resets));
+    // Strings are stored with their terminating NUL
+    for &(field_type, text) in [
+      (TLV_CAP_HOSTNAME, &self.hostname),
+      (TLV_COMMENTS, &self.comments),
+    ]
+    .iter()
+    {
+      if let Some(text) = text {
+        let mut value = text.as_bytes().to_vec();
+        value.push(0);
+        if value.len() > MAX_LEN {
+          return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "header text too long",
+          ));
+        }
+        field(field_type, &value);
+      }
+    }
+
+    if data.len() > MAX_LEN {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "header too long",
+      ));
+    }
+    // The header length counts the fixed fields too: it is the offset of the
+    // first record
+    let len = data.len() as u32;
+    order.write_u32(&mut data[4..8], len);
+    Ok(data)
+  }
+
+  /// Reads the header at the start of a file, leaving `reader` at the first
+  /// record
+  pub(crate) fn read<R: Read>(reader: &mut R) -> Result<FileHeader, Error> {
+    let mut fixed = [0; FIXED_LEN];
+    reader.read_exact(&mut fixed)?;
+    let magic = u16::from_be_bytes([fixed[0], fixed[1]]);
+    if magic != MAGIC {
+      return Err(Error::InvalidMagic(magic));
+    }
+    let order = Endianness::from_id(fixed[2]).ok_or(Error::InvalidByteOrder(fixed[2]))?;
+    if fixed[3] != STREAM_VERSION {
+      return Err(Error::UnsupportedStreamVersion(fixed[3]));
+    }
+    let len = order.read_u32(&fixed[4..8]);
+    if (len as usize) < FIXED_LEN || len as usize > MAX_LEN {
+      return Err(Error::InvalidHeaderLength(len));
+    }
+    let mut data = vec![0; len as usize - FIXED_LEN];
+    reader.read_exact(&mut data)?;
+
+    let mut header = FileHeader {
+      byte_order: order,
+      ..FileHeader::default()
+    };
+    let mut export_version = 0;
+    let mut offset = 0;
+    while offset + 4 <= data.len() {
+      let field_type = order.read_u16(&data[offset..offset + 2]);
+      let field_len = usize::from(order.read_u16(&data[offset + 2..offset + 4]));
+      let invalid = Error::InvalidHeader {
+        offset: FIXED_LEN + offset,
+      };
+      let value = match data.get(offset + 4..offset + 4 + field_len) {
+        Some(value) => value,
+        None => return Err(invalid),
+      };
+      let u32 = || match value.len() {
+        4 => Ok(order.read_u32(value)),
+        _ => Err(Error::InvalidHeader {
+          offset: FIXED_LEN + offset,
+        }),
+      };
+      let text = || {
+        let end = value.iter().position(|&b| b == 0).unwrap_or(value.len());
+        Some(String::from_utf8_lossy(&value[..end]).into_owned())
+      };
+
+      match field_type {
+        TLV_EX_VER if value.len() == 2 => export_version = order.read_u16(value),
+        TLV_EX_VER => return Err(invalid),
+        TLV_EXPORTER_IP => header.exporter = Some(Ipv4Addr::from(u32()?)),
+        TLV_CAP_START => header.capture_start = u32()?,
+        TLV_CAP_END => header.capture_end = u32()?,
+        TLV_HEADER_FLAGS => {
+          let flags = u32()?;
+          header.complete = flags & FLAG_DONE != 0;
+          header.compressed = flags & FLAG_COMPRESS != 0;
+        }
+        TLV_FLOW_COUNT => header.flow_count = u32()?,
+        TLV_FLOW_LOST => header.flows_lost = u32()?,
+        TLV_FLOW_MISORDERED => header.flows_misordered = u32()?,
+        TLV_PKT_CORRUPT => header.packets_corrupt = u32()?,
+        TLV_SEQ_RESET => header.sequence_resets = u32()?,
+        TLV_CAP_HOSTNAME => header.hostname = text(),
+        TLV_COMMENTS => header.comments = text(),
+        // Vendor, aggregation, rotation and interface names
+        _ => (),
+      }
+      offset += 4 + field_len;
+    }
+
+    if export_version != EXPORT_VERSION {
+      return Err(Error::UnsupportedExportVersion(export_version));
+    }
+    Ok(header)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn header_should_be_laid_out_as_flow_tools() {
+    let header = FileHeader {
+      byte_order: Endianness::Big,
+      compressed: true,
+      flow_count: 29,
+      comments: Some(String::from("test")),
+      ..FileHeader::default()
+    };
+    let data = header.to_bytes().unwrap();
+
+    assert_eq!(&data[0..8], &[0xcf, 0x10, 2, 3, 0, 0, 0, 92]);
+    assert_eq!(data.len(), 92);
+    // Vendor, then the export version
+    assert_eq!(&data[8..19], &[0, 1, 0, 1, 1, 0, 2, 0, 2, 0, 5]);
+    // Flags
+    assert_eq!(&data[35..43], &[0, 8, 0, 4, 0, 0, 0, 2]);
+    assert_eq!(&data[83..], &[0, 0x10, 0, 5, b't', b'e', b's', b't', 0]);
+    assert_eq!(FileHeader::read(&mut &data[..]).unwrap(), header);
+  }
+
+  #[test]
+  fn header_should_skip_unknown_fields() {
+    let header = FileHeader::default();
+    let mut data = header.to_bytes().unwrap();
+    // An interface name
+    data.extend_from_slice(&[0x11, 0, 4, 0, 1, 0, b'x', 0]);
+    data[4] += 8;
+
+    assert_eq!(FileHeader::read(&mut &data[..]).unwrap(), header);
+  }
+
+  #[test]
+  fn header_should_be_validated() {
+    let data = FileHeader::default().to_bytes().unwrap();
+    let read = |change: &dyn Fn(&mut Vec<u8>)| {
+      let mut data = data.clone();
+      change(&mut data);
+      FileHeader::read(&mut &data[..]).unwrap_err()
+    };
+
+    match read(&|data| data[1] = 0x11) {
+      Error::InvalidMagic(0xcf11) => (),
+      error => panic!("unexpected error {:?}", error),
+    }
+    match read(&|data| data[2] = 3) {
+      Error::InvalidByteOrder(3) => (),
+      error => panic!("unexpected error {:?}", error),
+    }
+    match read(&|data| data[3] = 1) {
+      Error::UnsupportedStreamVersion(1) => (),
+      error => panic!("unexpected error {:?}", error),
+    }
+    match read(&|data| data[4] = 4) {
+      Error::InvalidHeaderLength(4) => (),
+      error => panic!("unexpected error {:?}", error),
+    }
+    // Export version 7
+    match read(&|data| data[17] = 7) {
+      Error::UnsupportedExportVersion(7) => (),
+      error => panic!("unexpected error {:?}", error),
+    }
+    // Vendor field running past the header
+    match read(&|data| data[10] = 0xff) {
+      Error::InvalidHeader { offset: 8 } => (),
+      error => panic!("unexpected error {:?}", error),
+    }
+    match read(&|data| data.truncate(20)) {
+      Error::Io(_) => (),
+      error => panic!("unexpected error {:?}", error),
+    }
+  }
+}
@@ -0,0 +1,264 @@
+//! Writes decoded Netflow v5 records to, and reads them back from, flow-tools
+//! files, as written by `flow-capture` and read by `flow-cat`, `flow-print`
+//! and nfdump's `ft2nfdump`
+//!
+//! A file is a version 3 stream: a header of type-length-value fields
+//! followed by fixed size v5 records, either plain or compressed with zlib.
+//! Both byte orders are read; records are written in the one the header
+//! declares.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use netflow::flow_tools::{Compression, FileHeader, FlowReader, FlowWriter};
+//! use netflow::v5::raw::FlowSet;
+//! use netflow::v5::{FlowRecord, PacketHeader};
+//! use std::net::Ipv4Addr;
+//!
+//! # let data = [0; 24];
+//! let set = FlowSet::new(&data)?;
+//! let header = PacketHeader::from(&set.header());
+//! let records: Vec<FlowRecord> = set.records().map(|r| FlowRecord::from(&r)).collect();
+//!
+//! let mut writer = FlowWriter::create("ft-v05.2019-01-01", FileHeader::default(), Compression::Zlib(5))?;
+//! writer.write_packet(Ipv4Addr::new(192, 0, 2, 1), &header, &records)?;
+//! writer.finish()?;
+//!
+//! for entry in FlowReader::open("ft-v05.2019-01-01")? {
+//!   let entry = entry?;
+//!   println!("{} {:?}", entry.exporter, entry.record);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod entry;
+mod header;
+mod reader;
+mod writer;
+
+pub use entry::FlowEntry;
+pub use header::FileHeader;
+pub use reader::FlowReader;
+pub use writer::{Compression, FlowWriter};
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use std::fmt;
+use std::io;
+
+/// Byte order of the header fields and records of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+  /// Least significant byte first, as written on x86 hosts
+  Little,
+  /// Most significant byte first
+  Big,
+}
+
+impl Endianness {
+  /// Identifier of the byte order in the file header
+  pub(crate) fn id(self) -> u8 {
+    match self {
+      Endianness::Little => 1,
+      Endianness::Big => 2,
+    }
+  }
+
+  pub(crate) fn from_id(id: u8) -> Option<Endianness> {
+    match id {
+      1 => Some(Endianness::Little),
+      2 => Some(Endianness::Big),
+      _ => None,
+    }
+  }
+
+  pub(crate) fn read_u16(self, data: &[u8]) -> u16 {
+    match self {
+      Endianness::Little => LittleEndian::read_u16(data),
+      Endianness::Big => BigEndian::read_u16(data),
+    }
+  }
+
+  pub(crate) fn read_u32(self, data: &[u8]) -> u32 {
+    match self {
+      Endianness::Little => LittleEndian::read_u32(data),
+      Endianness::Big => BigEndian::read_u32(data),
+    }
+  }
+
+  pub(crate) fn write_u16(self, data: &mut [u8], value: u16) {
+    match self {
+      Endianness::Little => LittleEndian::write_u16(data, value),
+      Endianness::Big => BigEndian::write_u16(data, value),
+    }
+  }
+
+  pub(crate) fn write_u32(self, data: &mut [u8], value: u32) {
+    match self {
+      Endianness::Little => LittleEndian::write_u32(data, value),
+      Endianness::Big => BigEndian::write_u32(data, value),
+    }
+  }
+}
+
+/// Errors returned while reading a flow-tools file
+#[derive(Debug)]
+pub enum Error {
+  /// Reading the file failed, or it ended in the middle of its header
+  Io(io::Error),
+  /// The file does not start with the flow-tools magic number
+  InvalidMagic(u16),
+  /// The header declares neither little nor big endian records
+  InvalidByteOrder(u8),
+  /// The file is not a version 3 stream, such as those of flow-tools 0.56
+  /// and earlier
+  UnsupportedStreamVersion(u8),
+  /// The header is shorter than its fixed fields or longer than flow-tools
+  /// allows
+  InvalidHeaderLength(u32),
+  /// The header field at `offset` runs past the header, or has a length
+  /// that does not fit its type
+  InvalidHeader { offset: usize },
+  /// The records are not Netflow v5 ones; 0 when the header declares no
+  /// export version
+  UnsupportedExportVersion(u16),
+  /// The file ends with `length` bytes of an incomplete record
+  TruncatedRecord { length: usize },
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::Io(error) => write!(f, "cannot read flow-tools file: {}", error),
+      Error::InvalidMagic(magic) => write!(f, "invalid flow-tools magic number {:#06x}", magic),
+      Error::InvalidByteOrder(order) => write!(f, "invalid byte order {}", order),
+      Error::UnsupportedStreamVersion(version) => {
+        write!(f, "unsupported stream version {}", version)
+      }
+      Error::InvalidHeaderLength(length) => write!(f, "invalid header length {}", length),
+      Error::InvalidHeader { offset } => write!(f, "invalid header field at offset {}", offset),
+      Error::UnsupportedExportVersion(version) => {
+        write!(f, "unsupported export version {}", version)
+      }
+      Error::TruncatedRecord { length } => {
+        write!(f, "file ends with {} bytes of a record", length)
+      }
+    }
+  }
+}
+
+impl std::error::Error for Error {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Error::Io(error) => Some(error),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for Error {
+  fn from(error: io::Error) -> Error {
+    Error::Io(error)
+  }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use super::*;
+  use crate::v5::raw::FlowSet;
+  use crate::v5::{self, FlowRecord, PacketHeader};
+  use std::io::Cursor;
+  use std::net::Ipv4Addr;
+
+  pub const EXPORTER: Ipv4Addr = Ipv4Addr::new(192, 0, 2, 1);
+
+  /// The entries of the v5 sample packet, as exported by `EXPORTER`
+  pub fn entries() -> Vec<FlowEntry> {
+    let set = FlowSet::new(&v5::tests::FLOW_PACKET_1).unwrap();
+    let header = PacketHeader::from(&set.header());
+    set
+      .records()
+      .map(|record| FlowEntry::new(EXPORTER, &header, FlowRecord::from(&record)))
+      .collect()
+  }
+
+  fn round_trip(byte_order: Endianness, compression: Compression) -> (FileHeader, Vec<FlowEntry>) {
+    let header = FileHeader {
+      byte_order,
+      exporter: Some(EXPORTER),
+      hostname: Some(String::from("collector")),
+      ..FileHeader::default()
+    };
+    let mut writer = FlowWriter::new(Cursor::new(Vec::new()), header, compression).unwrap();
+    for entry in &entries() {
+      writer.write(entry).unwrap();
+    }
+    let data = writer.finish().unwrap().into_inner();
+
+    let reader = FlowReader::new(&data[..]).unwrap();
+    let header = reader.header().clone();
+    (header, reader.collect::<Result<_, _>>().unwrap())
+  }
+
+  #[test]
+  fn records_should_round_trip_in_every_format() {
+    for &byte_order in [Endianness::Little, Endianness::Big].iter() {
+      for &compression in [
+        Compression::None,
+        Compression::Zlib(0),
+        Compression::Zlib(9),
+      ]
+      .iter()
+      {
+        let (header, read) = round_trip(byte_order, compression);
+
+        assert_eq!(read, entries());
+        assert_eq!(header.byte_order, byte_order);
+        assert_eq!(header.compressed, compression != Compression::None);
+        assert!(header.complete);
+        assert_eq!(header.flow_count, 29);
+        assert_eq!(header.exporter, Some(EXPORTER));
+        assert_eq!(header.hostname.as_deref(), Some("collector"));
+        assert_eq!(header.capture_start, 0x5c0e_d7a5);
+        assert_eq!(header.capture_end, 0x5c0e_d7a5);
+      }
+    }
+  }
+
+  #[test]
+  fn compression_should_shrink_files() {
+    let write = |compression| {
+      let mut writer = FlowWriter::new(Vec::new(), FileHeader::default(), compression).unwrap();
+      for entry in &entries() {
+        writer.write(entry).unwrap();
+      }
+      writer.into_inner().unwrap().len()
+    };
+
+    assert!(write(Compression::Zlib(9)) < write(Compression::None) / 2);
+  }
+
+  #[test]
+  fn streamed_files_should_be_readable_before_completion() {
+    let mut writer = FlowWriter::new(Vec::new(), FileHeader::default(), Compression::None).unwrap();
+    writer.write(&entries()[0]).unwrap();
+    let data = writer.into_inner().unwrap();
+    let reader = FlowReader::new(&data[..]).unwrap();
+
+    assert!(!reader.header().complete);
+    assert_eq!(reader.header().flow_count, 0);
+    assert_eq!(reader.count(), 1);
+  }
+
+  #[test]
+  fn truncated_records_should_be_reported() {
+    let mut writer = FlowWriter::new(Vec::new(), FileHeader::default(), Compression::None).unwrap();
+    writer.write(&entries()[0]).unwrap();
+    let data = writer.into_inner().unwrap();
+    let mut reader = FlowReader::new(&data[..data.len() - 4]).unwrap();
+
+    match reader.next() {
+      Some(Err(Error::TruncatedRecord { length: 60 })) => (),
+      result => panic!("unexpected result {:?}", result),
+    }
+  }
+}
@@ -0,0 +1,80 @@
+use super::{Error, FileHeader, FlowEntry};
+use flate2::read::ZlibDecoder;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+#[derive(Debug)]
+enum Source<R: Read> {
+  Plain(R),
+  Zlib(ZlibDecoder<R>),
+}
+
+impl<R: Read> Read for Source<R> {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    match self {
+      Source::Plain(reader) => reader.read(buf),
+      Source::Zlib(decoder) => decoder.read(buf),
+    }
+  }
+}
+
+/// Reads the v5 records of a flow-tools file, compressed or not
+#[derive(Debug)]
+pub struct FlowReader<R: Read> {
+  source: Source<R>,
+  header: FileHeader,
+}
+
+impl FlowReader<BufReader<File>> {
+  /// Opens a file
+  pub fn open<P: AsRef<Path>>(path: P) -> Result<FlowReader<BufReader<File>>, Error> {
+    FlowReader::new(BufReader::new(File::open(path)?))
+  }
+}
+
+impl<R: Read> FlowReader<R> {
+  /// Reads the header of a file
+  pub fn new(mut reader: R) -> Result<FlowReader<R>, Error> {
+    let header = FileHeader::read(&mut reader)?;
+    let source = if header.compressed {
+      Source::Zlib(ZlibDecoder::new(reader))
+    } else {
+      Source::Plain(reader)
+    };
+    Ok(FlowReader { source, header })
+  }
+
+  /// The header of the file
+  pub fn header(&self) -> &FileHeader {
+    &self.header
+  }
+
+  /// Reads the next entry, or `None` at the end of the file
+  pub fn next_entry(&mut self) -> Result<Option<FlowEntry>, Error> {
+    let mut data = [0; FlowEntry::LEN];
+    let mut length = 0;
+    while length < data.len() {
+      match self.source.read(&mut data[length..]) {
+        Ok(0) => break,
+        Ok(n) => length += n,
+        Err(ref error) if error.kind() == io::ErrorKind::Interrupted => (),
+        Err(error) => return Err(Error::Io(error)),
+      }
+    }
+
+    match length {
+      0 => Ok(None),
+      FlowEntry::LEN => Ok(Some(FlowEntry::read(self.header.byte_order, &data))),
+      length => Err(Error::TruncatedRecord { length }),
+    }
+  }
+}
+
+impl<R: Read> Iterator for FlowReader<R> {
+  type Item = Result<FlowEntry, Error>;
+
+  fn next(&mut self) -> Option<Result<FlowEntry, Error>> {
+    self.next_entry().transpose()
+  }
+}
@@ -0,0 +1,147 @@
+use super::{FileHeader, FlowEntry};
+use crate::v5::{FlowRecord, PacketHeader};
+use flate2::write::ZlibEncoder;
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::net::Ipv4Addr;
+use std::path::Path;
+
+/// Compression of the records of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+  /// Records are stored as they are
+  #[default]
+  None,
+  /// Records are compressed with zlib at the given level, from 0 to 9, as
+  /// with `flow-capture -z`
+  Zlib(u32),
+}
+
+#[derive(Debug)]
+enum Stream<W: Write> {
+  Plain(W),
+  Zlib(ZlibEncoder<W>),
+}
+
+/// Writes v5 records to a flow-tools file
+///
+/// The header is written first, then completed by `finish` when the writer
+/// can seek back to it. Writers that cannot, such as pipes, are closed with
+/// `into_inner` and leave a header without counters, as `flow-capture` does
+/// when streaming.
+#[derive(Debug)]
+pub struct FlowWriter<W: Write> {
+  stream: Stream<W>,
+  header: FileHeader,
+  header_len: u64,
+}
+
+impl FlowWriter<BufWriter<File>> {
+  /// Creates a file, truncating any existing one
+  pub fn create<P: AsRef<Path>>(
+    path: P,
+    header: FileHeader,
+    compression: Compression,
+  ) -> io::Result<FlowWriter<BufWriter<File>>> {
+    FlowWriter::new(BufWriter::new(File::create(path)?), header, compression)
+  }
+}
+
+impl<W: Write> FlowWriter<W> {
+  /// Writes `header` to `writer`, with its `compressed` and `complete` flags
+  /// set from `compression`
+  pub fn new(
+    mut writer: W,
+    mut header: FileHeader,
+    compression: Compression,
+  ) -> io::Result<FlowWriter<W>> {
+    header.compressed = compression != Compression::None;
+    header.complete = false;
+    let data = header.to_bytes()?;
+    writer.write_all(&data)?;
+
+    let stream = match compression {
+      Compression::None => Stream::Plain(writer),
+      Compression::Zlib(level) => Stream::Zlib(ZlibEncoder::new(
+        writer,
+        flate2::Compression::new(level.min(9)),
+      )),
+    };
+    Ok(FlowWriter {
+      stream,
+      header,
+      header_len: data.len() as u64,
+    })
+  }
+
+  /// The header as it will be completed
+  pub fn header(&self) -> &FileHeader {
+    &self.header
+  }
+
+  /// Writes an entry, counting it in the header and widening the capture
+  /// times to its export time
+  pub fn write(&mut self, entry: &FlowEntry) -> io::Result<()> {
+    let mut data = [0; FlowEntry::LEN];
+    entry.write(self.header.byte_order, &mut data);
+    match &mut self.stream {
+      Stream::Plain(writer) => writer.write_all(&data)?,
+      Stream::Zlib(encoder) => encoder.write_all(&data)?,
+    }
+
+    let header = &mut self.header;
+    if header.capture_start == 0 || entry.unix_secs < header.capture_start {
+      header.capture_start = entry.unix_secs;
+    }
+    header.capture_end = header.capture_end.max(entry.unix_secs);
+    header.flow_count = header.flow_count.wrapping_add(1);
+    Ok(())
+  }
+
+  /// Writes the records of a packet sent by `exporter`
+  pub fn write_packet(
+    &mut self,
+    exporter: Ipv4Addr,
+    header: &PacketHeader,
+    records: &[FlowRecord],
+  ) -> io::Result<()> {
+    for &record in records {
+      self.write(&FlowEntry::new(exporter, header, record))?;
+    }
+    Ok(())
+  }
+
+  /// Ends the compressed stream and returns the underlying writer, leaving
+  /// the header as it was first written
+  pub fn into_inner(self) -> io::Result<W> {
+    let mut writer = match self.stream {
+      Stream::Plain(writer) => writer,
+      Stream::Zlib(encoder) => encoder.finish()?,
+    };
+    writer.flush()?;
+    Ok(writer)
+  }
+}
+
+impl<W: Write + Seek> FlowWriter<W> {
+  /// Ends the compressed stream and rewrites the header with the counters
+  /// and capture times of the records written
+  ///
+  /// The header is expected at the start of the writer, where `create` puts
+  /// it.
+  pub fn finish(self) -> io::Result<W> {
+    let mut header = self.header.clone();
+    header.complete = true;
+    let header_len = self.header_len;
+    let mut writer = self.into_inner()?;
+
+    let data = header.to_bytes()?;
+    debug_assert_eq!(data.len() as u64, header_len);
+    let end = writer.stream_position()?;
+    writer.seek(SeekFrom::Start(0))?;
+    writer.write_all(&data)?;
+    writer.seek(SeekFrom::Start(end))?;
+    writer.flush()?;
+    Ok(writer)
+  }
+}
@@ -0,0 +1,187 @@
+use super::raw::{Message, Set};
+use super::Template;
+use crate::Error;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+/// Key of a template announced by an exporter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TemplateKey {
+  /// Address the exporter sends its messages from
+  pub exporter: IpAddr,
+  /// Observation domain ID from the message header
+  pub observation_domain_id: u32,
+  /// ID of the template
+  pub template_id: u16,
+}
+
+/// Templates announced by every exporter, needed to decode their data sets
+#[derive(Debug, Default)]
+pub struct TemplateCache {
+  templates: HashMap<TemplateKey, Template>,
+}
+
+impl TemplateCache {
+  pub fn new() -> TemplateCache {
+    TemplateCache::default()
+  }
+
+  /// Stores a template, returning the one it replaces if any
+  pub fn insert(
+    &mut self,
+    exporter: IpAddr,
+    observation_domain_id: u32,
+    template: Template,
+  ) -> Option<Template> {
+    let key = TemplateKey {
+      exporter,
+      observation_domain_id,
+      template_id: template.id,
+    };
+    self.templates.insert(key, template)
+  }
+
+  pub fn get(
+    &self,
+    exporter: IpAddr,
+    observation_domain_id: u32,
+    template_id: u16,
+  ) -> Option<&Template> {
+    self.templates.get(&TemplateKey {
+      exporter,
+      observation_domain_id,
+      template_id,
+    })
+  }
+
+  /// Forgets a template, returning it if it was known
+  pub fn remove(
+    &mut self,
+    exporter: IpAddr,
+    observation_domain_id: u32,
+    template_id: u16,
+  ) -> Option<Template> {
+    self.templates.remove(&TemplateKey {
+      exporter,
+      observation_domain_id,
+      template_id,
+    })
+  }
+
+  /// Forgets every template (or options template) of an observation domain
+  fn remove_all(&mut self, exporter: IpAddr, observation_domain_id: u32, options: bool) {
+    self.templates.retain(|key, template| {
+      key.exporter != exporter
+        || key.observation_domain_id != observation_domain_id
+        || template.is_options() != options
+    });
+  }
+
+  /// Stores every template and options template of a message sent by
+  /// `exporter` and applies its template withdrawals, returning how many
+  /// template records were processed
+  ///
+  /// Templates found before an invalid set or record are kept.
+  pub fn learn(&mut self, exporter: IpAddr, message: &Message) -> Result<usize, Error> {
+    let domain = message.header().observation_domain_id();
+    let mut learnt = 0;
+    for set in message.sets() {
+      let set = set?;
+      for template in set.templates() {
+        let template = template?;
+        match (template.is_withdrawal(), template.id()) {
+          (false, _) => {
+            self.insert(exporter, domain, Template::from(&template));
+          }
+          (true, Set::TEMPLATE_ID) => self.remove_all(exporter, domain, false),
+          (true, id) => {
+            self.remove(exporter, domain, id);
+          }
+        }
+        learnt += 1;
+      }
+      for template in set.options_templates() {
+        let template = template?;
+        match (template.is_withdrawal(), template.id()) {
+          (false, _) => {
+            self.insert(exporter, domain, Template::from(&template));
+          }
+          (true, Set::OPTIONS_TEMPLATE_ID) => self.remove_all(exporter, domain, true),
+          (true, id) => {
+            self.remove(exporter, domain, id);
+          }
+        }
+        learnt += 1;
+      }
+    }
+    Ok(learnt)
+  }
+
+  /// Forgets every template announced by `exporter`, for example after it restarted
+  pub fn remove_exporter(&mut self, exporter: IpAddr) {
+    self.templates.retain(|key, _| key.exporter != exporter);
+  }
+
+  pub fn len(&self) -> usize {
+    self.templates.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.templates.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::MESSAGE_1;
+  use super::*;
+  use std::net::Ipv4Addr;
+
+  const EXPORTER: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+  fn withdrawal(set_id: u8, template_id: u16) -> Vec<u8> {
+    let mut data = vec![
+      0x00, 0x0a, 0x00, 0x18, 0x5c, 0x0e, 0xd7, 0xa5, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00,
+      0x01, 0x00, set_id, 0x00, 0x08,
+    ];
+    data.extend_from_slice(&template_id.to_be_bytes());
+    data.extend_from_slice(&[0x00, 0x00]);
+    data
+  }
+
+  #[test]
+  fn cache_learn_should_store_templates_per_domain() {
+    let mut cache = TemplateCache::new();
+    let message = Message::new(&MESSAGE_1).unwrap();
+
+    assert_eq!(cache.learn(EXPORTER, &message), Ok(2));
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(EXPORTER, 1, 256).unwrap().fields.len(), 10);
+    assert!(cache.get(EXPORTER, 1, 257).unwrap().is_options());
+    assert_eq!(cache.get(EXPORTER, 2, 256), None);
+
+    cache.remove_exporter(EXPORTER);
+    assert!(cache.is_empty());
+  }
+
+  #[test]
+  fn cache_learn_should_apply_withdrawals() {
+    let mut cache = TemplateCache::new();
+    cache
+      .learn(EXPORTER, &Message::new(&MESSAGE_1).unwrap())
+      .unwrap();
+
+    let data = withdrawal(2, 256);
+    cache
+      .learn(EXPORTER, &Message::new(&data).unwrap())
+      .unwrap();
+    assert_eq!(cache.get(EXPORTER, 1, 256), None);
+    assert!(cache.get(EXPORTER, 1, 257).is_some());
+
+    let data = withdrawal(3, 3);
+    cache
+      .learn(EXPORTER, &Message::new(&data).unwrap())
+      .unwrap();
+    assert!(cache.is_empty());
+  }
+}
@@ -0,0 +1,140 @@
+use crate::value;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+pub use crate::value::MplsLabel;
+
+/// IANA information element identifiers, see https://www.iana.org/assignments/ipfix
+pub mod information_element {
+  pub const OCTET_DELTA_COUNT: u16 = 1;
+  pub const PACKET_DELTA_COUNT: u16 = 2;
+  pub const DELTA_FLOW_COUNT: u16 = 3;
+  pub const PROTOCOL_IDENTIFIER: u16 = 4;
+  pub const IP_CLASS_OF_SERVICE: u16 = 5;
+  pub const TCP_CONTROL_BITS: u16 = 6;
+  pub const SOURCE_TRANSPORT_PORT: u16 = 7;
+  pub const SOURCE_IPV4_ADDRESS: u16 = 8;
+  pub const SOURCE_IPV4_PREFIX_LENGTH: u16 = 9;
+  pub const INGRESS_INTERFACE: u16 = 10;
+  pub const DESTINATION_TRANSPORT_PORT: u16 = 11;
+  pub const DESTINATION_IPV4_ADDRESS: u16 = 12;
+  pub const DESTINATION_IPV4_PREFIX_LENGTH: u16 = 13;
+  pub const EGRESS_INTERFACE: u16 = 14;
+  pub const IP_NEXT_HOP_IPV4_ADDRESS: u16 = 15;
+  pub const BGP_SOURCE_AS_NUMBER: u16 = 16;
+  pub const BGP_DESTINATION_AS_NUMBER: u16 = 17;
+  pub const FLOW_END_SYS_UP_TIME: u16 = 21;
+  pub const FLOW_START_SYS_UP_TIME: u16 = 22;
+  pub const SOURCE_IPV6_ADDRESS: u16 = 27;
+  pub const DESTINATION_IPV6_ADDRESS: u16 = 28;
+  pub const SOURCE_IPV6_PREFIX_LENGTH: u16 = 29;
+  pub const DESTINATION_IPV6_PREFIX_LENGTH: u16 = 30;
+  pub const SAMPLING_INTERVAL: u16 = 34;
+  pub const SAMPLING_ALGORITHM: u16 = 35;
+  pub const SOURCE_MAC_ADDRESS: u16 = 56;
+  pub const VLAN_ID: u16 = 58;
+  pub const IP_VERSION: u16 = 60;
+  pub const FLOW_DIRECTION: u16 = 61;
+  pub const IP_NEXT_HOP_IPV6_ADDRESS: u16 = 62;
+  pub const MPLS_TOP_LABEL_STACK_SECTION: u16 = 70;
+  pub const MPLS_LABEL_STACK_SECTION_10: u16 = 79;
+  pub const DESTINATION_MAC_ADDRESS: u16 = 80;
+  pub const OCTET_TOTAL_COUNT: u16 = 85;
+  pub const PACKET_TOTAL_COUNT: u16 = 86;
+  pub const APPLICATION_NAME: u16 = 96;
+  pub const EXPORTER_IPV4_ADDRESS: u16 = 130;
+  pub const EXPORTER_IPV6_ADDRESS: u16 = 131;
+  pub const OBSERVATION_DOMAIN_ID: u16 = 149;
+  pub const FLOW_START_SECONDS: u16 = 150;
+  pub const FLOW_END_SECONDS: u16 = 151;
+  pub const FLOW_START_MILLISECONDS: u16 = 152;
+  pub const FLOW_END_MILLISECONDS: u16 = 153;
+  pub const SAMPLING_PACKET_INTERVAL: u16 = 305;
+}
+
+/// Information element, length and enterprise number of a field, as announced by a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldSpec {
+  /// Information element identifier, without the enterprise bit
+  pub information_element: u16,
+  /// Length of the field, `VARIABLE_LENGTH` if encoded in each data record
+  pub length: u16,
+  /// Private enterprise number of enterprise-specific information elements
+  pub enterprise_number: Option<u32>,
+}
+
+impl FieldSpec {
+  /// Length announcing a field whose length is encoded in each data record
+  pub const VARIABLE_LENGTH: u16 = 65535;
+
+  pub fn is_variable_length(&self) -> bool {
+    self.length == Self::VARIABLE_LENGTH
+  }
+
+  pub fn is_enterprise(&self) -> bool {
+    self.enterprise_number.is_some()
+  }
+}
+
+/// A field value of a data record helper struct
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Field<'a> {
+  pub(crate) spec: FieldSpec,
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> Field<'a> {
+  /// Specifier of the field, as announced by its template
+  pub fn spec(&self) -> FieldSpec {
+    self.spec
+  }
+
+  /// Information element identifier, see `information_element` for IANA ones
+  pub fn information_element(&self) -> u16 {
+    self.spec.information_element
+  }
+
+  /// Private enterprise number of an enterprise-specific field
+  pub fn enterprise_number(&self) -> Option<u32> {
+    self.spec.enterprise_number
+  }
+
+  /// Raw bytes of the field, without the length prefix of variable-length fields
+  pub fn data(&self) -> &'a [u8] {
+    self.data
+  }
+
+  /// Value of an unsigned integer field of 1 to 8 bytes, including reduced-size encodings
+  pub fn as_u64(&self) -> Option<u64> {
+    value::read_u64(self.data)
+  }
+
+  /// Value of a 4-byte address field
+  pub fn as_ipv4(&self) -> Option<Ipv4Addr> {
+    value::read_ipv4(self.data)
+  }
+
+  /// Value of a 16-byte address field
+  pub fn as_ipv6(&self) -> Option<Ipv6Addr> {
+    value::read_ipv6(self.data)
+  }
+
+  /// Value of a 4-byte or 16-byte address field
+  pub fn as_ip_addr(&self) -> Option<IpAddr> {
+    value::read_ip_addr(self.data)
+  }
+
+  /// Value of a 6-byte MAC address field
+  pub fn as_mac_addr(&self) -> Option<[u8; 6]> {
+    value::read_mac_addr(self.data)
+  }
+
+  /// Value of a 3-byte MPLS label stack section field
+  pub fn as_mpls_label(&self) -> Option<MplsLabel> {
+    value::read_mpls_label(self.data)
+  }
+
+  /// Value of a UTF-8 string field
+  pub fn as_str(&self) -> Option<&'a str> {
+    std::str::from_utf8(self.data).ok()
+  }
+}
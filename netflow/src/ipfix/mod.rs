@@ -0,0 +1,152 @@
+pub mod raw;
+
+mod cache;
+mod field;
+mod template;
+
+pub use cache::{TemplateCache, TemplateKey};
+pub use field::{information_element, Field, FieldSpec, MplsLabel};
+pub use template::Template;
+
+// Based on RFC 7011: https://tools.ietf.org/html/rfc7011
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use super::raw::Message;
+  use super::*;
+  use std::net::{IpAddr, Ipv4Addr};
+
+  const EXPORTER: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+  #[test]
+  fn decoding_data_records_with_learnt_templates() {
+    let message = Message::new(&MESSAGE_1).unwrap();
+    let mut cache = TemplateCache::new();
+    cache.learn(EXPORTER, &message).unwrap();
+    let domain = message.header().observation_domain_id();
+
+    let mut records = Vec::new();
+    for set in message.sets() {
+      let set = set.unwrap();
+      if let Some(template) = cache.get(EXPORTER, domain, set.id()) {
+        for record in set.records(template) {
+          let record = record.unwrap();
+          records.push((
+            record.source_addr(),
+            record.destination_addr(),
+            record.source_port(),
+            record.destination_port(),
+            record.protocol(),
+            record.packets(),
+            record.bytes(),
+            record.flow_start_milliseconds(),
+            record.flow_end_milliseconds(),
+            record
+              .get_enterprise(9, 12235)
+              .and_then(|field| field.as_str()),
+          ));
+        }
+      }
+    }
+
+    assert_eq!(
+      records,
+      vec![
+        (
+          Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))),
+          Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))),
+          Some(50000),
+          Some(443),
+          Some(6),
+          Some(10),
+          Some(1500),
+          Some(1544476581000),
+          Some(1544476581500),
+          Some("https"),
+        ),
+        (
+          Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))),
+          Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2))),
+          Some(53),
+          Some(54321),
+          Some(17),
+          Some(1),
+          Some(72),
+          Some(1544476582000),
+          Some(1544476582000),
+          Some("dns"),
+        ),
+        (None, None, None, None, None, None, None, None, None, None),
+      ]
+    );
+  }
+
+  #[test]
+  fn decoding_options_data_records() {
+    let message = Message::new(&MESSAGE_1).unwrap();
+    let mut cache = TemplateCache::new();
+    cache.learn(EXPORTER, &message).unwrap();
+    let template = cache.get(EXPORTER, 1, 257).unwrap();
+    let set = message.sets().last().unwrap().unwrap();
+
+    let records = set
+      .records(template)
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+    assert_eq!(records.len(), 1);
+    println!("{:?}", records[0]);
+    assert_eq!(
+      records[0]
+        .scope(information_element::OBSERVATION_DOMAIN_ID)
+        .and_then(|f| f.as_u64()),
+      Some(1)
+    );
+    assert_eq!(
+      records[0]
+        .get(information_element::SAMPLING_INTERVAL)
+        .and_then(|f| f.as_u64()),
+      Some(1000)
+    );
+  }
+
+  #[test]
+  fn decoding_data_records_should_fail_with_overflowing_variable_length() {
+    let mut data = MESSAGE_1.to_vec();
+    // Length prefix of the variable-length field of the first data record
+    data[88 + 4 + 45] = 200;
+    let message = Message::new(&data).unwrap();
+    let mut cache = TemplateCache::new();
+    cache.learn(EXPORTER, &message).unwrap();
+    let template = cache.get(EXPORTER, 1, 256).unwrap();
+    let set = message.sets().nth(2).unwrap().unwrap();
+
+    assert_eq!(
+      set.records(template).collect::<Vec<_>>(),
+      vec![Err(crate::Error::NotEnoughData {
+        protocol: crate::Protocol::Ipfix,
+        offset: 88 + 4 + 45,
+        expected: 201,
+        actual: 104 - 45,
+      })]
+    );
+  }
+
+  /// Header, template set (template 256 with an enterprise-specific
+  /// variable-length field), options template set (template 257), data set
+  /// with two records, options data set
+  pub const MESSAGE_1: [u8; 208] = [
+    0x00, 0x0a, 0x00, 0xd0, 0x5c, 0x0e, 0xd7, 0xa5, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x01,
+    0x00, 0x02, 0x00, 0x34, 0x01, 0x00, 0x00, 0x0a, 0x00, 0x08, 0x00, 0x04, 0x00, 0x0c, 0x00, 0x04,
+    0x00, 0x07, 0x00, 0x02, 0x00, 0x0b, 0x00, 0x02, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02, 0x00, 0x08,
+    0x00, 0x01, 0x00, 0x08, 0x00, 0x98, 0x00, 0x08, 0x00, 0x99, 0x00, 0x08, 0xaf, 0xcb, 0xff, 0xff,
+    0x00, 0x00, 0x00, 0x09, 0x00, 0x03, 0x00, 0x14, 0x01, 0x01, 0x00, 0x02, 0x00, 0x01, 0x00, 0x95,
+    0x00, 0x04, 0x00, 0x22, 0x00, 0x04, 0x00, 0x00, 0x01, 0x00, 0x00, 0x6c, 0xc0, 0xa8, 0x01, 0x01,
+    0x0a, 0x00, 0x00, 0x01, 0xc3, 0x50, 0x01, 0xbb, 0x06, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x0a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0xdc, 0x00, 0x00, 0x01, 0x67, 0x99, 0xfa, 0x5c,
+    0x88, 0x00, 0x00, 0x01, 0x67, 0x99, 0xfa, 0x5e, 0x7c, 0x05, 0x68, 0x74, 0x74, 0x70, 0x73, 0x0a,
+    0x00, 0x00, 0x02, 0xc0, 0xa8, 0x01, 0x02, 0x00, 0x35, 0xd4, 0x31, 0x11, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x48, 0x00, 0x00, 0x01, 0x67,
+    0x99, 0xfa, 0x60, 0x70, 0x00, 0x00, 0x01, 0x67, 0x99, 0xfa, 0x60, 0x70, 0xff, 0x00, 0x03, 0x64,
+    0x6e, 0x73, 0x00, 0x00, 0x01, 0x01, 0x00, 0x0c, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x03, 0xe8,
+  ];
+}
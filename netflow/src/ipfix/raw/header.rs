@@ -0,0 +1,96 @@
+use super::{Error, Protocol};
+use byteorder::{ByteOrder, NetworkEndian};
+
+/// An IPFIX message header helper struct
+#[derive(PartialEq)]
+pub struct Header<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for Header<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Header {{ version: {}, length: {}, export_time: {}, sequence_number: {}, observation_domain_id: {} }}",
+        self.version(), self.length(), self.export_time(), self.sequence_number(), self.observation_domain_id())
+  }
+}
+
+impl<'a> Header<'a> {
+  pub const LEN: usize = 16;
+  pub const VERSION: u16 = 10;
+
+  pub fn new(data: &'a [u8]) -> Result<Header<'a>, Error> {
+    if data.len() < Self::LEN {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::Ipfix,
+        offset: 0,
+        expected: Self::LEN,
+        actual: data.len(),
+      });
+    }
+    let version = NetworkEndian::read_u16(&data[0..2]);
+    if version != Self::VERSION {
+      return Err(Error::InvalidVersion {
+        protocol: Protocol::Ipfix,
+        version,
+      });
+    }
+
+    Ok(Header { data })
+  }
+
+  /// IPFIX version number
+  pub fn version(&self) -> u16 {
+    Self::VERSION
+  }
+
+  /// Total length of the message in bytes, including the header
+  pub fn length(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[2..4]))
+  }
+
+  /// Time in seconds since 0000 Coordinated Universal Time 1970 at which the message left the exporter
+  pub fn export_time(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[4..8]))
+  }
+
+  /// Incremental sequence counter of all data records sent in the observation domain
+  pub fn sequence_number(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[8..12]))
+  }
+
+  /// Identifier of the observation domain, locally unique to the exporter
+  pub fn observation_domain_id(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[12..16]))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::MESSAGE_1;
+  use super::*;
+
+  #[test]
+  fn header_new_should_fail_with_invalid_version() {
+    let mut data = MESSAGE_1.to_vec();
+    data[1] = 9;
+
+    assert_eq!(
+      Header::new(&data),
+      Err(Error::InvalidVersion {
+        protocol: Protocol::Ipfix,
+        version: 9,
+      })
+    );
+  }
+
+  #[test]
+  fn header_accessors_expose_fields() {
+    let header = Header::new(&MESSAGE_1).unwrap();
+
+    assert_eq!(header.version(), 10);
+    assert_eq!(header.length(), 208);
+    assert_eq!(header.export_time(), 1544476581);
+    assert_eq!(header.sequence_number(), 7);
+    assert_eq!(header.observation_domain_id(), 1);
+  }
+}
@@ -0,0 +1,158 @@
+use super::{DataRecord, Error, OptionsTemplate, Protocol, Set, Template};
+use byteorder::{ByteOrder, NetworkEndian};
+
+/// Iterator over the sets of a message, stopping after the first invalid one
+pub struct SetIterator<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+}
+
+impl<'a> Iterator for SetIterator<'a> {
+  type Item = Result<Set<'a>, Error>;
+
+  fn next(&mut self) -> Option<Result<Set<'a>, Error>> {
+    let data = &self.data[self.offset..];
+    if data.is_empty() {
+      return None;
+    }
+
+    match Set::parse(data, self.offset) {
+      Ok(set) => {
+        self.offset += set.data.len();
+        Some(Ok(set))
+      }
+      Err(error) => {
+        self.offset = self.data.len();
+        Some(Err(error))
+      }
+    }
+  }
+}
+
+/// Iterator over the template records of a set, stopping after the first invalid one
+pub struct TemplateIterator<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+}
+
+impl<'a> Iterator for TemplateIterator<'a> {
+  type Item = Result<Template<'a>, Error>;
+
+  fn next(&mut self) -> Option<Result<Template<'a>, Error>> {
+    // Anything shorter than a template header is padding
+    if self.data.len() < Template::HEADER_LEN {
+      return None;
+    }
+
+    match Template::new(self.data) {
+      Ok(template) => {
+        let len = template.data.len();
+        self.data = &self.data[len..];
+        self.offset += len;
+        Some(Ok(template))
+      }
+      Err(error) => {
+        self.data = &[];
+        Some(Err(error.offset_by(self.offset)))
+      }
+    }
+  }
+}
+
+/// Iterator over the options template records of a set, stopping after the first invalid one
+pub struct OptionsTemplateIterator<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+}
+
+impl<'a> Iterator for OptionsTemplateIterator<'a> {
+  type Item = Result<OptionsTemplate<'a>, Error>;
+
+  fn next(&mut self) -> Option<Result<OptionsTemplate<'a>, Error>> {
+    // Anything shorter than an options template withdrawal is padding
+    if self.data.len() < OptionsTemplate::WITHDRAWAL_LEN {
+      return None;
+    }
+
+    match OptionsTemplate::new(self.data) {
+      Ok(template) => {
+        let len = template.data.len();
+        self.data = &self.data[len..];
+        self.offset += len;
+        Some(Ok(template))
+      }
+      Err(error) => {
+        self.data = &[];
+        Some(Err(error.offset_by(self.offset)))
+      }
+    }
+  }
+}
+
+/// Iterator over the data records of a set, stopping after the first invalid one
+pub struct DataRecordIterator<'a, 't> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+  pub(crate) template: &'t crate::ipfix::Template,
+  pub(crate) min_record_length: usize,
+}
+
+impl<'a, 't> DataRecordIterator<'a, 't> {
+  /// Length of the record at the start of the remaining data, reading the
+  /// length prefix of its variable-length fields
+  fn record_length(&self) -> Result<usize, Error> {
+    let mut len = 0;
+    let specs = self.template.scope_fields.iter();
+    for spec in specs.chain(self.template.fields.iter()) {
+      let (prefix, field) = if !spec.is_variable_length() {
+        (0, spec.length as usize)
+      } else if self.data.len() > len && self.data[len] < 255 {
+        (1, self.data[len] as usize)
+      } else if self.data.len() >= len + 3 {
+        (
+          3,
+          NetworkEndian::read_u16(&self.data[len + 1..len + 3]) as usize,
+        )
+      } else {
+        (3, 0)
+      };
+      if self.data.len() < len + prefix + field {
+        return Err(Error::NotEnoughData {
+          protocol: Protocol::Ipfix,
+          offset: self.offset + len,
+          expected: prefix + field,
+          actual: self.data.len() - len,
+        });
+      }
+      len += prefix + field;
+    }
+    Ok(len)
+  }
+}
+
+impl<'a, 't> Iterator for DataRecordIterator<'a, 't> {
+  type Item = Result<DataRecord<'a, 't>, Error>;
+
+  fn next(&mut self) -> Option<Result<DataRecord<'a, 't>, Error>> {
+    // Anything shorter than the smallest possible record is padding
+    if self.min_record_length == 0 || self.data.len() < self.min_record_length {
+      return None;
+    }
+
+    match self.record_length() {
+      Ok(len) => {
+        let (data, rest) = self.data.split_at(len);
+        self.data = rest;
+        self.offset += len;
+        Some(Ok(DataRecord {
+          template: self.template,
+          data,
+        }))
+      }
+      Err(error) => {
+        self.data = &[];
+        Some(Err(error))
+      }
+    }
+  }
+}
@@ -0,0 +1,118 @@
+use super::{Error, Protocol};
+
+use super::{Header, SetIterator};
+
+/// An IPFIX message helper struct
+#[derive(PartialEq)]
+pub struct Message<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for Message<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Message")
+      .field("header", &self.header())
+      .field("sets", &self.sets().collect::<Vec<_>>())
+      .finish()
+  }
+}
+
+impl<'a> Message<'a> {
+  /// Parses the message at the start of `data`, checking that it holds the
+  /// length announced by the header and ignoring any trailing bytes
+  pub fn new(data: &'a [u8]) -> Result<Message<'a>, Error> {
+    let length = Header::new(data)?.length() as usize;
+    if length < Header::LEN {
+      return Err(Error::InvalidLength {
+        protocol: Protocol::Ipfix,
+        offset: 0,
+        length,
+      });
+    }
+    if data.len() < length {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::Ipfix,
+        offset: 0,
+        expected: length,
+        actual: data.len(),
+      });
+    }
+
+    Ok(Message {
+      data: &data[..length],
+    })
+  }
+
+  pub fn header(&self) -> Header<'a> {
+    Header { data: self.data }
+  }
+
+  /// Sets following the header, up to the first invalid one
+  pub fn sets(&self) -> SetIterator<'a> {
+    SetIterator {
+      data: self.data,
+      offset: Header::LEN,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::MESSAGE_1;
+  use super::super::Set;
+  use super::*;
+
+  #[test]
+  fn message_new_should_fail_with_not_enough_data() {
+    assert_eq!(
+      Message::new(&MESSAGE_1[..100]),
+      Err(Error::NotEnoughData {
+        protocol: Protocol::Ipfix,
+        offset: 0,
+        expected: 208,
+        actual: 100,
+      })
+    );
+  }
+
+  #[test]
+  fn message_sets_should_split_message() {
+    let message = Message::new(&MESSAGE_1).unwrap();
+    let sets = message
+      .sets()
+      .map(|set| set.map(|set| (set.id(), set.length())))
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+
+    assert_eq!(sets, vec![(2, 52), (3, 20), (256, 108), (257, 12)]);
+  }
+
+  #[test]
+  fn message_templates_should_expose_fields() {
+    let message = Message::new(&MESSAGE_1).unwrap();
+    let sets = message.sets().collect::<Result<Vec<Set>, _>>().unwrap();
+
+    let templates = sets[0].templates().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(templates.len(), 1);
+    assert_eq!(templates[0].id(), 256);
+    assert_eq!(templates[0].field_count(), 10);
+    assert_eq!(
+      templates[0].fields().last().unwrap().enterprise_number,
+      Some(9)
+    );
+
+    let options = sets[1]
+      .options_templates()
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+    assert_eq!(options.len(), 1);
+    assert_eq!(options[0].id(), 257);
+    assert_eq!(options[0].scope_fields().count(), 1);
+    assert_eq!(options[0].fields().count(), 1);
+  }
+
+  #[test]
+  fn message_implements_debug() {
+    println!("{:?}", Message::new(&MESSAGE_1).unwrap());
+  }
+}
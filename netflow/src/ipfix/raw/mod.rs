@@ -0,0 +1,21 @@
+use super::{information_element, Field, FieldSpec, MplsLabel};
+use crate::{Error, Protocol};
+
+mod header;
+mod iterator;
+mod message;
+mod record;
+mod set;
+mod template;
+
+pub use header::Header;
+pub use iterator::{DataRecordIterator, OptionsTemplateIterator, SetIterator, TemplateIterator};
+pub use message::Message;
+pub use record::{DataRecord, FieldIterator};
+pub use set::Set;
+pub use template::{FieldSpecIterator, OptionsTemplate, Template};
+
+#[cfg(test)]
+mod tests {
+  pub use super::super::tests::MESSAGE_1;
+}
@@ -0,0 +1,331 @@
+use super::{information_element as ie, Field, FieldSpec, MplsLabel};
+use crate::ipfix::Template;
+use crate::Flow;
+use byteorder::{ByteOrder, NetworkEndian};
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Iterator over the fields of a data record
+pub struct FieldIterator<'a, 't> {
+  pub(crate) specs: std::slice::Iter<'t, FieldSpec>,
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a, 't> Iterator for FieldIterator<'a, 't> {
+  type Item = Field<'a>;
+
+  fn next(&mut self) -> Option<Field<'a>> {
+    let spec = *self.specs.next()?;
+    // Lengths were checked when the record was split off its set
+    let (prefix, length) = match (spec.is_variable_length(), self.data.first()) {
+      (false, _) => (0, spec.length as usize),
+      (true, Some(&length)) if length < 255 => (1, length as usize),
+      (true, _) => (3, NetworkEndian::read_u16(&self.data[1..3]) as usize),
+    };
+    let data = &self.data[prefix..prefix + length];
+    self.data = &self.data[prefix + length..];
+    Some(Field { spec, data })
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.specs.size_hint()
+  }
+}
+
+/// An IPFIX data record helper struct
+#[derive(PartialEq)]
+pub struct DataRecord<'a, 't> {
+  pub(crate) template: &'t Template,
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a, 't> std::fmt::Debug for DataRecord<'a, 't> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("DataRecord")
+      .field("template_id", &self.template.id)
+      .field("scope_fields", &self.scope_fields().collect::<Vec<_>>())
+      .field("fields", &self.fields().collect::<Vec<_>>())
+      .finish()
+  }
+}
+
+impl<'a, 't> DataRecord<'a, 't> {
+  /// Template this record was decoded with
+  pub fn template(&self) -> &'t Template {
+    self.template
+  }
+
+  /// Scope fields of an options data record
+  pub fn scope_fields(&self) -> FieldIterator<'a, 't> {
+    FieldIterator {
+      specs: self.template.scope_fields.iter(),
+      data: self.data,
+    }
+  }
+
+  /// Fields of the record, following the scope fields if any
+  pub fn fields(&self) -> FieldIterator<'a, 't> {
+    let mut scope_fields = self.scope_fields();
+    while scope_fields.next().is_some() {}
+    FieldIterator {
+      specs: self.template.fields.iter(),
+      data: scope_fields.data,
+    }
+  }
+
+  /// First IANA field with the given information element identifier
+  pub fn get(&self, information_element: u16) -> Option<Field<'a>> {
+    self.fields().find(|field| {
+      field.spec.enterprise_number.is_none()
+        && field.spec.information_element == information_element
+    })
+  }
+
+  /// First enterprise-specific field with the given private enterprise number
+  /// and information element identifier
+  pub fn get_enterprise(
+    &self,
+    enterprise_number: u32,
+    information_element: u16,
+  ) -> Option<Field<'a>> {
+    self.fields().find(|field| {
+      field.spec.enterprise_number == Some(enterprise_number)
+        && field.spec.information_element == information_element
+    })
+  }
+
+  /// First IANA scope field with the given information element identifier
+  pub fn scope(&self, information_element: u16) -> Option<Field<'a>> {
+    self.scope_fields().find(|field| {
+      field.spec.enterprise_number.is_none()
+        && field.spec.information_element == information_element
+    })
+  }
+
+  fn get_u64(&self, information_element: u16) -> Option<u64> {
+    self
+      .get(information_element)
+      .and_then(|field| field.as_u64())
+  }
+
+  fn get_ip_addr(&self, v4: u16, v6: u16) -> Option<IpAddr> {
+    self
+      .get(v4)
+      .or_else(|| self.get(v6))
+      .and_then(|field| field.as_ip_addr())
+  }
+
+  /// Source IPv4 or IPv6 address
+  pub fn source_addr(&self) -> Option<IpAddr> {
+    self.get_ip_addr(ie::SOURCE_IPV4_ADDRESS, ie::SOURCE_IPV6_ADDRESS)
+  }
+
+  /// Destination IPv4 or IPv6 address
+  pub fn destination_addr(&self) -> Option<IpAddr> {
+    self.get_ip_addr(ie::DESTINATION_IPV4_ADDRESS, ie::DESTINATION_IPV6_ADDRESS)
+  }
+
+  /// IPv4 or IPv6 address of next hop router
+  pub fn next_hop(&self) -> Option<IpAddr> {
+    self.get_ip_addr(ie::IP_NEXT_HOP_IPV4_ADDRESS, ie::IP_NEXT_HOP_IPV6_ADDRESS)
+  }
+
+  /// TCP or UDP source port number
+  pub fn source_port(&self) -> Option<u16> {
+    self.get_u64(ie::SOURCE_TRANSPORT_PORT).map(|v| v as u16)
+  }
+
+  /// TCP or UDP destination port number
+  pub fn destination_port(&self) -> Option<u16> {
+    self
+      .get_u64(ie::DESTINATION_TRANSPORT_PORT)
+      .map(|v| v as u16)
+  }
+
+  /// IP protocol type (for example, TCP = 6, UDP = 17, ...)
+  pub fn protocol(&self) -> Option<u8> {
+    self.get_u64(ie::PROTOCOL_IDENTIFIER).map(|v| v as u8)
+  }
+
+  /// Index of input interface
+  pub fn input(&self) -> Option<u32> {
+    self.get_u64(ie::INGRESS_INTERFACE).map(|v| v as u32)
+  }
+
+  /// Index of output interface
+  pub fn output(&self) -> Option<u32> {
+    self.get_u64(ie::EGRESS_INTERFACE).map(|v| v as u32)
+  }
+
+  /// Packets in the flow, from the delta count or else the total count
+  pub fn packets(&self) -> Option<u64> {
+    self
+      .get_u64(ie::PACKET_DELTA_COUNT)
+      .or_else(|| self.get_u64(ie::PACKET_TOTAL_COUNT))
+  }
+
+  /// Bytes in the flow, from the delta count or else the total count
+  pub fn bytes(&self) -> Option<u64> {
+    self
+      .get_u64(ie::OCTET_DELTA_COUNT)
+      .or_else(|| self.get_u64(ie::OCTET_TOTAL_COUNT))
+  }
+
+  /// IP type of service (ToS) or traffic class
+  pub fn type_of_service(&self) -> Option<u8> {
+    self.get_u64(ie::IP_CLASS_OF_SERVICE).map(|v| v as u8)
+  }
+
+  /// TCP flags observed in the packets of the flow, restricted to the 8 bits
+  /// of the original TCP header
+  pub fn tcp_flags(&self) -> Option<u8> {
+    self.get_u64(ie::TCP_CONTROL_BITS).map(|v| v as u8)
+  }
+
+  /// Autonomous system number of the source
+  pub fn source_as(&self) -> Option<u32> {
+    self.get_u64(ie::BGP_SOURCE_AS_NUMBER).map(|v| v as u32)
+  }
+
+  /// Autonomous system number of the destination
+  pub fn destination_as(&self) -> Option<u32> {
+    self
+      .get_u64(ie::BGP_DESTINATION_AS_NUMBER)
+      .map(|v| v as u32)
+  }
+
+  /// Source IPv4 or IPv6 address prefix length
+  pub fn source_mask(&self) -> Option<u8> {
+    self
+      .get_u64(ie::SOURCE_IPV4_PREFIX_LENGTH)
+      .or_else(|| self.get_u64(ie::SOURCE_IPV6_PREFIX_LENGTH))
+      .map(|v| v as u8)
+  }
+
+  /// Destination IPv4 or IPv6 address prefix length
+  pub fn destination_mask(&self) -> Option<u8> {
+    self
+      .get_u64(ie::DESTINATION_IPV4_PREFIX_LENGTH)
+      .or_else(|| self.get_u64(ie::DESTINATION_IPV6_PREFIX_LENGTH))
+      .map(|v| v as u8)
+  }
+
+  /// SysUptime in milliseconds at start of flow
+  pub fn flow_start_sys_up_time(&self) -> Option<u32> {
+    self.get_u64(ie::FLOW_START_SYS_UP_TIME).map(|v| v as u32)
+  }
+
+  /// SysUptime in milliseconds at end of flow
+  pub fn flow_end_sys_up_time(&self) -> Option<u32> {
+    self.get_u64(ie::FLOW_END_SYS_UP_TIME).map(|v| v as u32)
+  }
+
+  /// Milliseconds since 0000 Coordinated Universal Time 1970 at start of flow,
+  /// from `flowStartMilliseconds` or else `flowStartSeconds`
+  pub fn flow_start_milliseconds(&self) -> Option<u64> {
+    self
+      .get_u64(ie::FLOW_START_MILLISECONDS)
+      .or_else(|| self.get_u64(ie::FLOW_START_SECONDS).map(|s| s * 1000))
+  }
+
+  /// Milliseconds since 0000 Coordinated Universal Time 1970 at end of flow,
+  /// from `flowEndMilliseconds` or else `flowEndSeconds`
+  pub fn flow_end_milliseconds(&self) -> Option<u64> {
+    self
+      .get_u64(ie::FLOW_END_MILLISECONDS)
+      .or_else(|| self.get_u64(ie::FLOW_END_SECONDS).map(|s| s * 1000))
+  }
+
+  /// MPLS label stack, from `mplsTopLabelStackSection` to `mplsLabelStackSection10`
+  pub fn mpls_labels(&self) -> Vec<MplsLabel> {
+    (ie::MPLS_TOP_LABEL_STACK_SECTION..=ie::MPLS_LABEL_STACK_SECTION_10)
+      .filter_map(|id| self.get(id).and_then(|field| field.as_mpls_label()))
+      .collect()
+  }
+}
+
+impl<'a, 't> Flow for DataRecord<'a, 't> {
+  fn source_addr(&self) -> Option<IpAddr> {
+    self.source_addr()
+  }
+
+  fn destination_addr(&self) -> Option<IpAddr> {
+    self.destination_addr()
+  }
+
+  fn source_port(&self) -> Option<u16> {
+    self.source_port()
+  }
+
+  fn destination_port(&self) -> Option<u16> {
+    self.destination_port()
+  }
+
+  fn protocol(&self) -> Option<u8> {
+    self.protocol()
+  }
+
+  fn packets(&self) -> Option<u64> {
+    self.packets()
+  }
+
+  fn bytes(&self) -> Option<u64> {
+    self.bytes()
+  }
+
+  fn input(&self) -> Option<u32> {
+    self.input()
+  }
+
+  fn output(&self) -> Option<u32> {
+    self.output()
+  }
+
+  fn next_hop(&self) -> Option<IpAddr> {
+    self.next_hop()
+  }
+
+  fn source_as(&self) -> Option<u32> {
+    self.source_as()
+  }
+
+  fn destination_as(&self) -> Option<u32> {
+    self.destination_as()
+  }
+
+  fn source_mask(&self) -> Option<u8> {
+    self.source_mask()
+  }
+
+  fn destination_mask(&self) -> Option<u8> {
+    self.destination_mask()
+  }
+
+  fn type_of_service(&self) -> Option<u8> {
+    self.type_of_service()
+  }
+
+  fn tcp_flags(&self) -> Option<u8> {
+    self.tcp_flags()
+  }
+
+  fn first_packet_sys_uptime(&self) -> Option<u32> {
+    self.flow_start_sys_up_time()
+  }
+
+  fn last_packet_sys_uptime(&self) -> Option<u32> {
+    self.flow_end_sys_up_time()
+  }
+
+  fn start_time(&self) -> Option<SystemTime> {
+    self
+      .flow_start_milliseconds()
+      .map(|ms| UNIX_EPOCH + Duration::from_millis(ms))
+  }
+
+  fn end_time(&self) -> Option<SystemTime> {
+    self
+      .flow_end_milliseconds()
+      .map(|ms| UNIX_EPOCH + Duration::from_millis(ms))
+  }
+}
@@ -0,0 +1,153 @@
+use super::{Error, Protocol};
+use byteorder::{ByteOrder, NetworkEndian};
+
+use super::{DataRecordIterator, OptionsTemplateIterator, TemplateIterator};
+
+/// An IPFIX set helper struct
+#[derive(PartialEq)]
+pub struct Set<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+}
+
+impl<'a> std::fmt::Debug for Set<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Set {{ id: {}, length: {} }}", self.id(), self.length())
+  }
+}
+
+impl<'a> Set<'a> {
+  pub const HEADER_LEN: usize = 4;
+  /// ID of the sets holding template records
+  pub const TEMPLATE_ID: u16 = 2;
+  /// ID of the sets holding options template records
+  pub const OPTIONS_TEMPLATE_ID: u16 = 3;
+  /// Lowest ID of the sets holding data records, lower IDs are reserved
+  pub const MIN_DATA_ID: u16 = 256;
+
+  /// Parses the set at the start of `data`, ignoring any trailing bytes
+  pub fn new(data: &'a [u8]) -> Result<Set<'a>, Error> {
+    Self::parse(data, 0)
+  }
+
+  /// Parses the set at the start of `data`, found at `offset` in its message
+  pub(crate) fn parse(data: &'a [u8], offset: usize) -> Result<Set<'a>, Error> {
+    if data.len() < Self::HEADER_LEN {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::Ipfix,
+        offset,
+        expected: Self::HEADER_LEN,
+        actual: data.len(),
+      });
+    }
+    let length = NetworkEndian::read_u16(&data[2..4]) as usize;
+    if length < Self::HEADER_LEN {
+      return Err(Error::InvalidLength {
+        protocol: Protocol::Ipfix,
+        offset,
+        length,
+      });
+    }
+    if data.len() < length {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::Ipfix,
+        offset,
+        expected: length,
+        actual: data.len(),
+      });
+    }
+
+    Ok(Set {
+      data: &data[..length],
+      offset,
+    })
+  }
+
+  /// Set ID: 2 for templates, 3 for options templates, a template ID for data
+  pub fn id(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[0..2]))
+  }
+
+  /// Total length of the set in bytes, including its header and padding
+  pub fn length(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[2..4]))
+  }
+
+  /// Content of the set, following its header
+  pub fn body(&self) -> &'a [u8] {
+    &self.data[Self::HEADER_LEN..]
+  }
+
+  pub fn is_template(&self) -> bool {
+    self.id() == Self::TEMPLATE_ID
+  }
+
+  pub fn is_options_template(&self) -> bool {
+    self.id() == Self::OPTIONS_TEMPLATE_ID
+  }
+
+  pub fn is_data(&self) -> bool {
+    self.id() >= Self::MIN_DATA_ID
+  }
+
+  /// Template records of a template set, nothing for other sets
+  pub fn templates(&self) -> TemplateIterator<'a> {
+    TemplateIterator {
+      data: if self.is_template() { self.body() } else { &[] },
+      offset: self.offset + Self::HEADER_LEN,
+    }
+  }
+
+  /// Options template records of an options template set, nothing for other sets
+  pub fn options_templates(&self) -> OptionsTemplateIterator<'a> {
+    OptionsTemplateIterator {
+      data: if self.is_options_template() {
+        self.body()
+      } else {
+        &[]
+      },
+      offset: self.offset + Self::HEADER_LEN,
+    }
+  }
+
+  /// Data records of a data set decoded with `template`, nothing if the
+  /// template ID does not match the set ID
+  pub fn records<'t>(&self, template: &'t crate::ipfix::Template) -> DataRecordIterator<'a, 't> {
+    DataRecordIterator {
+      data: if self.is_data() && self.id() == template.id {
+        self.body()
+      } else {
+        &[]
+      },
+      offset: self.offset + Self::HEADER_LEN,
+      template,
+      min_record_length: template.min_record_length(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn set_new_should_fail_with_invalid_length() {
+    assert_eq!(
+      Set::new(&[0x01, 0x00, 0x00, 0x03]),
+      Err(Error::InvalidLength {
+        protocol: Protocol::Ipfix,
+        offset: 0,
+        length: 3,
+      })
+    );
+  }
+
+  #[test]
+  fn set_templates_should_be_empty_for_data_sets() {
+    let set = Set::new(&[0x01, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00]).unwrap();
+
+    assert!(set.is_data());
+    assert_eq!(set.templates().count(), 0);
+    assert_eq!(set.options_templates().count(), 0);
+  }
+}
@@ -0,0 +1,294 @@
+use super::{Error, FieldSpec, Protocol};
+use byteorder::{ByteOrder, NetworkEndian};
+
+const ENTERPRISE_BIT: u16 = 0x8000;
+
+/// Iterator over the field specifiers of a template
+pub struct FieldSpecIterator<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) count: usize,
+}
+
+impl<'a> Iterator for FieldSpecIterator<'a> {
+  type Item = FieldSpec;
+
+  fn next(&mut self) -> Option<FieldSpec> {
+    if self.count == 0 {
+      return None;
+    }
+
+    let id = NetworkEndian::read_u16(&self.data[0..2]);
+    let length = NetworkEndian::read_u16(&self.data[2..4]);
+    let (enterprise_number, len) = if id & ENTERPRISE_BIT != 0 {
+      (Some(NetworkEndian::read_u32(&self.data[4..8])), 8)
+    } else {
+      (None, 4)
+    };
+    self.data = &self.data[len..];
+    self.count -= 1;
+    Some(FieldSpec {
+      information_element: id & !ENTERPRISE_BIT,
+      length,
+      enterprise_number,
+    })
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    (self.count, Some(self.count))
+  }
+}
+
+/// Returns the offset following `count` field specifiers starting at `start`
+fn field_specs_end(data: &[u8], start: usize, count: usize) -> Result<usize, Error> {
+  let mut end = start;
+  for _ in 0..count {
+    let mut len = 4;
+    if data.len() >= end + 2 && NetworkEndian::read_u16(&data[end..end + 2]) & ENTERPRISE_BIT != 0 {
+      len = 8;
+    }
+    if data.len() < end + len {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::Ipfix,
+        offset: end,
+        expected: len,
+        actual: data.len() - end,
+      });
+    }
+    end += len;
+  }
+  Ok(end)
+}
+
+/// An IPFIX template record helper struct
+#[derive(PartialEq)]
+pub struct Template<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for Template<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Template")
+      .field("id", &self.id())
+      .field("fields", &self.fields().collect::<Vec<_>>())
+      .finish()
+  }
+}
+
+impl<'a> Template<'a> {
+  pub const HEADER_LEN: usize = 4;
+
+  /// Parses the template record at the start of `data`, ignoring any trailing bytes
+  pub fn new(data: &'a [u8]) -> Result<Template<'a>, Error> {
+    if data.len() < Self::HEADER_LEN {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::Ipfix,
+        offset: 0,
+        expected: Self::HEADER_LEN,
+        actual: data.len(),
+      });
+    }
+    let count = NetworkEndian::read_u16(&data[2..4]) as usize;
+    let len = field_specs_end(data, Self::HEADER_LEN, count)?;
+
+    Ok(Template { data: &data[..len] })
+  }
+
+  /// ID of the template, matching the ID of the data sets it describes
+  pub fn id(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[0..2]))
+  }
+
+  /// Number of fields in the template
+  pub fn field_count(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[2..4]))
+  }
+
+  /// Whether this record withdraws the template instead of announcing it
+  pub fn is_withdrawal(&self) -> bool {
+    self.field_count() == 0
+  }
+
+  /// Information element, length and enterprise number of each field, in data record order
+  pub fn fields(&self) -> FieldSpecIterator<'a> {
+    FieldSpecIterator {
+      data: &self.data[Self::HEADER_LEN..],
+      count: self.field_count() as usize,
+    }
+  }
+}
+
+/// An IPFIX options template record helper struct
+#[derive(PartialEq)]
+pub struct OptionsTemplate<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for OptionsTemplate<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("OptionsTemplate")
+      .field("id", &self.id())
+      .field("scope_fields", &self.scope_fields().collect::<Vec<_>>())
+      .field("fields", &self.fields().collect::<Vec<_>>())
+      .finish()
+  }
+}
+
+impl<'a> OptionsTemplate<'a> {
+  pub const HEADER_LEN: usize = 6;
+  /// Length of an options template withdrawal record, which has no scope field count
+  pub const WITHDRAWAL_LEN: usize = 4;
+
+  /// Parses the options template record at the start of `data`, ignoring any trailing bytes
+  pub fn new(data: &'a [u8]) -> Result<OptionsTemplate<'a>, Error> {
+    if data.len() < Self::WITHDRAWAL_LEN {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::Ipfix,
+        offset: 0,
+        expected: Self::WITHDRAWAL_LEN,
+        actual: data.len(),
+      });
+    }
+    let count = NetworkEndian::read_u16(&data[2..4]);
+    if count == 0 {
+      return Ok(OptionsTemplate {
+        data: &data[..Self::WITHDRAWAL_LEN],
+      });
+    }
+    if data.len() < Self::HEADER_LEN {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::Ipfix,
+        offset: 0,
+        expected: Self::HEADER_LEN,
+        actual: data.len(),
+      });
+    }
+    let scope_count = NetworkEndian::read_u16(&data[4..6]);
+    if scope_count == 0 || scope_count > count {
+      return Err(Error::InvalidCount {
+        protocol: Protocol::Ipfix,
+        count: scope_count,
+      });
+    }
+    let len = field_specs_end(data, Self::HEADER_LEN, count as usize)?;
+
+    Ok(OptionsTemplate { data: &data[..len] })
+  }
+
+  /// ID of the template, matching the ID of the data sets it describes
+  pub fn id(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[0..2]))
+  }
+
+  /// Number of fields in the template, including scope fields
+  pub fn field_count(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[2..4]))
+  }
+
+  /// Number of scope fields, which come first in the template
+  pub fn scope_field_count(&self) -> u16 {
+    if self.is_withdrawal() {
+      return 0;
+    }
+    NetworkEndian::read_u16(&(self.data[4..6]))
+  }
+
+  /// Whether this record withdraws the options template instead of announcing it
+  pub fn is_withdrawal(&self) -> bool {
+    self.field_count() == 0
+  }
+
+  /// Information element, length and enterprise number of each scope field
+  pub fn scope_fields(&self) -> FieldSpecIterator<'a> {
+    let mut specs = self.all_fields();
+    specs.count = self.scope_field_count() as usize;
+    specs
+  }
+
+  /// Information element, length and enterprise number of each field following the scope fields
+  pub fn fields(&self) -> FieldSpecIterator<'a> {
+    let mut specs = self.all_fields();
+    for _ in 0..self.scope_field_count() {
+      specs.next();
+    }
+    specs
+  }
+
+  fn all_fields(&self) -> FieldSpecIterator<'a> {
+    if self.is_withdrawal() {
+      return FieldSpecIterator {
+        data: &[],
+        count: 0,
+      };
+    }
+    FieldSpecIterator {
+      data: &self.data[Self::HEADER_LEN..],
+      count: self.field_count() as usize,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn template_new_should_read_enterprise_fields() {
+    let data = [
+      0x01, 0x00, 0x00, 0x02, 0x00, 0x08, 0x00, 0x04, 0x80, 0x01, 0xff, 0xff, 0x00, 0x00, 0x00,
+      0x09,
+    ];
+    let template = Template::new(&data).unwrap();
+
+    assert_eq!(
+      template.fields().collect::<Vec<_>>(),
+      vec![
+        FieldSpec {
+          information_element: 8,
+          length: 4,
+          enterprise_number: None,
+        },
+        FieldSpec {
+          information_element: 1,
+          length: FieldSpec::VARIABLE_LENGTH,
+          enterprise_number: Some(9),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn template_new_should_fail_with_truncated_enterprise_field() {
+    let data = [0x01, 0x00, 0x00, 0x01, 0x80, 0x01, 0x00, 0x04, 0x00, 0x00];
+
+    assert_eq!(
+      Template::new(&data),
+      Err(Error::NotEnoughData {
+        protocol: Protocol::Ipfix,
+        offset: 4,
+        expected: 8,
+        actual: 6,
+      })
+    );
+  }
+
+  #[test]
+  fn template_new_should_accept_withdrawal() {
+    let template = Template::new(&[0x01, 0x00, 0x00, 0x00]).unwrap();
+
+    assert!(template.is_withdrawal());
+    assert_eq!(template.fields().count(), 0);
+  }
+
+  #[test]
+  fn options_template_new_should_fail_with_invalid_scope_count() {
+    let data = [0x01, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0x95, 0x00, 0x04];
+
+    assert_eq!(
+      OptionsTemplate::new(&data),
+      Err(Error::InvalidCount {
+        protocol: Protocol::Ipfix,
+        count: 0,
+      })
+    );
+  }
+}
@@ -0,0 +1,74 @@
+use super::raw;
+use super::FieldSpec;
+
+/// An owned IPFIX template or options template
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Template {
+  /// ID of the template, matching the ID of the data sets it describes
+  pub id: u16,
+  /// Scope fields, only announced by options templates
+  pub scope_fields: Vec<FieldSpec>,
+  /// Fields, following the scope fields in data records
+  pub fields: Vec<FieldSpec>,
+}
+
+impl Template {
+  fn specs(&self) -> impl Iterator<Item = &FieldSpec> {
+    self.scope_fields.iter().chain(self.fields.iter())
+  }
+
+  /// Length in bytes of the data records described by this template, unless
+  /// it has variable-length fields
+  pub fn record_length(&self) -> Option<usize> {
+    self
+      .specs()
+      .map(|spec| {
+        if spec.is_variable_length() {
+          None
+        } else {
+          Some(spec.length as usize)
+        }
+      })
+      .sum()
+  }
+
+  /// Minimum length in bytes of the data records described by this template,
+  /// counting one byte for each variable-length field
+  pub fn min_record_length(&self) -> usize {
+    self
+      .specs()
+      .map(|spec| {
+        if spec.is_variable_length() {
+          1
+        } else {
+          spec.length as usize
+        }
+      })
+      .sum()
+  }
+
+  /// Whether this template describes options data records, which start with scope fields
+  pub fn is_options(&self) -> bool {
+    !self.scope_fields.is_empty()
+  }
+}
+
+impl<'a> From<&raw::Template<'a>> for Template {
+  fn from(template: &raw::Template<'a>) -> Template {
+    Template {
+      id: template.id(),
+      scope_fields: Vec::new(),
+      fields: template.fields().collect(),
+    }
+  }
+}
+
+impl<'a> From<&raw::OptionsTemplate<'a>> for Template {
+  fn from(template: &raw::OptionsTemplate<'a>) -> Template {
+    Template {
+      id: template.id(),
+      scope_fields: template.scope_fields().collect(),
+      fields: template.fields().collect(),
+    }
+  }
+}
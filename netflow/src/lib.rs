@@ -1,10 +1,42 @@
 //! This is the documentation for `netflow`
 //!
+//! # Features
+//!
+//! * `serde`: derives `Serialize` and `Deserialize` for the owned types, such
+//!   as `v5::FlowRecord` and `v5::PacketHeader`
+//! * `chrono`: adds `chrono::DateTime` variants of the wall-clock time accessors
+//! * `flow-tools`: adds the `flow_tools` module, writing and reading v5 records
+//!   in flow-tools files, plain or compressed with zlib
+//! * `pcap`: adds the `pcap` module, reading export packets out of pcap and
+//!   pcapng captures
+//! * `arrow`: adds the `arrow` module, building Arrow record batches of v5
+//!   records and writing them to rolling Parquet files
+//!
 //! # Examples
 
 extern crate byteorder;
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+mod error;
+mod fixed;
+mod flow;
+#[cfg(feature = "flow-tools")]
+pub mod flow_tools;
+pub mod ipfix;
+mod packet;
+#[cfg(feature = "pcap")]
+pub mod pcap;
+pub mod sflow;
+pub mod v1;
 pub mod v5;
+pub mod v7;
+pub mod v9;
+mod value;
+
+pub use error::{Error, Protocol};
+pub use flow::Flow;
+pub use packet::{parse, Packet};
 
 /// Say hello from netflow crate
 ///
@@ -0,0 +1,109 @@
+use crate::{ipfix, v1, v5, v7, v9, Error, Protocol};
+use byteorder::{ByteOrder, NetworkEndian};
+
+/// A packet of any supported flow export protocol
+#[derive(Debug, PartialEq)]
+pub enum Packet<'a> {
+  V1(v1::raw::FlowSet<'a>),
+  V5(v5::raw::FlowSet<'a>),
+  V7(v7::raw::FlowSet<'a>),
+  V9(v9::raw::Packet<'a>),
+  Ipfix(ipfix::raw::Message<'a>),
+}
+
+impl<'a> Packet<'a> {
+  /// Protocol the packet was decoded as
+  pub fn protocol(&self) -> Protocol {
+    match self {
+      Packet::V1(_) => Protocol::V1,
+      Packet::V5(_) => Protocol::V5,
+      Packet::V7(_) => Protocol::V7,
+      Packet::V9(_) => Protocol::V9,
+      Packet::Ipfix(_) => Protocol::Ipfix,
+    }
+  }
+
+  /// Export format version number of the packet
+  pub fn version(&self) -> u16 {
+    match self {
+      Packet::V1(_) => v1::raw::Header::VERSION,
+      Packet::V5(_) => v5::raw::Header::VERSION,
+      Packet::V7(_) => v7::raw::Header::VERSION,
+      Packet::V9(_) => v9::raw::Header::VERSION,
+      Packet::Ipfix(_) => ipfix::raw::Header::VERSION,
+    }
+  }
+}
+
+/// Decodes a packet of any supported protocol, chosen from the export format
+/// version number it starts with
+///
+/// Each protocol is decoded with its strict parser, such as
+/// `v5::raw::FlowSet::new` or `v9::raw::Packet::new`.
+pub fn parse(data: &[u8]) -> Result<Packet<'_>, Error> {
+  if data.len() < 2 {
+    return Err(Error::MissingVersion { actual: data.len() });
+  }
+
+  match NetworkEndian::read_u16(&data[0..2]) {
+    v1::raw::Header::VERSION => v1::raw::FlowSet::new(data).map(Packet::V1),
+    v5::raw::Header::VERSION => v5::raw::FlowSet::new(data).map(Packet::V5),
+    v7::raw::Header::VERSION => v7::raw::FlowSet::new(data).map(Packet::V7),
+    v9::raw::Header::VERSION => v9::raw::Packet::new(data).map(Packet::V9),
+    ipfix::raw::Header::VERSION => ipfix::raw::Message::new(data).map(Packet::Ipfix),
+    version => Err(Error::UnsupportedVersion { version }),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_should_dispatch_on_version() {
+    let packets: [(&[u8], Protocol); 5] = [
+      (&v1::tests::FLOW_PACKET_1, Protocol::V1),
+      (&v5::tests::FLOW_PACKET_1, Protocol::V5),
+      (&v7::tests::FLOW_PACKET_1, Protocol::V7),
+      (&v9::tests::FLOW_PACKET_1, Protocol::V9),
+      (&ipfix::tests::MESSAGE_1, Protocol::Ipfix),
+    ];
+
+    for &(data, protocol) in packets.iter() {
+      let packet = parse(data).unwrap();
+      assert_eq!(packet.protocol(), protocol);
+      assert_eq!(packet.version(), NetworkEndian::read_u16(data));
+    }
+  }
+
+  #[test]
+  fn parse_should_expose_decoded_packet() {
+    match parse(&v5::tests::FLOW_PACKET_1).unwrap() {
+      Packet::V5(set) => assert_eq!(set.len(), 29),
+      packet => panic!("unexpected packet {:?}", packet),
+    }
+  }
+
+  #[test]
+  fn parse_should_fail_with_unsupported_version() {
+    assert_eq!(
+      parse(&[0x00, 0x08, 0x00, 0x01]),
+      Err(Error::UnsupportedVersion { version: 8 })
+    );
+    assert_eq!(parse(&[0x00]), Err(Error::MissingVersion { actual: 1 }));
+  }
+
+  #[test]
+  fn parse_should_report_errors_of_the_protocol() {
+    let mut data = v5::tests::FLOW_PACKET_1.to_vec();
+    data[3] = 0;
+
+    assert_eq!(
+      parse(&data),
+      Err(Error::InvalidCount {
+        protocol: Protocol::V5,
+        count: 0,
+      })
+    );
+  }
+}
@@ -0,0 +1,477 @@
+use super::Error;
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use std::io::{self, Read};
+use std::ops::Range;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const PCAP_HEADER_LEN: usize = 24;
+const PCAP_RECORD_HEADER_LEN: usize = 16;
+
+const SECTION_HEADER_BLOCK: u32 = 0x0a0d_0d0a;
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 1;
+const ENHANCED_PACKET_BLOCK: u32 = 6;
+const BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+const OPTION_END: u16 = 0;
+const OPTION_IF_TSRESOL: u16 = 9;
+/// Timestamp resolution of an interface without `if_tsresol`: microseconds
+const DEFAULT_RESOLUTION: u8 = 6;
+
+/// Largest record or block accepted, so that a corrupt length does not
+/// allocate gigabytes
+const MAX_LEN: usize = 16 * 1024 * 1024;
+
+/// A captured link-layer frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame<'a> {
+  /// Time at which the frame was captured
+  pub timestamp: SystemTime,
+  /// Link-layer header type of the frame, see `link_type`
+  pub link_type: u16,
+  /// Length of the frame on the wire, of which `data` may only hold the start
+  pub original_len: usize,
+  /// Captured bytes of the frame
+  pub data: &'a [u8],
+}
+
+/// Position of a frame within the buffer of its reader
+pub(crate) struct FrameInfo {
+  pub(crate) timestamp: SystemTime,
+  pub(crate) link_type: u16,
+  pub(crate) original_len: usize,
+  pub(crate) data: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Endian {
+  Little,
+  Big,
+}
+
+impl Endian {
+  fn u16(self, data: &[u8]) -> u16 {
+    match self {
+      Endian::Little => LittleEndian::read_u16(data),
+      Endian::Big => BigEndian::read_u16(data),
+    }
+  }
+
+  fn u32(self, data: &[u8]) -> u32 {
+    match self {
+      Endian::Little => LittleEndian::read_u32(data),
+      Endian::Big => BigEndian::read_u32(data),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Interface {
+  link_type: u16,
+  resolution: u8,
+}
+
+#[derive(Debug)]
+enum Format {
+  Pcap { nanos: bool, link_type: u16 },
+  Pcapng,
+}
+
+/// Reads the frames of a pcap or pcapng capture, one at a time
+///
+/// Each frame is read into the same buffer, which the next call overwrites.
+/// Of pcapng blocks, only interface descriptions and enhanced packets are
+/// read: simple packet blocks, which carry no timestamp, are skipped along
+/// with every other block type.
+#[derive(Debug)]
+pub struct Reader<R> {
+  reader: R,
+  endian: Endian,
+  format: Format,
+  /// Interfaces described so far in the current pcapng section
+  interfaces: Vec<Interface>,
+  buffer: Vec<u8>,
+  offset: u64,
+}
+
+impl<R: Read> Reader<R> {
+  /// Reads the file header of a capture, telling pcap from pcapng by its
+  /// magic number
+  pub fn new(mut reader: R) -> Result<Reader<R>, Error> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    let mut this = Reader {
+      reader,
+      endian: Endian::Big,
+      format: Format::Pcapng,
+      interfaces: Vec::new(),
+      buffer: Vec::new(),
+      offset: magic.len() as u64,
+    };
+
+    match (BigEndian::read_u32(&magic), LittleEndian::read_u32(&magic)) {
+      (SECTION_HEADER_BLOCK, _) => {
+        let mut length = [0; 4];
+        this.read(&mut length)?;
+        this.read_section_header(0, &length)?
+      }
+      (magic, _) if magic == PCAP_MAGIC_MICROS || magic == PCAP_MAGIC_NANOS => {
+        this.read_pcap_header(Endian::Big, magic == PCAP_MAGIC_NANOS)?
+      }
+      (_, magic) if magic == PCAP_MAGIC_MICROS || magic == PCAP_MAGIC_NANOS => {
+        this.read_pcap_header(Endian::Little, magic == PCAP_MAGIC_NANOS)?
+      }
+      (magic, _) => return Err(Error::InvalidMagic(magic)),
+    }
+    Ok(this)
+  }
+
+  /// Reads the next frame, or `None` at the end of the capture
+  pub fn next_frame(&mut self) -> Result<Option<Frame<'_>>, Error> {
+    Ok(self.advance()?.map(move |info| Frame {
+      timestamp: info.timestamp,
+      link_type: info.link_type,
+      original_len: info.original_len,
+      data: &self.buffer[info.data],
+    }))
+  }
+
+  /// Bytes of the frame last read by `advance`
+  pub(crate) fn data(&self, info: &FrameInfo) -> &[u8] {
+    &self.buffer[info.data.clone()]
+  }
+
+  pub(crate) fn advance(&mut self) -> Result<Option<FrameInfo>, Error> {
+    match self.format {
+      Format::Pcap { nanos, link_type } => self.next_record(nanos, link_type),
+      Format::Pcapng => self.next_packet_block(),
+    }
+  }
+
+  fn read_pcap_header(&mut self, endian: Endian, nanos: bool) -> Result<(), Error> {
+    let mut header = [0; PCAP_HEADER_LEN - 4];
+    self.read(&mut header)?;
+    self.endian = endian;
+    self.format = Format::Pcap {
+      nanos,
+      // The upper bits of the network field carry FCS information
+      link_type: endian.u32(&header[16..20]) as u16,
+    };
+    Ok(())
+  }
+
+  fn next_record(&mut self, nanos: bool, link_type: u16) -> Result<Option<FrameInfo>, Error> {
+    let offset = self.offset;
+    let mut header = [0; PCAP_RECORD_HEADER_LEN];
+    if !self.read_or_eof(&mut header)? {
+      return Ok(None);
+    }
+    let endian = self.endian;
+    let captured_len = endian.u32(&header[8..12]) as usize;
+    if captured_len > MAX_LEN {
+      return Err(Error::InvalidLength {
+        offset,
+        length: captured_len,
+      });
+    }
+    self.fill(captured_len)?;
+
+    let fraction = endian.u32(&header[4..8]);
+    let nanos = if nanos {
+      u64::from(fraction)
+    } else {
+      u64::from(fraction) * 1000
+    };
+    let since_epoch =
+      Duration::from_secs(u64::from(endian.u32(&header[0..4]))) + Duration::from_nanos(nanos);
+    Ok(Some(FrameInfo {
+      timestamp: UNIX_EPOCH
+        .checked_add(since_epoch)
+        .ok_or(Error::InvalidTimestamp { offset })?,
+      link_type,
+      original_len: endian.u32(&header[12..16]) as usize,
+      data: 0..captured_len,
+    }))
+  }
+
+  fn next_packet_block(&mut self) -> Result<Option<FrameInfo>, Error> {
+    loop {
+      let offset = self.offset;
+      let mut header = [0; 8];
+      if !self.read_or_eof(&mut header)? {
+        return Ok(None);
+      }
+      // Reads the same in both byte orders
+      if BigEndian::read_u32(&header[0..4]) == SECTION_HEADER_BLOCK {
+        self.read_section_header(offset, &header[4..8])?;
+        continue;
+      }
+
+      let endian = self.endian;
+      let block_type = endian.u32(&header[0..4]);
+      let length = endian.u32(&header[4..8]) as usize;
+      self.check_block_length(offset, length, 12)?;
+      self.fill(length - 8)?;
+      let body = &self.buffer[..length - 12];
+
+      match block_type {
+        INTERFACE_DESCRIPTION_BLOCK => {
+          if body.len() < 8 {
+            return Err(Error::InvalidLength { offset, length });
+          }
+          self.interfaces.push(Interface {
+            link_type: endian.u16(&body[0..2]),
+            resolution: resolution(endian, &body[8..]),
+          });
+        }
+        ENHANCED_PACKET_BLOCK => {
+          if body.len() < 20 {
+            return Err(Error::InvalidLength { offset, length });
+          }
+          let id = endian.u32(&body[0..4]);
+          let interface = *self
+            .interfaces
+            .get(id as usize)
+            .ok_or(Error::UnknownInterface {
+              offset,
+              interface: id,
+            })?;
+          let captured_len = endian.u32(&body[12..16]) as usize;
+          if captured_len > body.len() - 20 {
+            return Err(Error::InvalidLength { offset, length });
+          }
+          let units =
+            u64::from(endian.u32(&body[4..8])) << 32 | u64::from(endian.u32(&body[8..12]));
+          return Ok(Some(FrameInfo {
+            timestamp: UNIX_EPOCH
+              .checked_add(duration(units, interface.resolution))
+              .ok_or(Error::InvalidTimestamp { offset })?,
+            link_type: interface.link_type,
+            original_len: endian.u32(&body[16..20]) as usize,
+            data: 20..20 + captured_len,
+          }));
+        }
+        _ => {}
+      }
+    }
+  }
+
+  /// Reads the rest of the section header block at `offset`, once its type
+  /// and its `length` in unknown byte order were read
+  fn read_section_header(&mut self, offset: u64, length: &[u8]) -> Result<(), Error> {
+    let mut magic = [0; 4];
+    self.read(&mut magic)?;
+    self.endian = match BigEndian::read_u32(&magic) {
+      BYTE_ORDER_MAGIC => Endian::Big,
+      _ if LittleEndian::read_u32(&magic) == BYTE_ORDER_MAGIC => Endian::Little,
+      magic => return Err(Error::InvalidMagic(magic)),
+    };
+    let length = self.endian.u32(length) as usize;
+    self.check_block_length(offset, length, 16)?;
+    self.fill(length - 12)?;
+    self.format = Format::Pcapng;
+    self.interfaces.clear();
+    Ok(())
+  }
+
+  fn check_block_length(&self, offset: u64, length: usize, min: usize) -> Result<(), Error> {
+    if length < min || !length.is_multiple_of(4) || length > MAX_LEN {
+      return Err(Error::InvalidLength { offset, length });
+    }
+    Ok(())
+  }
+
+  /// Reads `len` bytes into the start of the buffer
+  fn fill(&mut self, len: usize) -> Result<(), Error> {
+    self.buffer.resize(len, 0);
+    self.reader.read_exact(&mut self.buffer)?;
+    self.offset += len as u64;
+    Ok(())
+  }
+
+  fn read(&mut self, data: &mut [u8]) -> Result<(), Error> {
+    self.reader.read_exact(data)?;
+    self.offset += data.len() as u64;
+    Ok(())
+  }
+
+  /// Fills `data`, or returns `false` if the capture ends right away
+  fn read_or_eof(&mut self, data: &mut [u8]) -> Result<bool, Error> {
+    let mut read = 0;
+    while read < data.len() {
+      match self.reader.read(&mut data[read..]) {
+        Ok(0) if read == 0 => return Ok(false),
+        Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+        Ok(n) => read += n,
+        Err(ref error) if error.kind() == io::ErrorKind::Interrupted => {}
+        Err(error) => return Err(error.into()),
+      }
+    }
+    self.offset += read as u64;
+    Ok(true)
+  }
+}
+
+/// Timestamp resolution from the `if_tsresol` option of an interface
+fn resolution(endian: Endian, mut options: &[u8]) -> u8 {
+  while options.len() >= 4 {
+    let code = endian.u16(&options[0..2]);
+    let length = endian.u16(&options[2..4]) as usize;
+    if code == OPTION_END {
+      break;
+    }
+    if code == OPTION_IF_TSRESOL && length == 1 && options.len() > 4 {
+      return options[4];
+    }
+    let padded = length.div_ceil(4) * 4;
+    options = options.get(4 + padded..).unwrap_or(&[]);
+  }
+  DEFAULT_RESOLUTION
+}
+
+/// Converts a pcapng timestamp, in units of the given resolution: a negative
+/// power of 10, or of 2 if the most significant bit is set
+fn duration(units: u64, resolution: u8) -> Duration {
+  let exponent = u32::from(resolution & 0x7f);
+  let per_second = if resolution & 0x80 == 0 {
+    10u128.checked_pow(exponent)
+  } else {
+    1u128.checked_shl(exponent)
+  };
+  match per_second {
+    Some(per_second) => {
+      let nanos = u128::from(units) * 1_000_000_000 / per_second;
+      Duration::new(
+        (nanos / 1_000_000_000) as u64,
+        (nanos % 1_000_000_000) as u32,
+      )
+    }
+    None => Duration::from_secs(0),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::{pcap, pcapng};
+  use super::*;
+
+  const FRAME: [u8; 6] = [1, 2, 3, 4, 5, 6];
+
+  #[test]
+  fn reader_should_read_pcap_in_both_byte_orders() {
+    for &big_endian in [false, true].iter() {
+      let data = pcap(big_endian, false, 1, &[(1544476581, 250_000, &FRAME[..])]);
+      let mut reader = Reader::new(&data[..]).unwrap();
+
+      assert_eq!(
+        reader.next_frame().unwrap(),
+        Some(Frame {
+          timestamp: UNIX_EPOCH + Duration::from_millis(1544476581250),
+          link_type: 1,
+          original_len: 6,
+          data: &FRAME,
+        })
+      );
+      assert_eq!(reader.next_frame().unwrap(), None);
+    }
+  }
+
+  #[test]
+  fn reader_should_read_nanosecond_pcap() {
+    let data = pcap(false, true, 101, &[(10, 5, &FRAME[..])]);
+    let mut reader = Reader::new(&data[..]).unwrap();
+    let frame = reader.next_frame().unwrap().unwrap();
+
+    assert_eq!(frame.timestamp, UNIX_EPOCH + Duration::new(10, 5));
+    assert_eq!(frame.link_type, 101);
+  }
+
+  #[test]
+  fn reader_should_read_pcapng_enhanced_packets() {
+    for &big_endian in [false, true].iter() {
+      // Nanosecond resolution
+      let data = pcapng(
+        big_endian,
+        Some(9),
+        1,
+        &[(1_544_476_581_000_000_007, &FRAME[..])],
+      );
+      let mut reader = Reader::new(&data[..]).unwrap();
+
+      assert_eq!(
+        reader.next_frame().unwrap(),
+        Some(Frame {
+          timestamp: UNIX_EPOCH + Duration::new(1544476581, 7),
+          link_type: 1,
+          original_len: 6,
+          data: &FRAME,
+        })
+      );
+      assert_eq!(reader.next_frame().unwrap(), None);
+    }
+  }
+
+  #[test]
+  fn reader_should_default_to_microsecond_pcapng_timestamps() {
+    let data = pcapng(false, None, 113, &[(1_500_000, &FRAME[..])]);
+    let mut reader = Reader::new(&data[..]).unwrap();
+    let frame = reader.next_frame().unwrap().unwrap();
+
+    assert_eq!(frame.timestamp, UNIX_EPOCH + Duration::from_millis(1500));
+    assert_eq!(frame.link_type, 113);
+  }
+
+  #[test]
+  fn reader_should_fail_with_invalid_magic() {
+    match Reader::new(&[0u8; 24][..]) {
+      Err(Error::InvalidMagic(0)) => {}
+      result => panic!("unexpected result {:?}", result.map(|_| ())),
+    }
+  }
+
+  #[test]
+  fn reader_should_fail_with_truncated_frame() {
+    let data = pcap(false, false, 1, &[(0, 0, &FRAME[..])]);
+    let mut reader = Reader::new(&data[..data.len() - 1]).unwrap();
+
+    match reader.next_frame() {
+      Err(Error::Io(error)) => assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof),
+      result => panic!("unexpected result {:?}", result),
+    }
+  }
+
+  #[test]
+  fn reader_should_fail_with_unknown_interface() {
+    let mut data = pcapng(false, None, 1, &[(0, &FRAME[..])]);
+    // Interface id of the enhanced packet block, after the section header
+    // block (28 bytes), the interface description block (20 bytes) and the
+    // name resolution block (16 bytes)
+    data[28 + 20 + 16 + 8] = 1;
+    let mut reader = Reader::new(&data[..]).unwrap();
+
+    match reader.next_frame() {
+      Err(Error::UnknownInterface {
+        offset: 64,
+        interface: 1,
+      }) => {}
+      result => panic!("unexpected result {:?}", result),
+    }
+  }
+
+  #[test]
+  fn reader_should_fail_with_timestamp_out_of_range() {
+    let data = pcapng(false, Some(0), 1, &[(u64::MAX, &FRAME[..])]);
+    let mut reader = Reader::new(&data[..]).unwrap();
+
+    match reader.next_frame() {
+      Err(Error::InvalidTimestamp { offset: 76 }) => {}
+      result => panic!("unexpected result {:?}", result),
+    }
+  }
+
+  #[test]
+  fn duration_should_support_binary_resolutions() {
+    assert_eq!(duration(3 << 20, 0x80 | 20), Duration::from_secs(3));
+    assert_eq!(duration(1500, 3), Duration::from_millis(1500));
+  }
+}
@@ -0,0 +1,229 @@
+use super::link_type;
+use byteorder::{ByteOrder, LittleEndian, NetworkEndian};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::ops::Range;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+/// 802.1Q, 802.1ad and the pre-standard QinQ tag
+const ETHERTYPE_VLAN: [u16; 3] = [0x8100, 0x88a8, 0x9100];
+
+const IP_PROTOCOL_UDP: u8 = 17;
+/// IPv6 extension headers skipped on the way to the UDP header: hop-by-hop
+/// options, routing and destination options
+const IPV6_EXTENSION_HEADERS: [u8; 3] = [0, 43, 60];
+
+/// A UDP datagram found in a frame, with its payload as a range of the frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Udp {
+  pub(crate) source: SocketAddr,
+  pub(crate) destination: SocketAddr,
+  pub(crate) payload: Range<usize>,
+}
+
+/// Finds the UDP datagram carried by a frame of the given link type
+///
+/// Fragmented IP packets are not reassembled, so their datagrams are not
+/// found. Neither are those cut by the capture snapshot length.
+pub(crate) fn udp(link_type: u16, data: &[u8]) -> Option<Udp> {
+  match link_type {
+    link_type::ETHERNET => ethernet(data),
+    link_type::LINUX_SLL => ethertype(data, NetworkEndian::read_u16(data.get(14..16)?), 16),
+    link_type::LINUX_SLL2 => ethertype(data, NetworkEndian::read_u16(data.get(0..2)?), 20),
+    link_type::NULL => {
+      // Address family in the byte order of the capturing host
+      let family = LittleEndian::read_u32(data.get(0..4)?);
+      match family.max(family.swap_bytes()) {
+        0x0200_0000 => ipv4(data, 4),
+        0x1800_0000 | 0x1c00_0000 | 0x1e00_0000 => ipv6(data, 4),
+        _ => None,
+      }
+    }
+    link_type::RAW => match data.first()? >> 4 {
+      4 => ipv4(data, 0),
+      6 => ipv6(data, 0),
+      _ => None,
+    },
+    link_type::IPV4 => ipv4(data, 0),
+    link_type::IPV6 => ipv6(data, 0),
+    _ => None,
+  }
+}
+
+fn ethernet(data: &[u8]) -> Option<Udp> {
+  let mut offset = 12;
+  let mut ethertype = NetworkEndian::read_u16(data.get(offset..offset + 2)?);
+  while ETHERTYPE_VLAN.contains(&ethertype) {
+    offset += 4;
+    ethertype = NetworkEndian::read_u16(data.get(offset..offset + 2)?);
+  }
+  self::ethertype(data, ethertype, offset + 2)
+}
+
+fn ethertype(data: &[u8], ethertype: u16, offset: usize) -> Option<Udp> {
+  match ethertype {
+    ETHERTYPE_IPV4 => ipv4(data, offset),
+    ETHERTYPE_IPV6 => ipv6(data, offset),
+    _ => None,
+  }
+}
+
+fn ipv4(data: &[u8], offset: usize) -> Option<Udp> {
+  let header = data.get(offset..offset + 20)?;
+  let header_len = usize::from(header[0] & 0x0f) * 4;
+  if header[0] >> 4 != 4 || header_len < 20 || header[9] != IP_PROTOCOL_UDP {
+    return None;
+  }
+  // More fragments flag or fragment offset
+  if NetworkEndian::read_u16(&header[6..8]) & 0x3fff != 0 {
+    return None;
+  }
+  let total_len = usize::from(NetworkEndian::read_u16(&header[2..4]));
+  if total_len < header_len {
+    return None;
+  }
+
+  let source = IpAddr::V4(Ipv4Addr::new(
+    header[12], header[13], header[14], header[15],
+  ));
+  let destination = IpAddr::V4(Ipv4Addr::new(
+    header[16], header[17], header[18], header[19],
+  ));
+  udp_datagram(
+    data,
+    offset + header_len..offset + total_len,
+    source,
+    destination,
+  )
+}
+
+fn ipv6(data: &[u8], offset: usize) -> Option<Udp> {
+  let header = data.get(offset..offset + 40)?;
+  if header[0] >> 4 != 6 {
+    return None;
+  }
+  let end = offset + 40 + usize::from(NetworkEndian::read_u16(&header[4..6]));
+  let mut next_header = header[6];
+  let mut position = offset + 40;
+  while IPV6_EXTENSION_HEADERS.contains(&next_header) {
+    let extension = data.get(position..position + 2)?;
+    next_header = extension[0];
+    position += (usize::from(extension[1]) + 1) * 8;
+  }
+  if next_header != IP_PROTOCOL_UDP || position > end {
+    return None;
+  }
+
+  let mut source = [0; 16];
+  source.copy_from_slice(&header[8..24]);
+  let mut destination = [0; 16];
+  destination.copy_from_slice(&header[24..40]);
+  udp_datagram(
+    data,
+    position..end,
+    IpAddr::V6(Ipv6Addr::from(source)),
+    IpAddr::V6(Ipv6Addr::from(destination)),
+  )
+}
+
+/// Reads the UDP datagram held by `data[range]`, the payload of an IP packet
+fn udp_datagram(
+  data: &[u8],
+  range: Range<usize>,
+  source: IpAddr,
+  destination: IpAddr,
+) -> Option<Udp> {
+  let start = range.start;
+  let datagram = data.get(range)?;
+  let header = datagram.get(0..8)?;
+  let len = usize::from(NetworkEndian::read_u16(&header[4..6]));
+  if len < 8 || len > datagram.len() {
+    return None;
+  }
+
+  Some(Udp {
+    source: SocketAddr::new(source, NetworkEndian::read_u16(&header[0..2])),
+    destination: SocketAddr::new(destination, NetworkEndian::read_u16(&header[2..4])),
+    payload: start + 8..start + len,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::{ethernet_ipv4, ethernet_ipv6, EXPORTER_V4, EXPORTER_V6};
+  use super::*;
+
+  const PAYLOAD: [u8; 4] = [0, 5, 0, 1];
+
+  #[test]
+  fn udp_should_be_found_in_ethernet_ipv4() {
+    let frame = ethernet_ipv4(&[], 2055, &PAYLOAD);
+    let udp = udp(link_type::ETHERNET, &frame).unwrap();
+
+    assert_eq!(udp.source, EXPORTER_V4);
+    assert_eq!(udp.destination.port(), 2055);
+    assert_eq!(&frame[udp.payload], &PAYLOAD);
+  }
+
+  #[test]
+  fn udp_should_be_found_behind_vlan_tags() {
+    let frame = ethernet_ipv4(&[0x88a8, 0x8100], 2055, &PAYLOAD);
+    let udp = udp(link_type::ETHERNET, &frame).unwrap();
+
+    assert_eq!(&frame[udp.payload], &PAYLOAD);
+  }
+
+  #[test]
+  fn udp_should_be_found_in_ethernet_ipv6() {
+    let frame = ethernet_ipv6(&[0x8100], 4739, &PAYLOAD);
+    let udp = udp(link_type::ETHERNET, &frame).unwrap();
+
+    assert_eq!(udp.source, EXPORTER_V6);
+    assert_eq!(udp.destination.port(), 4739);
+    assert_eq!(&frame[udp.payload], &PAYLOAD);
+  }
+
+  #[test]
+  fn udp_should_be_found_in_raw_and_cooked_frames() {
+    let frame = ethernet_ipv4(&[], 2055, &PAYLOAD);
+    let packet = &frame[14..];
+    let mut cooked = vec![0; 14];
+    cooked.extend_from_slice(&[0x08, 0x00]);
+    cooked.extend_from_slice(packet);
+    let mut null = vec![2, 0, 0, 0];
+    null.extend_from_slice(packet);
+
+    for &(link_type, data) in [
+      (link_type::RAW, packet),
+      (link_type::IPV4, packet),
+      (link_type::LINUX_SLL, &cooked[..]),
+      (link_type::NULL, &null[..]),
+    ]
+    .iter()
+    {
+      let udp = udp(link_type, data).unwrap();
+      assert_eq!(&data[udp.payload], &PAYLOAD);
+    }
+  }
+
+  #[test]
+  fn udp_should_ignore_ethernet_padding() {
+    let mut frame = ethernet_ipv4(&[], 2055, &PAYLOAD);
+    frame.extend_from_slice(&[0; 12]);
+    let udp = udp(link_type::ETHERNET, &frame).unwrap();
+
+    assert_eq!(&frame[udp.payload], &PAYLOAD);
+  }
+
+  #[test]
+  fn udp_should_skip_fragments_and_truncated_frames() {
+    let frame = ethernet_ipv4(&[], 2055, &PAYLOAD);
+    let mut fragment = frame.clone();
+    // More fragments flag
+    fragment[14 + 6] = 0x20;
+
+    assert_eq!(udp(link_type::ETHERNET, &fragment), None);
+    assert_eq!(udp(link_type::ETHERNET, &frame[..frame.len() - 1]), None);
+    assert_eq!(udp(link_type::ETHERNET, &frame[..20]), None);
+  }
+}
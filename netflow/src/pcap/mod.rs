@@ -0,0 +1,426 @@
+//! Reads export packets out of pcap and pcapng captures of export traffic,
+//! such as those of `tcpdump -w`
+//!
+//! `FlowReader` finds the UDP datagrams of a capture, tags each with the time
+//! it was captured and the exporter that sent it, and decodes it with
+//! `netflow::parse`:
+//!
+//! ```no_run
+//! # fn main() -> Result<(), netflow::pcap::Error> {
+//! use netflow::pcap::FlowReader;
+//! use netflow::Packet;
+//!
+//! let mut reader = FlowReader::open("export.pcap")?.port(2055);
+//! while let Some(captured) = reader.next_packet()? {
+//!   if let Ok(Packet::V5(set)) = captured.packet() {
+//!     println!("{:?} {} {}", captured.timestamp, captured.exporter, set.len());
+//!   }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod file;
+mod frame;
+
+pub use file::{Frame, Reader};
+
+use crate::Packet;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Link-layer header types of the frames UDP datagrams are found in
+pub mod link_type {
+  /// BSD loopback
+  pub const NULL: u16 = 0;
+  /// Ethernet, with any number of VLAN tags
+  pub const ETHERNET: u16 = 1;
+  /// Raw IPv4 or IPv6
+  pub const RAW: u16 = 101;
+  /// Linux cooked capture, as of `tcpdump -i any`
+  pub const LINUX_SLL: u16 = 113;
+  /// Raw IPv4
+  pub const IPV4: u16 = 228;
+  /// Raw IPv6
+  pub const IPV6: u16 = 229;
+  /// Linux cooked capture v2
+  pub const LINUX_SLL2: u16 = 276;
+}
+
+/// Errors returned while reading a capture
+#[derive(Debug)]
+pub enum Error {
+  /// Reading the capture failed, or it ended in the middle of a block
+  Io(io::Error),
+  /// The capture starts with neither a pcap nor a pcapng magic number
+  InvalidMagic(u32),
+  /// The record or block at `offset` has an impossible length
+  InvalidLength { offset: u64, length: usize },
+  /// The pcapng packet block at `offset` refers to an interface that was not
+  /// described before it
+  UnknownInterface { offset: u64, interface: u32 },
+  /// The timestamp of the record or block at `offset` is past what
+  /// `SystemTime` can hold
+  InvalidTimestamp { offset: u64 },
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::Io(error) => write!(f, "cannot read capture: {}", error),
+      Error::InvalidMagic(magic) => write!(f, "invalid capture magic number {:#010x}", magic),
+      Error::InvalidLength { offset, length } => {
+        write!(f, "invalid block length {} at offset {}", length, offset)
+      }
+      Error::UnknownInterface { offset, interface } => write!(
+        f,
+        "unknown interface {} for packet at offset {}",
+        interface, offset
+      ),
+      Error::InvalidTimestamp { offset } => {
+        write!(f, "invalid timestamp for packet at offset {}", offset)
+      }
+    }
+  }
+}
+
+impl std::error::Error for Error {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Error::Io(error) => Some(error),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for Error {
+  fn from(error: io::Error) -> Error {
+    Error::Io(error)
+  }
+}
+
+/// The payload of a captured UDP datagram, as sent by an exporter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Captured<'a> {
+  /// Time at which the datagram was captured
+  pub timestamp: SystemTime,
+  /// Source address of the datagram
+  pub exporter: SocketAddr,
+  /// Destination address of the datagram
+  pub collector: SocketAddr,
+  /// UDP payload
+  pub data: &'a [u8],
+}
+
+impl<'a> Captured<'a> {
+  /// Decodes the payload as a packet of any supported protocol, see
+  /// `netflow::parse`
+  pub fn packet(&self) -> Result<Packet<'a>, crate::Error> {
+    crate::parse(self.data)
+  }
+}
+
+/// Reads the UDP datagrams of a capture, optionally only those sent to some
+/// ports
+#[derive(Debug)]
+pub struct FlowReader<R> {
+  reader: Reader<R>,
+  ports: Vec<u16>,
+}
+
+impl FlowReader<BufReader<File>> {
+  /// Opens a pcap or pcapng file
+  pub fn open<P: AsRef<Path>>(path: P) -> Result<FlowReader<BufReader<File>>, Error> {
+    FlowReader::new(BufReader::new(File::open(path)?))
+  }
+}
+
+impl<R: Read> FlowReader<R> {
+  /// Reads a capture, see `Reader::new`
+  pub fn new(reader: R) -> Result<FlowReader<R>, Error> {
+    Ok(FlowReader {
+      reader: Reader::new(reader)?,
+      ports: Vec::new(),
+    })
+  }
+
+  /// Only reads datagrams sent to `port`, in addition to those of previous
+  /// calls
+  pub fn port(mut self, port: u16) -> FlowReader<R> {
+    self.ports.push(port);
+    self
+  }
+
+  /// Reads the next matching datagram, or `None` at the end of the capture
+  ///
+  /// Frames that carry no complete UDP datagram, such as IP fragments or
+  /// frames of other link types, are skipped.
+  pub fn next_packet(&mut self) -> Result<Option<Captured<'_>>, Error> {
+    loop {
+      let info = match self.reader.advance()? {
+        Some(info) => info,
+        None => return Ok(None),
+      };
+      let udp = match frame::udp(info.link_type, self.reader.data(&info)) {
+        Some(udp) => udp,
+        None => continue,
+      };
+      if self.ports.is_empty() || self.ports.contains(&udp.destination.port()) {
+        return Ok(Some(Captured {
+          timestamp: info.timestamp,
+          exporter: udp.source,
+          collector: udp.destination,
+          data: &self.reader.data(&info)[udp.payload],
+        }));
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use super::*;
+  use crate::{ipfix, v5};
+  use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+  use std::time::{Duration, UNIX_EPOCH};
+
+  pub const EXPORTER_V4: SocketAddr =
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), 50000);
+  pub const EXPORTER_V6: SocketAddr = SocketAddr::new(
+    IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+    50000,
+  );
+
+  fn udp(source_port: u16, destination_port: u16, payload: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&source_port.to_be_bytes());
+    data.extend_from_slice(&destination_port.to_be_bytes());
+    data.extend_from_slice(&(8 + payload.len() as u16).to_be_bytes());
+    // Checksum, optional over IPv4
+    data.extend_from_slice(&[0, 0]);
+    data.extend_from_slice(payload);
+    data
+  }
+
+  fn ethernet(vlans: &[u16], ethertype: u16) -> Vec<u8> {
+    let mut data = vec![0x02, 0, 0, 0, 0, 0x01, 0x02, 0, 0, 0, 0, 0x02];
+    for &vlan in vlans {
+      data.extend_from_slice(&vlan.to_be_bytes());
+      data.extend_from_slice(&[0x00, 0x64]);
+    }
+    data.extend_from_slice(&ethertype.to_be_bytes());
+    data
+  }
+
+  /// An Ethernet frame with the given VLAN tags, carrying a datagram from
+  /// `EXPORTER_V4` to `port` of 192.0.2.2
+  pub fn ethernet_ipv4(vlans: &[u16], port: u16, payload: &[u8]) -> Vec<u8> {
+    let udp = udp(EXPORTER_V4.port(), port, payload);
+    let mut data = ethernet(vlans, 0x0800);
+    data.extend_from_slice(&[0x45, 0, 0, 0, 0, 0, 0x40, 0, 64, 17, 0, 0]);
+    data.extend_from_slice(&[192, 0, 2, 1, 192, 0, 2, 2]);
+    let total_len = (20 + udp.len()) as u16;
+    let ip = data.len() - 20;
+    data[ip + 2..ip + 4].copy_from_slice(&total_len.to_be_bytes());
+    data.extend_from_slice(&udp);
+    data
+  }
+
+  /// An Ethernet frame with the given VLAN tags, carrying a datagram from
+  /// `EXPORTER_V6` to `port` of 2001:db8::2, behind a hop-by-hop options header
+  pub fn ethernet_ipv6(vlans: &[u16], port: u16, payload: &[u8]) -> Vec<u8> {
+    let udp = udp(EXPORTER_V6.port(), port, payload);
+    let mut data = ethernet(vlans, 0x86dd);
+    data.extend_from_slice(&[0x60, 0, 0, 0]);
+    data.extend_from_slice(&(8 + udp.len() as u16).to_be_bytes());
+    data.extend_from_slice(&[0, 64]);
+    data.extend_from_slice(&[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+    data.extend_from_slice(&[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2]);
+    data.extend_from_slice(&[17, 0, 1, 4, 0, 0, 0, 0]);
+    data.extend_from_slice(&udp);
+    data
+  }
+
+  /// A pcap file of the given frames, as (seconds, microseconds or
+  /// nanoseconds, data)
+  pub fn pcap(
+    big_endian: bool,
+    nanos: bool,
+    link_type: u32,
+    frames: &[(u32, u32, &[u8])],
+  ) -> Vec<u8> {
+    let u16 = |value: u16| {
+      if big_endian {
+        value.to_be_bytes()
+      } else {
+        value.to_le_bytes()
+      }
+    };
+    let u32 = |value: u32| {
+      if big_endian {
+        value.to_be_bytes()
+      } else {
+        value.to_le_bytes()
+      }
+    };
+    let mut data = Vec::new();
+    data.extend_from_slice(&u32(if nanos { 0xa1b2_3c4d } else { 0xa1b2_c3d4 }));
+    data.extend_from_slice(&u16(2));
+    data.extend_from_slice(&u16(4));
+    data.extend_from_slice(&[0; 8]);
+    data.extend_from_slice(&u32(65535));
+    data.extend_from_slice(&u32(link_type));
+    for &(seconds, fraction, frame) in frames {
+      data.extend_from_slice(&u32(seconds));
+      data.extend_from_slice(&u32(fraction));
+      data.extend_from_slice(&u32(frame.len() as u32));
+      data.extend_from_slice(&u32(frame.len() as u32));
+      data.extend_from_slice(frame);
+    }
+    data
+  }
+
+  /// A pcapng file of one section with one interface, optionally with an
+  /// `if_tsresol` option, and the given frames as (timestamp, data)
+  pub fn pcapng(
+    big_endian: bool,
+    resolution: Option<u8>,
+    link_type: u16,
+    frames: &[(u64, &[u8])],
+  ) -> Vec<u8> {
+    let u16 = |value: u16| {
+      if big_endian {
+        value.to_be_bytes()
+      } else {
+        value.to_le_bytes()
+      }
+    };
+    let u32 = |value: u32| {
+      if big_endian {
+        value.to_be_bytes()
+      } else {
+        value.to_le_bytes()
+      }
+    };
+    let block = |data: &mut Vec<u8>, block_type: u32, body: &[u8]| {
+      let padded = body.len().div_ceil(4) * 4;
+      let length = (12 + padded) as u32;
+      data.extend_from_slice(&u32(block_type));
+      data.extend_from_slice(&u32(length));
+      data.extend_from_slice(body);
+      data.resize(data.len() + padded - body.len(), 0);
+      data.extend_from_slice(&u32(length));
+    };
+
+    let mut data = Vec::new();
+    let mut body = Vec::new();
+    body.extend_from_slice(&u32(0x1a2b_3c4d));
+    body.extend_from_slice(&u16(1));
+    body.extend_from_slice(&u16(0));
+    body.extend_from_slice(&[0xff; 8]);
+    block(&mut data, 0x0a0d_0d0a, &body);
+
+    let mut body = Vec::new();
+    body.extend_from_slice(&u16(link_type));
+    body.extend_from_slice(&[0; 2]);
+    body.extend_from_slice(&u32(65535));
+    if let Some(resolution) = resolution {
+      body.extend_from_slice(&u16(9));
+      body.extend_from_slice(&u16(1));
+      body.extend_from_slice(&[resolution, 0, 0, 0]);
+      body.extend_from_slice(&[0; 4]);
+    }
+    block(&mut data, 1, &body);
+
+    // A name resolution block, to be skipped
+    block(&mut data, 4, &[0; 4]);
+
+    for &(timestamp, frame) in frames {
+      let mut body = Vec::new();
+      body.extend_from_slice(&u32(0));
+      body.extend_from_slice(&u32((timestamp >> 32) as u32));
+      body.extend_from_slice(&u32(timestamp as u32));
+      body.extend_from_slice(&u32(frame.len() as u32));
+      body.extend_from_slice(&u32(frame.len() as u32));
+      body.extend_from_slice(frame);
+      block(&mut data, 6, &body);
+    }
+    data
+  }
+
+  #[test]
+  fn flow_reader_should_decode_captured_packets() {
+    let v5 = ethernet_ipv4(&[0x8100], 2055, &v5::tests::FLOW_PACKET_1);
+    let ipfix = ethernet_ipv6(&[], 4739, &ipfix::tests::MESSAGE_1);
+    let dns = ethernet_ipv4(&[], 53, &[0; 12]);
+    let data = pcapng(
+      false,
+      Some(9),
+      link_type::ETHERNET,
+      &[
+        (1_544_476_581_000_000_000, &v5),
+        (1_544_476_582_000_000_000, &dns),
+        (1_544_476_583_000_000_000, &ipfix),
+      ],
+    );
+    let mut reader = FlowReader::new(&data[..]).unwrap().port(2055).port(4739);
+
+    let captured = reader.next_packet().unwrap().unwrap();
+    assert_eq!(
+      captured.timestamp,
+      UNIX_EPOCH + Duration::from_secs(1544476581)
+    );
+    assert_eq!(captured.exporter, EXPORTER_V4);
+    assert_eq!(captured.collector.port(), 2055);
+    match captured.packet().unwrap() {
+      Packet::V5(set) => assert_eq!(set.len(), 29),
+      packet => panic!("unexpected packet {:?}", packet),
+    }
+
+    let captured = reader.next_packet().unwrap().unwrap();
+    assert_eq!(
+      captured.timestamp,
+      UNIX_EPOCH + Duration::from_secs(1544476583)
+    );
+    assert_eq!(captured.exporter, EXPORTER_V6);
+    assert_eq!(
+      captured.packet().unwrap().protocol(),
+      crate::Protocol::Ipfix
+    );
+
+    assert_eq!(reader.next_packet().unwrap(), None);
+  }
+
+  #[test]
+  fn flow_reader_should_read_every_datagram_without_ports() {
+    let frames = [ethernet_ipv4(&[], 2055, &[1]), ethernet_ipv4(&[], 53, &[2])];
+    let data = pcap(
+      false,
+      false,
+      u32::from(link_type::ETHERNET),
+      &[(0, 0, &frames[0]), (0, 0, &[0; 10]), (0, 0, &frames[1])],
+    );
+    let mut reader = FlowReader::new(&data[..]).unwrap();
+
+    assert_eq!(reader.next_packet().unwrap().unwrap().data, &[1]);
+    assert_eq!(reader.next_packet().unwrap().unwrap().data, &[2]);
+    assert_eq!(reader.next_packet().unwrap(), None);
+  }
+
+  #[test]
+  fn flow_reader_should_open_files() {
+    let frame = ethernet_ipv4(&[], 2055, &v5::tests::FLOW_PACKET_1);
+    let path = std::env::temp_dir().join(format!("netflow-pcap-{}.pcap", std::process::id()));
+    std::fs::write(&path, pcap(true, false, 1, &[(1, 0, &frame)])).unwrap();
+
+    let mut reader = FlowReader::open(&path).unwrap();
+    let data = reader.next_packet().unwrap().unwrap().data.to_vec();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(data, &v5::tests::FLOW_PACKET_1[..]);
+  }
+}
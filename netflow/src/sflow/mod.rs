@@ -0,0 +1,63 @@
+//! sFlow version 5 datagrams, as sent by switches sampling their traffic
+//!
+//! A datagram carries flow samples, each a sampled packet described by flow
+//! records such as its leading bytes or decoded addresses, and counter
+//! samples of interface statistics. `raw::Datagram` walks them in place:
+//!
+//! ```
+//! use netflow::sflow::raw::{Datagram, FlowRecord, Sample};
+//!
+//! # let data = [0, 0, 0, 5, 0, 0, 0, 1, 192, 0, 2, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0];
+//! let datagram = Datagram::new(&data)?;
+//! for sample in datagram.samples() {
+//!   if let Sample::Flow(sample) = sample? {
+//!     for record in sample.records() {
+//!       if let FlowRecord::SampledIpv4(ip) = record? {
+//!         println!("{} -> {} 1:{}", ip.source_addr(), ip.destination_addr(), sample.sampling_rate());
+//!       }
+//!     }
+//!   }
+//! }
+//! # Ok::<(), netflow::Error>(())
+//! ```
+//!
+//! sFlow is not sniffed by `netflow::parse`: its version number is 32 bits
+//! long and exporters send it to a port of its own, 6343.
+
+pub mod raw;
+
+// Based on https://sflow.org/sflow_version_5.txt
+
+#[cfg(test)]
+pub(crate) mod tests {
+  /// A datagram from agent 192.0.2.10 holding a flow sample with a raw packet
+  /// header and an extended switch record, an expanded flow sample with a
+  /// sampled IPv4 record, and a counters sample with generic interface
+  /// counters
+  pub const DATAGRAM_1: [u8; 380] = [
+    0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0xc0, 0x00, 0x02, 0x0a, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x04, 0xd2, 0x00, 0x36, 0xee, 0x80, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01,
+    0x00, 0x00, 0x00, 0x88, 0x00, 0x00, 0x00, 0x64, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x04, 0x00,
+    0x00, 0x01, 0x90, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x05,
+    0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x48, 0x00, 0x00, 0x00, 0x01,
+    0x00, 0x00, 0x05, 0xee, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x36, 0x00, 0x11, 0x22, 0x33,
+    0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0x08, 0x00, 0x45, 0x00, 0x05, 0xdc, 0x00, 0x00,
+    0x40, 0x00, 0x40, 0x06, 0x00, 0x00, 0xc0, 0x00, 0x02, 0x64, 0xc6, 0x33, 0x64, 0xc8, 0x01, 0xbb,
+    0xc7, 0x38, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x50, 0x18, 0xff, 0xff, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xe9, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x64,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03,
+    0x00, 0x00, 0x00, 0x54, 0x00, 0x00, 0x00, 0x65, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07,
+    0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0xc8, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x01,
+    0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x05, 0xdc, 0x00, 0x00, 0x00, 0x11,
+    0xc6, 0x33, 0x64, 0x01, 0xcb, 0x00, 0x71, 0x05, 0x00, 0x00, 0x00, 0x35, 0x00, 0x00, 0x80, 0xe8,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x6c,
+    0x00, 0x00, 0x00, 0x32, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01,
+    0x00, 0x00, 0x00, 0x58, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x02,
+    0x54, 0x0b, 0xe4, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x1c,
+    0xbe, 0x99, 0x1a, 0x14, 0x00, 0x00, 0x03, 0xe8, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00, 0x01,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x3a, 0xde, 0x68, 0xb1, 0x00, 0x00, 0x07, 0xd0, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x02,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+  ];
+}
@@ -0,0 +1,199 @@
+use super::{check_len, SampleIterator};
+use crate::{Error, Protocol};
+use byteorder::{ByteOrder, NetworkEndian};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// An sFlow datagram helper struct
+#[derive(PartialEq)]
+pub struct Datagram<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for Datagram<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Datagram")
+      .field("agent_address", &self.agent_address())
+      .field("sub_agent_id", &self.sub_agent_id())
+      .field("sequence_number", &self.sequence_number())
+      .field("uptime", &self.uptime())
+      .field("samples", &self.samples().collect::<Vec<_>>())
+      .finish()
+  }
+}
+
+impl<'a> Datagram<'a> {
+  pub const VERSION: u32 = 5;
+  /// Address type of IPv4 agents
+  pub const ADDRESS_IPV4: u32 = 1;
+  /// Address type of IPv6 agents
+  pub const ADDRESS_IPV6: u32 = 2;
+
+  /// Parses the header of the datagram in `data`, whose samples are read
+  /// lazily by `samples`
+  ///
+  /// Versions that do not fit `Error::InvalidVersion` are reported as
+  /// `u16::MAX`.
+  pub fn new(data: &'a [u8]) -> Result<Datagram<'a>, Error> {
+    check_len(data, 0, 8)?;
+    let version = NetworkEndian::read_u32(&data[0..4]);
+    if version != Self::VERSION {
+      return Err(Error::InvalidVersion {
+        protocol: Protocol::Sflow,
+        version: version.min(u32::from(u16::MAX)) as u16,
+      });
+    }
+    let address_len = match NetworkEndian::read_u32(&data[4..8]) {
+      0 => 0,
+      Self::ADDRESS_IPV4 => 4,
+      Self::ADDRESS_IPV6 => 16,
+      address_type => {
+        return Err(Error::InvalidAddressType {
+          protocol: Protocol::Sflow,
+          offset: 4,
+          address_type,
+        })
+      }
+    };
+    check_len(data, 0, 24 + address_len)?;
+
+    Ok(Datagram { data })
+  }
+
+  /// sFlow version number
+  pub fn version(&self) -> u32 {
+    Self::VERSION
+  }
+
+  /// IP address of the agent, `None` if it declares an unknown address type
+  pub fn agent_address(&self) -> Option<IpAddr> {
+    let data = self.data;
+    match NetworkEndian::read_u32(&data[4..8]) {
+      Self::ADDRESS_IPV4 => Some(IpAddr::V4(Ipv4Addr::new(
+        data[8], data[9], data[10], data[11],
+      ))),
+      Self::ADDRESS_IPV6 => {
+        let mut octets = [0; 16];
+        octets.copy_from_slice(&data[8..24]);
+        Some(IpAddr::V6(Ipv6Addr::from(octets)))
+      }
+      _ => None,
+    }
+  }
+
+  /// Length of the header, which depends on the agent address type
+  pub fn header_len(&self) -> usize {
+    match NetworkEndian::read_u32(&self.data[4..8]) {
+      Self::ADDRESS_IPV4 => 28,
+      Self::ADDRESS_IPV6 => 40,
+      _ => 24,
+    }
+  }
+
+  /// Identifier of the sub-agent sending the datagram, for agents split into
+  /// several
+  pub fn sub_agent_id(&self) -> u32 {
+    let offset = self.header_len() - 16;
+    NetworkEndian::read_u32(&self.data[offset..offset + 4])
+  }
+
+  /// Sequence number of the datagram, incremented by each sub-agent
+  pub fn sequence_number(&self) -> u32 {
+    let offset = self.header_len() - 12;
+    NetworkEndian::read_u32(&self.data[offset..offset + 4])
+  }
+
+  /// Current time in milliseconds since the agent started
+  pub fn uptime(&self) -> u32 {
+    let offset = self.header_len() - 8;
+    NetworkEndian::read_u32(&self.data[offset..offset + 4])
+  }
+
+  /// Number of samples announced by the header
+  pub fn sample_count(&self) -> u32 {
+    let offset = self.header_len() - 4;
+    NetworkEndian::read_u32(&self.data[offset..offset + 4])
+  }
+
+  /// Samples following the header, up to the announced count or the first
+  /// invalid one
+  pub fn samples(&self) -> SampleIterator<'a> {
+    SampleIterator {
+      data: &self.data[self.header_len()..],
+      offset: self.header_len(),
+      remaining: self.sample_count(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::DATAGRAM_1;
+  use super::*;
+
+  #[test]
+  fn datagram_accessors_expose_fields() {
+    let datagram = Datagram::new(&DATAGRAM_1).unwrap();
+
+    assert_eq!(datagram.version(), 5);
+    assert_eq!(
+      datagram.agent_address(),
+      Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 10)))
+    );
+    assert_eq!(datagram.header_len(), 28);
+    assert_eq!(datagram.sub_agent_id(), 0);
+    assert_eq!(datagram.sequence_number(), 1234);
+    assert_eq!(datagram.uptime(), 3_600_000);
+    assert_eq!(datagram.sample_count(), 3);
+  }
+
+  #[test]
+  fn datagram_should_read_ipv6_agents() {
+    let mut data = vec![0, 0, 0, 5, 0, 0, 0, 2];
+    data.extend_from_slice(&[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+    data.extend_from_slice(&[0, 0, 0, 7, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 0]);
+    let datagram = Datagram::new(&data).unwrap();
+
+    assert_eq!(
+      datagram.agent_address(),
+      Some(IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)))
+    );
+    assert_eq!(datagram.sub_agent_id(), 7);
+    assert_eq!(datagram.sequence_number(), 1);
+    assert_eq!(datagram.uptime(), 2);
+    assert_eq!(datagram.samples().count(), 0);
+  }
+
+  #[test]
+  fn datagram_new_should_check_header() {
+    let mut data = DATAGRAM_1.to_vec();
+    data[3] = 4;
+    assert_eq!(
+      Datagram::new(&data),
+      Err(Error::InvalidVersion {
+        protocol: Protocol::Sflow,
+        version: 4,
+      })
+    );
+
+    let mut data = DATAGRAM_1.to_vec();
+    data[7] = 3;
+    assert_eq!(
+      Datagram::new(&data),
+      Err(Error::InvalidAddressType {
+        protocol: Protocol::Sflow,
+        offset: 4,
+        address_type: 3,
+      })
+    );
+
+    assert_eq!(
+      Datagram::new(&DATAGRAM_1[..20]),
+      Err(Error::NotEnoughData {
+        protocol: Protocol::Sflow,
+        offset: 0,
+        expected: 28,
+        actual: 20,
+      })
+    );
+  }
+}
@@ -0,0 +1,196 @@
+use super::{CounterRecord, Element, FlowRecord, Sample, ELEMENT_HEADER_LEN};
+use crate::Error;
+
+/// Splits the next element off `data`, found at `offset`, moving both past it
+fn next_element<'a>(
+  data: &mut &'a [u8],
+  offset: &mut usize,
+  remaining: &mut u32,
+) -> Option<Result<(Element<'a>, usize), Error>> {
+  if *remaining == 0 {
+    return None;
+  }
+
+  match Element::parse(data, *offset) {
+    Ok(element) => {
+      let body_offset = *offset + ELEMENT_HEADER_LEN;
+      *data = &data[element.len..];
+      *offset += element.len;
+      *remaining -= 1;
+      Some(Ok((element, body_offset)))
+    }
+    Err(error) => {
+      *data = &[];
+      *remaining = 0;
+      Some(Err(error))
+    }
+  }
+}
+
+/// Iterator over the samples of a datagram, stopping after the first invalid
+/// one
+pub struct SampleIterator<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+  pub(crate) remaining: u32,
+}
+
+impl<'a> Iterator for SampleIterator<'a> {
+  type Item = Result<Sample<'a>, Error>;
+
+  fn next(&mut self) -> Option<Result<Sample<'a>, Error>> {
+    let result = next_element(&mut self.data, &mut self.offset, &mut self.remaining)?
+      .and_then(|(element, offset)| Sample::parse(element, offset));
+    if result.is_err() {
+      self.remaining = 0;
+    }
+    Some(result)
+  }
+}
+
+/// Iterator over the records of a flow sample, stopping after the first
+/// invalid one
+pub struct FlowRecordIterator<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+  pub(crate) remaining: u32,
+}
+
+impl<'a> Iterator for FlowRecordIterator<'a> {
+  type Item = Result<FlowRecord<'a>, Error>;
+
+  fn next(&mut self) -> Option<Result<FlowRecord<'a>, Error>> {
+    let result = next_element(&mut self.data, &mut self.offset, &mut self.remaining)?.and_then(
+      |(element, offset)| FlowRecord::parse(element).map_err(|error| error.offset_by(offset)),
+    );
+    if result.is_err() {
+      self.remaining = 0;
+    }
+    Some(result)
+  }
+}
+
+/// Iterator over the records of a counters sample, stopping after the first
+/// invalid one
+pub struct CounterRecordIterator<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+  pub(crate) remaining: u32,
+}
+
+impl<'a> Iterator for CounterRecordIterator<'a> {
+  type Item = Result<CounterRecord<'a>, Error>;
+
+  fn next(&mut self) -> Option<Result<CounterRecord<'a>, Error>> {
+    let result = next_element(&mut self.data, &mut self.offset, &mut self.remaining)?.and_then(
+      |(element, offset)| CounterRecord::parse(element).map_err(|error| error.offset_by(offset)),
+    );
+    if result.is_err() {
+      self.remaining = 0;
+    }
+    Some(result)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::DATAGRAM_1;
+  use super::super::Datagram;
+  use super::*;
+  use crate::Protocol;
+  use std::net::{IpAddr, Ipv4Addr};
+
+  #[test]
+  fn samples_should_split_datagram() {
+    let datagram = Datagram::new(&DATAGRAM_1).unwrap();
+    let mut flows = Vec::new();
+    let mut counters = Vec::new();
+    for sample in datagram.samples() {
+      match sample.unwrap() {
+        Sample::Flow(sample) => {
+          for record in sample.records() {
+            flows.push(match record.unwrap() {
+              FlowRecord::RawPacketHeader(record) => record.header_length(),
+              FlowRecord::ExtendedSwitch(record) => record.destination_vlan(),
+              FlowRecord::SampledIpv4(record) => {
+                assert_eq!(
+                  record.destination_addr(),
+                  IpAddr::V4(Ipv4Addr::new(203, 0, 113, 5))
+                );
+                record.length()
+              }
+              record => panic!("unexpected record {:?}", record),
+            });
+          }
+        }
+        Sample::Counters(sample) => {
+          for record in sample.records() {
+            match record.unwrap() {
+              CounterRecord::Interface(record) => counters.push(record.if_index()),
+              record => panic!("unexpected record {:?}", record),
+            }
+          }
+        }
+        sample => panic!("unexpected sample {:?}", sample),
+      }
+    }
+
+    assert_eq!(flows, vec![54, 200, 1500]);
+    assert_eq!(counters, vec![3]);
+  }
+
+  #[test]
+  fn samples_should_stop_at_announced_count() {
+    let mut data = DATAGRAM_1.to_vec();
+    data[27] = 1;
+    let datagram = Datagram::new(&data).unwrap();
+
+    assert_eq!(datagram.samples().count(), 1);
+  }
+
+  #[test]
+  fn samples_should_stop_after_truncation() {
+    let datagram = Datagram::new(&DATAGRAM_1[..300]).unwrap();
+    let samples = datagram.samples().collect::<Vec<_>>();
+
+    assert_eq!(samples.len(), 3);
+    assert!(samples[1].is_ok());
+    assert_eq!(
+      samples[2],
+      Err(Error::NotEnoughData {
+        protocol: Protocol::Sflow,
+        offset: 264,
+        expected: 116,
+        actual: 36,
+      })
+    );
+  }
+
+  #[test]
+  fn record_errors_should_be_offset_within_datagram() {
+    let mut data = DATAGRAM_1.to_vec();
+    // Raw packet header longer than its record
+    data[91] = 80;
+    let datagram = Datagram::new(&data).unwrap();
+    let sample = match datagram.samples().next().unwrap().unwrap() {
+      Sample::Flow(sample) => sample,
+      sample => panic!("unexpected sample {:?}", sample),
+    };
+    let records = sample.records().collect::<Vec<_>>();
+
+    assert_eq!(
+      records,
+      vec![Err(Error::NotEnoughData {
+        protocol: Protocol::Sflow,
+        offset: 92,
+        expected: 80,
+        actual: 56,
+      })]
+    );
+  }
+
+  #[test]
+  fn datagram_implements_debug() {
+    println!("{:?}", Datagram::new(&DATAGRAM_1).unwrap());
+  }
+}
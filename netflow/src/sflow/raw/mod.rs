@@ -0,0 +1,81 @@
+use crate::{Error, Protocol};
+use byteorder::{ByteOrder, NetworkEndian};
+
+mod datagram;
+mod iterator;
+mod record;
+mod sample;
+
+pub use datagram::Datagram;
+pub use iterator::{CounterRecordIterator, FlowRecordIterator, SampleIterator};
+pub use record::{
+  CounterRecord, ExtendedSwitch, FlowRecord, InterfaceCounters, RawPacketHeader, SampledIpv4,
+  SampledIpv6,
+};
+pub use sample::{CountersSample, FlowSample, Sample};
+
+/// Length of the data format and length fields leading samples and records
+pub(crate) const ELEMENT_HEADER_LEN: usize = 8;
+
+/// A sample or record split off the data holding it
+pub(crate) struct Element<'a> {
+  /// Enterprise, 0 for the formats of the sFlow specification
+  pub(crate) enterprise: u32,
+  pub(crate) format: u32,
+  pub(crate) body: &'a [u8],
+  /// Bytes taken by the element, with its trailing padding
+  pub(crate) len: usize,
+}
+
+impl<'a> Element<'a> {
+  /// Splits the sample or record at the start of `data`, found at `offset`
+  pub(crate) fn parse(data: &'a [u8], offset: usize) -> Result<Element<'a>, Error> {
+    if data.len() < ELEMENT_HEADER_LEN {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::Sflow,
+        offset,
+        expected: ELEMENT_HEADER_LEN,
+        actual: data.len(),
+      });
+    }
+    let data_format = NetworkEndian::read_u32(&data[0..4]);
+    let length = NetworkEndian::read_u32(&data[4..8]) as usize;
+    let body = match data[ELEMENT_HEADER_LEN..].get(..length) {
+      Some(body) => body,
+      None => {
+        return Err(Error::NotEnoughData {
+          protocol: Protocol::Sflow,
+          offset,
+          expected: ELEMENT_HEADER_LEN.saturating_add(length),
+          actual: data.len(),
+        })
+      }
+    };
+
+    Ok(Element {
+      enterprise: data_format >> 12,
+      format: data_format & 0xfff,
+      body,
+      // Opaque data is padded to 4 bytes, which the last element may omit
+      len: (ELEMENT_HEADER_LEN + length.div_ceil(4) * 4).min(data.len()),
+    })
+  }
+}
+
+/// Checks that `data` holds the `len` bytes of a structure at `offset`
+pub(crate) fn check_len(data: &[u8], offset: usize, len: usize) -> Result<(), Error> {
+  if data.len() < len {
+    return Err(Error::NotEnoughData {
+      protocol: Protocol::Sflow,
+      offset,
+      expected: len,
+      actual: data.len(),
+    });
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  pub use super::super::tests::DATAGRAM_1;
+}
@@ -0,0 +1,556 @@
+use super::{check_len, Element};
+use crate::{Error, Protocol};
+use byteorder::{ByteOrder, NetworkEndian};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// A flow record of a flow sample
+#[derive(Debug, PartialEq)]
+pub enum FlowRecord<'a> {
+  /// Leading bytes of the sampled packet
+  RawPacketHeader(RawPacketHeader<'a>),
+  /// IPv4 header fields of the sampled packet
+  SampledIpv4(SampledIpv4<'a>),
+  /// IPv6 header fields of the sampled packet
+  SampledIpv6(SampledIpv6<'a>),
+  /// VLANs the sampled packet was switched between
+  ExtendedSwitch(ExtendedSwitch<'a>),
+  /// A record of a format this crate does not decode
+  Other {
+    enterprise: u32,
+    format: u32,
+    data: &'a [u8],
+  },
+}
+
+impl<'a> FlowRecord<'a> {
+  pub const RAW_PACKET_HEADER: u32 = 1;
+  pub const SAMPLED_IPV4: u32 = 3;
+  pub const SAMPLED_IPV6: u32 = 4;
+  pub const EXTENDED_SWITCH: u32 = 1001;
+
+  /// Decodes a record split off its sample
+  pub(crate) fn parse(element: Element<'a>) -> Result<FlowRecord<'a>, Error> {
+    let data = element.body;
+    match (element.enterprise, element.format) {
+      (0, Self::RAW_PACKET_HEADER) => RawPacketHeader::new(data).map(FlowRecord::RawPacketHeader),
+      (0, Self::SAMPLED_IPV4) => SampledIpv4::new(data).map(FlowRecord::SampledIpv4),
+      (0, Self::SAMPLED_IPV6) => SampledIpv6::new(data).map(FlowRecord::SampledIpv6),
+      (0, Self::EXTENDED_SWITCH) => ExtendedSwitch::new(data).map(FlowRecord::ExtendedSwitch),
+      (enterprise, format) => Ok(FlowRecord::Other {
+        enterprise,
+        format,
+        data,
+      }),
+    }
+  }
+}
+
+/// A counter record of a counters sample
+#[derive(Debug, PartialEq)]
+pub enum CounterRecord<'a> {
+  /// Interface statistics common to every interface type
+  Interface(InterfaceCounters<'a>),
+  /// A record of a format this crate does not decode
+  Other {
+    enterprise: u32,
+    format: u32,
+    data: &'a [u8],
+  },
+}
+
+impl<'a> CounterRecord<'a> {
+  pub const GENERIC_INTERFACE: u32 = 1;
+
+  /// Decodes a record split off its sample
+  pub(crate) fn parse(element: Element<'a>) -> Result<CounterRecord<'a>, Error> {
+    let data = element.body;
+    match (element.enterprise, element.format) {
+      (0, Self::GENERIC_INTERFACE) => InterfaceCounters::new(data).map(CounterRecord::Interface),
+      (enterprise, format) => Ok(CounterRecord::Other {
+        enterprise,
+        format,
+        data,
+      }),
+    }
+  }
+}
+
+fn ipv6(data: &[u8]) -> IpAddr {
+  let mut octets = [0; 16];
+  octets.copy_from_slice(&data[..16]);
+  IpAddr::V6(Ipv6Addr::from(octets))
+}
+
+/// An sFlow raw packet header record helper struct
+#[derive(PartialEq)]
+pub struct RawPacketHeader<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for RawPacketHeader<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "RawPacketHeader {{ header_protocol: {}, frame_length: {}, stripped: {}, header_length: {} }}",
+        self.header_protocol(), self.frame_length(), self.stripped(), self.header_length())
+  }
+}
+
+impl<'a> RawPacketHeader<'a> {
+  /// Length of the fields preceding the header bytes
+  pub const LEN: usize = 16;
+  /// Header protocol of Ethernet frames
+  pub const ETHERNET: u32 = 1;
+  /// Header protocol of IPv4 packets
+  pub const IPV4: u32 = 11;
+  /// Header protocol of IPv6 packets
+  pub const IPV6: u32 = 12;
+
+  pub fn new(data: &'a [u8]) -> Result<RawPacketHeader<'a>, Error> {
+    check_len(data, 0, Self::LEN)?;
+    let header_length = NetworkEndian::read_u32(&data[12..16]) as usize;
+    if data.len() - Self::LEN < header_length {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::Sflow,
+        offset: Self::LEN,
+        expected: header_length,
+        actual: data.len() - Self::LEN,
+      });
+    }
+
+    Ok(RawPacketHeader { data })
+  }
+
+  /// Protocol of the header, such as `ETHERNET`
+  pub fn header_protocol(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[0..4])
+  }
+
+  /// Length of the packet as it was received, before any stripping
+  pub fn frame_length(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[4..8])
+  }
+
+  /// Bytes removed from the end of the packet before sampling, such as the
+  /// Ethernet frame check sequence
+  pub fn stripped(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[8..12])
+  }
+
+  /// Number of header bytes sampled
+  pub fn header_length(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[12..16])
+  }
+
+  /// Leading bytes of the packet, starting with a header of `header_protocol`
+  pub fn header(&self) -> &'a [u8] {
+    &self.data[Self::LEN..Self::LEN + self.header_length() as usize]
+  }
+}
+
+/// An sFlow sampled IPv4 record helper struct
+#[derive(PartialEq)]
+pub struct SampledIpv4<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for SampledIpv4<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "SampledIpv4 {{ length: {}, protocol: {}, source_addr: {}, destination_addr: {}, source_port: {}, destination_port: {}, tcp_flags: {}, type_of_service: {} }}",
+        self.length(), self.protocol(), self.source_addr(), self.destination_addr(), self.source_port(), self.destination_port(), self.tcp_flags(), self.type_of_service())
+  }
+}
+
+impl<'a> SampledIpv4<'a> {
+  pub const LEN: usize = 32;
+
+  pub fn new(data: &'a [u8]) -> Result<SampledIpv4<'a>, Error> {
+    check_len(data, 0, Self::LEN)?;
+    Ok(SampledIpv4 { data })
+  }
+
+  /// Length of the IP packet, excluding lower layer encapsulations
+  pub fn length(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[0..4])
+  }
+
+  /// IP protocol type (for example, TCP = 6, UDP = 17, ...)
+  pub fn protocol(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[4..8])
+  }
+
+  /// Source IP address
+  pub fn source_addr(&self) -> IpAddr {
+    IpAddr::V4(Ipv4Addr::new(
+      self.data[8],
+      self.data[9],
+      self.data[10],
+      self.data[11],
+    ))
+  }
+
+  /// Destination IP address
+  pub fn destination_addr(&self) -> IpAddr {
+    IpAddr::V4(Ipv4Addr::new(
+      self.data[12],
+      self.data[13],
+      self.data[14],
+      self.data[15],
+    ))
+  }
+
+  /// TCP or UDP source port number or equivalent
+  pub fn source_port(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[16..20])
+  }
+
+  /// TCP or UDP destination port number or equivalent
+  pub fn destination_port(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[20..24])
+  }
+
+  /// TCP flags of the packet
+  pub fn tcp_flags(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[24..28])
+  }
+
+  /// IP type of service (ToS)
+  pub fn type_of_service(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[28..32])
+  }
+}
+
+/// An sFlow sampled IPv6 record helper struct
+#[derive(PartialEq)]
+pub struct SampledIpv6<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for SampledIpv6<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "SampledIpv6 {{ length: {}, protocol: {}, source_addr: {}, destination_addr: {}, source_port: {}, destination_port: {}, tcp_flags: {}, priority: {} }}",
+        self.length(), self.protocol(), self.source_addr(), self.destination_addr(), self.source_port(), self.destination_port(), self.tcp_flags(), self.priority())
+  }
+}
+
+impl<'a> SampledIpv6<'a> {
+  pub const LEN: usize = 56;
+
+  pub fn new(data: &'a [u8]) -> Result<SampledIpv6<'a>, Error> {
+    check_len(data, 0, Self::LEN)?;
+    Ok(SampledIpv6 { data })
+  }
+
+  /// Length of the IP packet, excluding lower layer encapsulations
+  pub fn length(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[0..4])
+  }
+
+  /// IP next header (for example, TCP = 6, UDP = 17, ...)
+  pub fn protocol(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[4..8])
+  }
+
+  /// Source IP address
+  pub fn source_addr(&self) -> IpAddr {
+    ipv6(&self.data[8..24])
+  }
+
+  /// Destination IP address
+  pub fn destination_addr(&self) -> IpAddr {
+    ipv6(&self.data[24..40])
+  }
+
+  /// TCP or UDP source port number or equivalent
+  pub fn source_port(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[40..44])
+  }
+
+  /// TCP or UDP destination port number or equivalent
+  pub fn destination_port(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[44..48])
+  }
+
+  /// TCP flags of the packet
+  pub fn tcp_flags(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[48..52])
+  }
+
+  /// IP priority
+  pub fn priority(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[52..56])
+  }
+}
+
+/// An sFlow extended switch record helper struct
+#[derive(PartialEq)]
+pub struct ExtendedSwitch<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for ExtendedSwitch<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "ExtendedSwitch {{ source_vlan: {}, source_priority: {}, destination_vlan: {}, destination_priority: {} }}",
+        self.source_vlan(), self.source_priority(), self.destination_vlan(), self.destination_priority())
+  }
+}
+
+impl<'a> ExtendedSwitch<'a> {
+  pub const LEN: usize = 16;
+
+  pub fn new(data: &'a [u8]) -> Result<ExtendedSwitch<'a>, Error> {
+    check_len(data, 0, Self::LEN)?;
+    Ok(ExtendedSwitch { data })
+  }
+
+  /// 802.1Q VLAN of the incoming frame
+  pub fn source_vlan(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[0..4])
+  }
+
+  /// 802.1p priority of the incoming frame
+  pub fn source_priority(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[4..8])
+  }
+
+  /// 802.1Q VLAN of the outgoing frame
+  pub fn destination_vlan(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[8..12])
+  }
+
+  /// 802.1p priority of the outgoing frame
+  pub fn destination_priority(&self) -> u32 {
+    NetworkEndian::read_u32(&self.data[12..16])
+  }
+}
+
+/// An sFlow generic interface counters record helper struct, the counters of
+/// the IF-MIB
+#[derive(PartialEq)]
+pub struct InterfaceCounters<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for InterfaceCounters<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "InterfaceCounters {{ if_index: {}, if_type: {}, if_speed: {}, if_direction: {}, if_status: {}, in_octets: {}, in_unicast_packets: {}, in_multicast_packets: {}, in_broadcast_packets: {}, in_discards: {}, in_errors: {}, in_unknown_protocols: {}, out_octets: {}, out_unicast_packets: {}, out_multicast_packets: {}, out_broadcast_packets: {}, out_discards: {}, out_errors: {}, promiscuous_mode: {} }}",
+        self.if_index(), self.if_type(), self.if_speed(), self.if_direction(), self.if_status(), self.in_octets(), self.in_unicast_packets(), self.in_multicast_packets(), self.in_broadcast_packets(), self.in_discards(), self.in_errors(), self.in_unknown_protocols(), self.out_octets(), self.out_unicast_packets(), self.out_multicast_packets(), self.out_broadcast_packets(), self.out_discards(), self.out_errors(), self.promiscuous_mode())
+  }
+}
+
+impl<'a> InterfaceCounters<'a> {
+  pub const LEN: usize = 88;
+
+  pub fn new(data: &'a [u8]) -> Result<InterfaceCounters<'a>, Error> {
+    check_len(data, 0, Self::LEN)?;
+    Ok(InterfaceCounters { data })
+  }
+
+  fn u32(&self, offset: usize) -> u32 {
+    NetworkEndian::read_u32(&self.data[offset..offset + 4])
+  }
+
+  fn u64(&self, offset: usize) -> u64 {
+    NetworkEndian::read_u64(&self.data[offset..offset + 8])
+  }
+
+  /// SNMP index of the interface
+  pub fn if_index(&self) -> u32 {
+    self.u32(0)
+  }
+
+  /// IANA interface type (for example, Ethernet = 6)
+  pub fn if_type(&self) -> u32 {
+    self.u32(4)
+  }
+
+  /// Speed of the interface in bits per second
+  pub fn if_speed(&self) -> u64 {
+    self.u64(8)
+  }
+
+  /// 0 if unknown, 1 for full duplex, 2 for half duplex, 3 for in and 4 for
+  /// out only
+  pub fn if_direction(&self) -> u32 {
+    self.u32(16)
+  }
+
+  /// Administrative status in bit 0 and operational status in bit 1, each
+  /// set when up
+  pub fn if_status(&self) -> u32 {
+    self.u32(20)
+  }
+
+  /// Bytes received
+  pub fn in_octets(&self) -> u64 {
+    self.u64(24)
+  }
+
+  /// Unicast packets received
+  pub fn in_unicast_packets(&self) -> u32 {
+    self.u32(32)
+  }
+
+  /// Multicast packets received
+  pub fn in_multicast_packets(&self) -> u32 {
+    self.u32(36)
+  }
+
+  /// Broadcast packets received
+  pub fn in_broadcast_packets(&self) -> u32 {
+    self.u32(40)
+  }
+
+  /// Packets received but discarded, without error
+  pub fn in_discards(&self) -> u32 {
+    self.u32(44)
+  }
+
+  /// Packets received with errors
+  pub fn in_errors(&self) -> u32 {
+    self.u32(48)
+  }
+
+  /// Packets received for unknown or unsupported protocols
+  pub fn in_unknown_protocols(&self) -> u32 {
+    self.u32(52)
+  }
+
+  /// Bytes sent
+  pub fn out_octets(&self) -> u64 {
+    self.u64(56)
+  }
+
+  /// Unicast packets sent
+  pub fn out_unicast_packets(&self) -> u32 {
+    self.u32(64)
+  }
+
+  /// Multicast packets sent
+  pub fn out_multicast_packets(&self) -> u32 {
+    self.u32(68)
+  }
+
+  /// Broadcast packets sent
+  pub fn out_broadcast_packets(&self) -> u32 {
+    self.u32(72)
+  }
+
+  /// Packets not sent, without error
+  pub fn out_discards(&self) -> u32 {
+    self.u32(76)
+  }
+
+  /// Packets not sent because of errors
+  pub fn out_errors(&self) -> u32 {
+    self.u32(80)
+  }
+
+  /// 1 if the interface is in promiscuous mode, 2 if not, 0 if unknown
+  pub fn promiscuous_mode(&self) -> u32 {
+    self.u32(84)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::DATAGRAM_1;
+  use super::*;
+
+  #[test]
+  fn raw_packet_header_accessors_expose_fields() {
+    let record = RawPacketHeader::new(&DATAGRAM_1[76..148]).unwrap();
+
+    assert_eq!(record.header_protocol(), RawPacketHeader::ETHERNET);
+    assert_eq!(record.frame_length(), 1518);
+    assert_eq!(record.stripped(), 4);
+    assert_eq!(record.header_length(), 54);
+    assert_eq!(record.header().len(), 54);
+    // Ethertype of the sampled frame
+    assert_eq!(&record.header()[12..14], &[0x08, 0x00]);
+  }
+
+  #[test]
+  fn raw_packet_header_should_fail_with_short_header() {
+    assert_eq!(
+      RawPacketHeader::new(&DATAGRAM_1[76..120]),
+      Err(Error::NotEnoughData {
+        protocol: Protocol::Sflow,
+        offset: 16,
+        expected: 54,
+        actual: 28,
+      })
+    );
+  }
+
+  #[test]
+  fn sampled_ipv4_accessors_expose_fields() {
+    let record = SampledIpv4::new(&DATAGRAM_1[232..264]).unwrap();
+
+    assert_eq!(record.length(), 1500);
+    assert_eq!(record.protocol(), 17);
+    assert_eq!(
+      record.source_addr(),
+      IpAddr::V4(Ipv4Addr::new(198, 51, 100, 1))
+    );
+    assert_eq!(
+      record.destination_addr(),
+      IpAddr::V4(Ipv4Addr::new(203, 0, 113, 5))
+    );
+    assert_eq!(record.source_port(), 53);
+    assert_eq!(record.destination_port(), 33000);
+    assert_eq!(record.tcp_flags(), 0);
+    assert_eq!(record.type_of_service(), 0);
+  }
+
+  #[test]
+  fn sampled_ipv6_accessors_expose_fields() {
+    let mut data = [0; SampledIpv6::LEN];
+    data[7] = 6;
+    data[8..10].copy_from_slice(&[0x20, 0x01]);
+    data[24..26].copy_from_slice(&[0xfe, 0x80]);
+    data[43] = 80;
+    data[51] = 0x12;
+    let record = SampledIpv6::new(&data).unwrap();
+
+    assert_eq!(record.protocol(), 6);
+    assert_eq!(
+      record.source_addr(),
+      IpAddr::V6(Ipv6Addr::new(0x2001, 0, 0, 0, 0, 0, 0, 0))
+    );
+    assert_eq!(
+      record.destination_addr(),
+      IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0))
+    );
+    assert_eq!(record.source_port(), 80);
+    assert_eq!(record.tcp_flags(), 0x12);
+  }
+
+  #[test]
+  fn extended_switch_accessors_expose_fields() {
+    let record = ExtendedSwitch::new(&DATAGRAM_1[156..172]).unwrap();
+
+    assert_eq!(record.source_vlan(), 100);
+    assert_eq!(record.source_priority(), 0);
+    assert_eq!(record.destination_vlan(), 200);
+    assert_eq!(record.destination_priority(), 0);
+  }
+
+  #[test]
+  fn interface_counters_accessors_expose_fields() {
+    let record = InterfaceCounters::new(&DATAGRAM_1[292..380]).unwrap();
+
+    assert_eq!(record.if_index(), 3);
+    assert_eq!(record.if_type(), 6);
+    assert_eq!(record.if_speed(), 10_000_000_000);
+    assert_eq!(record.if_direction(), 1);
+    assert_eq!(record.if_status(), 3);
+    assert_eq!(record.in_octets(), 123_456_789_012);
+    assert_eq!(record.in_unicast_packets(), 1000);
+    assert_eq!(record.in_multicast_packets(), 10);
+    assert_eq!(record.in_broadcast_packets(), 1);
+    assert_eq!(record.out_octets(), 987_654_321);
+    assert_eq!(record.out_unicast_packets(), 2000);
+    assert_eq!(record.out_multicast_packets(), 20);
+    assert_eq!(record.out_broadcast_packets(), 2);
+    assert_eq!(record.promiscuous_mode(), 0);
+  }
+}
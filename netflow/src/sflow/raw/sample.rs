@@ -0,0 +1,390 @@
+use super::{check_len, CounterRecordIterator, Element, FlowRecordIterator};
+use crate::Error;
+use byteorder::{ByteOrder, NetworkEndian};
+
+/// A sample of a datagram
+#[derive(Debug, PartialEq)]
+pub enum Sample<'a> {
+  /// A flow sample, compact or expanded
+  Flow(FlowSample<'a>),
+  /// A counters sample, compact or expanded
+  Counters(CountersSample<'a>),
+  /// A sample of a format this crate does not decode
+  Other {
+    enterprise: u32,
+    format: u32,
+    data: &'a [u8],
+  },
+}
+
+impl<'a> Sample<'a> {
+  pub const FLOW_SAMPLE: u32 = 1;
+  pub const COUNTERS_SAMPLE: u32 = 2;
+  pub const EXPANDED_FLOW_SAMPLE: u32 = 3;
+  pub const EXPANDED_COUNTERS_SAMPLE: u32 = 4;
+
+  /// Decodes a sample split off its datagram, whose body is at `offset`
+  pub(crate) fn parse(element: Element<'a>, offset: usize) -> Result<Sample<'a>, Error> {
+    let data = element.body;
+    match (element.enterprise, element.format) {
+      (0, Self::FLOW_SAMPLE) => FlowSample::parse(data, offset, false).map(Sample::Flow),
+      (0, Self::EXPANDED_FLOW_SAMPLE) => FlowSample::parse(data, offset, true).map(Sample::Flow),
+      (0, Self::COUNTERS_SAMPLE) => {
+        CountersSample::parse(data, offset, false).map(Sample::Counters)
+      }
+      (0, Self::EXPANDED_COUNTERS_SAMPLE) => {
+        CountersSample::parse(data, offset, true).map(Sample::Counters)
+      }
+      (enterprise, format) => Ok(Sample::Other {
+        enterprise,
+        format,
+        data,
+      }),
+    }
+  }
+}
+
+/// Reads a data source or interface split into a type of `type_bits` and
+/// an index
+fn split(value: u32, type_bits: u32) -> (u32, u32) {
+  (value >> (32 - type_bits), value & (u32::MAX >> type_bits))
+}
+
+/// An sFlow flow sample helper struct, for both the compact and the expanded
+/// formats
+///
+/// The compact format packs the type and index of the data source and of the
+/// interfaces into single words, the expanded one, used by switches with
+/// more interfaces than these words can number, gives each its own.
+#[derive(PartialEq)]
+pub struct FlowSample<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+  pub(crate) expanded: bool,
+}
+
+impl<'a> std::fmt::Debug for FlowSample<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "FlowSample {{ expanded: {}, sequence_number: {}, source_id_type: {}, source_id_index: {}, sampling_rate: {}, sample_pool: {}, drops: {}, input: {}, output: {}, records: {:?} }}",
+        self.expanded, self.sequence_number(), self.source_id_type(), self.source_id_index(), self.sampling_rate(), self.sample_pool(), self.drops(), self.input(), self.output(), self.records().collect::<Vec<_>>())
+  }
+}
+
+impl<'a> FlowSample<'a> {
+  pub const LEN: usize = 32;
+  pub const EXPANDED_LEN: usize = 44;
+
+  /// Parses the sample body in `data`, found at `offset` in its datagram
+  pub(crate) fn parse(
+    data: &'a [u8],
+    offset: usize,
+    expanded: bool,
+  ) -> Result<FlowSample<'a>, Error> {
+    let len = if expanded {
+      Self::EXPANDED_LEN
+    } else {
+      Self::LEN
+    };
+    check_len(data, offset, len)?;
+    Ok(FlowSample {
+      data,
+      offset,
+      expanded,
+    })
+  }
+
+  fn word(&self, index: usize) -> u32 {
+    NetworkEndian::read_u32(&self.data[index * 4..index * 4 + 4])
+  }
+
+  /// Whether the sample is an expanded flow sample
+  pub fn is_expanded(&self) -> bool {
+    self.expanded
+  }
+
+  /// Sequence number of the samples of the data source
+  pub fn sequence_number(&self) -> u32 {
+    self.word(0)
+  }
+
+  /// Type of the data source: 0 for an interface, 1 for a VLAN...
+  pub fn source_id_type(&self) -> u32 {
+    match self.expanded {
+      false => split(self.word(1), 8).0,
+      true => self.word(1),
+    }
+  }
+
+  /// Index of the data source, such as the SNMP index of its interface
+  pub fn source_id_index(&self) -> u32 {
+    match self.expanded {
+      false => split(self.word(1), 8).1,
+      true => self.word(2),
+    }
+  }
+
+  /// One packet in `sampling_rate` is sampled
+  pub fn sampling_rate(&self) -> u32 {
+    self.word(if self.expanded { 3 } else { 2 })
+  }
+
+  /// Packets that could have been sampled, whether they were or not
+  pub fn sample_pool(&self) -> u32 {
+    self.word(if self.expanded { 4 } else { 3 })
+  }
+
+  /// Samples dropped for lack of resources
+  pub fn drops(&self) -> u32 {
+    self.word(if self.expanded { 5 } else { 4 })
+  }
+
+  /// Format of `input`: 0 for an interface index
+  pub fn input_format(&self) -> u32 {
+    match self.expanded {
+      false => split(self.word(5), 2).0,
+      true => self.word(6),
+    }
+  }
+
+  /// SNMP index of input interface, 0 if unknown
+  pub fn input(&self) -> u32 {
+    match self.expanded {
+      false => split(self.word(5), 2).1,
+      true => self.word(7),
+    }
+  }
+
+  /// Format of `output`: 0 for an interface index, 1 for a discard reason,
+  /// 2 for a number of interfaces the packet was flooded to
+  pub fn output_format(&self) -> u32 {
+    match self.expanded {
+      false => split(self.word(6), 2).0,
+      true => self.word(8),
+    }
+  }
+
+  /// SNMP index of output interface, 0 if unknown
+  pub fn output(&self) -> u32 {
+    match self.expanded {
+      false => split(self.word(6), 2).1,
+      true => self.word(9),
+    }
+  }
+
+  /// Number of records announced by the sample
+  pub fn record_count(&self) -> u32 {
+    self.word(if self.expanded { 10 } else { 7 })
+  }
+
+  /// Flow records describing the sampled packet, up to the announced count
+  /// or the first invalid one
+  pub fn records(&self) -> FlowRecordIterator<'a> {
+    let len = if self.expanded {
+      Self::EXPANDED_LEN
+    } else {
+      Self::LEN
+    };
+    FlowRecordIterator {
+      data: &self.data[len..],
+      offset: self.offset + len,
+      remaining: self.record_count(),
+    }
+  }
+}
+
+/// An sFlow counters sample helper struct, for both the compact and the
+/// expanded formats
+#[derive(PartialEq)]
+pub struct CountersSample<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+  pub(crate) expanded: bool,
+}
+
+impl<'a> std::fmt::Debug for CountersSample<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "CountersSample {{ expanded: {}, sequence_number: {}, source_id_type: {}, source_id_index: {}, records: {:?} }}",
+        self.expanded, self.sequence_number(), self.source_id_type(), self.source_id_index(), self.records().collect::<Vec<_>>())
+  }
+}
+
+impl<'a> CountersSample<'a> {
+  pub const LEN: usize = 12;
+  pub const EXPANDED_LEN: usize = 16;
+
+  /// Parses the sample body in `data`, found at `offset` in its datagram
+  pub(crate) fn parse(
+    data: &'a [u8],
+    offset: usize,
+    expanded: bool,
+  ) -> Result<CountersSample<'a>, Error> {
+    let len = if expanded {
+      Self::EXPANDED_LEN
+    } else {
+      Self::LEN
+    };
+    check_len(data, offset, len)?;
+    Ok(CountersSample {
+      data,
+      offset,
+      expanded,
+    })
+  }
+
+  fn word(&self, index: usize) -> u32 {
+    NetworkEndian::read_u32(&self.data[index * 4..index * 4 + 4])
+  }
+
+  /// Whether the sample is an expanded counters sample
+  pub fn is_expanded(&self) -> bool {
+    self.expanded
+  }
+
+  /// Sequence number of the samples of the data source
+  pub fn sequence_number(&self) -> u32 {
+    self.word(0)
+  }
+
+  /// Type of the data source: 0 for an interface, 1 for a VLAN...
+  pub fn source_id_type(&self) -> u32 {
+    match self.expanded {
+      false => split(self.word(1), 8).0,
+      true => self.word(1),
+    }
+  }
+
+  /// Index of the data source, such as the SNMP index of its interface
+  pub fn source_id_index(&self) -> u32 {
+    match self.expanded {
+      false => split(self.word(1), 8).1,
+      true => self.word(2),
+    }
+  }
+
+  /// Number of records announced by the sample
+  pub fn record_count(&self) -> u32 {
+    self.word(if self.expanded { 3 } else { 2 })
+  }
+
+  /// Counter records of the data source, up to the announced count or the
+  /// first invalid one
+  pub fn records(&self) -> CounterRecordIterator<'a> {
+    let len = if self.expanded {
+      Self::EXPANDED_LEN
+    } else {
+      Self::LEN
+    };
+    CounterRecordIterator {
+      data: &self.data[len..],
+      offset: self.offset + len,
+      remaining: self.record_count(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::DATAGRAM_1;
+  use super::super::{Datagram, ELEMENT_HEADER_LEN};
+  use super::*;
+  use crate::Protocol;
+
+  fn samples() -> Vec<Sample<'static>> {
+    let datagram = Datagram::new(&DATAGRAM_1).unwrap();
+    datagram.samples().collect::<Result<_, _>>().unwrap()
+  }
+
+  #[test]
+  fn flow_sample_accessors_expose_fields() {
+    match &samples()[0] {
+      Sample::Flow(sample) => {
+        assert_eq!(sample.offset, 36);
+        assert!(!sample.is_expanded());
+        assert_eq!(sample.sequence_number(), 100);
+        assert_eq!(sample.source_id_type(), 0);
+        assert_eq!(sample.source_id_index(), 3);
+        assert_eq!(sample.sampling_rate(), 1024);
+        assert_eq!(sample.sample_pool(), 102_400);
+        assert_eq!(sample.drops(), 0);
+        assert_eq!((sample.input_format(), sample.input()), (0, 3));
+        assert_eq!((sample.output_format(), sample.output()), (0, 5));
+        assert_eq!(sample.record_count(), 2);
+      }
+      sample => panic!("unexpected sample {:?}", sample),
+    }
+  }
+
+  #[test]
+  fn expanded_flow_sample_accessors_expose_fields() {
+    match &samples()[1] {
+      Sample::Flow(sample) => {
+        assert!(sample.is_expanded());
+        assert_eq!(sample.sequence_number(), 101);
+        assert_eq!(sample.source_id_type(), 0);
+        assert_eq!(sample.source_id_index(), 7);
+        assert_eq!(sample.sampling_rate(), 512);
+        assert_eq!(sample.sample_pool(), 51_200);
+        assert_eq!(sample.drops(), 2);
+        assert_eq!((sample.input_format(), sample.input()), (0, 7));
+        assert_eq!((sample.output_format(), sample.output()), (0, 9));
+        assert_eq!(sample.record_count(), 1);
+      }
+      sample => panic!("unexpected sample {:?}", sample),
+    }
+  }
+
+  #[test]
+  fn compact_fields_should_be_split() {
+    // Source 0x01_00_00_2a: VLAN 42; output 0x80_00_00_05: flooded to 5 ports
+    let mut data = [0; FlowSample::LEN];
+    data[4..8].copy_from_slice(&[0x01, 0x00, 0x00, 0x2a]);
+    data[24..28].copy_from_slice(&[0x80, 0x00, 0x00, 0x05]);
+    let sample = FlowSample::parse(&data, 0, false).unwrap();
+
+    assert_eq!((sample.source_id_type(), sample.source_id_index()), (1, 42));
+    assert_eq!((sample.output_format(), sample.output()), (2, 5));
+  }
+
+  #[test]
+  fn counters_sample_accessors_expose_fields() {
+    match &samples()[2] {
+      Sample::Counters(sample) => {
+        assert!(!sample.is_expanded());
+        assert_eq!(sample.sequence_number(), 50);
+        assert_eq!(sample.source_id_index(), 3);
+        assert_eq!(sample.record_count(), 1);
+        assert_eq!(sample.records().count(), 1);
+      }
+      sample => panic!("unexpected sample {:?}", sample),
+    }
+  }
+
+  #[test]
+  fn flow_sample_should_fail_with_not_enough_data() {
+    assert_eq!(
+      FlowSample::parse(&[0; 40], 36, true),
+      Err(Error::NotEnoughData {
+        protocol: Protocol::Sflow,
+        offset: 36,
+        expected: FlowSample::EXPANDED_LEN,
+        actual: 40,
+      })
+    );
+  }
+
+  #[test]
+  fn unknown_samples_should_be_kept() {
+    let data = [0, 0, 0x10, 0x05, 0, 0, 0, 4, 1, 2, 3, 4];
+    let element = Element::parse(&data, 0).unwrap();
+
+    assert_eq!(element.len, ELEMENT_HEADER_LEN + 4);
+    assert_eq!(
+      Sample::parse(element, ELEMENT_HEADER_LEN),
+      Ok(Sample::Other {
+        enterprise: 1,
+        format: 5,
+        data: &[1, 2, 3, 4],
+      })
+    );
+  }
+}
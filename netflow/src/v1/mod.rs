@@ -0,0 +1,25 @@
+pub mod raw;
+
+// Based on https://www.cisco.com/c/en/us/td/docs/net_mgmt/netflow_collection_engine/3-6/user/guide/format.html#wp1003394
+
+#[cfg(test)]
+pub(crate) mod tests {
+  pub const FLOW_PACKET_1: [u8; 160] = [
+    0x00, 0x01, 0x00, 0x03, 0x0a, 0xf3, 0x7d, 0x2b, 0x5c, 0x0e, 0xd7, 0xa5, 0x07, 0x27, 0x0e, 0x00,
+    0x0a, 0x01, 0x04, 0x15, 0xc0, 0xa8, 0x1e, 0x07, 0x0a, 0x01, 0x00, 0x01, 0x00, 0x02, 0x00, 0x05,
+    0x00, 0x00, 0x00, 0x0c, 0x00, 0x00, 0x11, 0xa8, 0x0a, 0xf3, 0x03, 0x23, 0x0a, 0xf3, 0x76, 0x0f,
+    0xc8, 0x22, 0x00, 0x50, 0x00, 0x00, 0x06, 0x00, 0x1b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0xc0, 0xa8, 0x1e, 0x07, 0x0a, 0x01, 0x04, 0x15, 0x0a, 0x01, 0x04, 0x01, 0x00, 0x05, 0x00, 0x02,
+    0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x33, 0x38, 0x0a, 0xf3, 0x03, 0x4b, 0x0a, 0xf3, 0x76, 0x0a,
+    0x00, 0x50, 0xc8, 0x22, 0x00, 0x00, 0x06, 0x00, 0x1b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x0a, 0x01, 0x04, 0x1e, 0x08, 0x08, 0x08, 0x08, 0x0a, 0x01, 0x00, 0x01, 0x00, 0x02, 0x00, 0x01,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x4c, 0x0a, 0xf3, 0x7b, 0x37, 0x0a, 0xf3, 0x7b, 0x37,
+    0xcf, 0x09, 0x00, 0x35, 0x00, 0x00, 0x11, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+  ];
+  pub fn get_flow_packet_header() -> &'static [u8] {
+    &FLOW_PACKET_1[0..16]
+  }
+  pub fn get_flow_packet_records() -> &'static [u8] {
+    &FLOW_PACKET_1[16..]
+  }
+}
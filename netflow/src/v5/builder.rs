@@ -0,0 +1,325 @@
+use super::raw::{FlowSet, Header, Record};
+use super::{FlowRecord, PacketHeader};
+use crate::{Error, Protocol};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Builds Netflow v5 packets out of owned records, as an export device would
+///
+/// The builder keeps the state an exporter carries from one packet to the
+/// next: the time it booted, from which `sys_uptime_msecs` is derived, and the
+/// sequence counter of total flows sent.
+#[derive(Debug, Clone)]
+pub struct PacketBuilder {
+  boot_time: SystemTime,
+  sequence_number: u32,
+  engine_type: u8,
+  engine_id: u8,
+  sampling: u16,
+  records: Vec<FlowRecord>,
+}
+
+impl PacketBuilder {
+  /// Creates a builder for an export device that booted at `boot_time`
+  pub fn new(boot_time: SystemTime) -> PacketBuilder {
+    PacketBuilder {
+      boot_time,
+      sequence_number: 0,
+      engine_type: 0,
+      engine_id: 0,
+      sampling: 0,
+      records: Vec::with_capacity(FlowSet::MAX_COUNT as usize),
+    }
+  }
+
+  /// Sets the sequence counter of the next packet, for example to resume an export
+  pub fn sequence_number(mut self, sequence_number: u32) -> PacketBuilder {
+    self.sequence_number = sequence_number;
+    self
+  }
+
+  /// Sets the type and slot number of the flow-switching engine
+  pub fn engine(mut self, engine_type: u8, engine_id: u8) -> PacketBuilder {
+    self.engine_type = engine_type;
+    self.engine_id = engine_id;
+    self
+  }
+
+  /// Sets the raw sampling field: mode in the first two bits, interval in the other 14
+  pub fn sampling(mut self, sampling: u16) -> PacketBuilder {
+    self.sampling = sampling;
+    self
+  }
+
+  /// Sequence counter the next packet will carry
+  pub fn next_sequence_number(&self) -> u32 {
+    self.sequence_number
+  }
+
+  /// Milliseconds elapsed between the boot of the export device and `now`,
+  /// wrapping around like a 32-bit SysUptime counter
+  pub fn sys_uptime_msecs(&self, now: SystemTime) -> u32 {
+    let uptime = now.duration_since(self.boot_time).unwrap_or_default();
+    (uptime.as_secs().wrapping_mul(1000) + u64::from(uptime.subsec_millis())) as u32
+  }
+
+  /// Adds a record to the next packet, failing if it already holds 30 records
+  pub fn push(&mut self, record: FlowRecord) -> Result<(), Error> {
+    if self.is_full() {
+      return Err(Error::InvalidCount {
+        protocol: Protocol::V5,
+        count: FlowSet::MAX_COUNT + 1,
+      });
+    }
+    self.records.push(record);
+    Ok(())
+  }
+
+  /// Number of records pending for the next packet
+  pub fn len(&self) -> usize {
+    self.records.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.records.is_empty()
+  }
+
+  /// Whether the next packet cannot hold any more records
+  pub fn is_full(&self) -> bool {
+    self.records.len() >= FlowSet::MAX_COUNT as usize
+  }
+
+  /// Length in bytes of the next packet
+  pub fn packet_len(&self) -> usize {
+    Header::LEN + self.records.len() * Record::LEN
+  }
+
+  /// Writes the pending records as a packet exported at `now` into `data`,
+  /// returning its length
+  ///
+  /// On success the pending records are cleared and the sequence counter
+  /// advances by their number. Fails without side effect if there is no
+  /// pending record or if `data` is too short.
+  pub fn write(&mut self, now: SystemTime, data: &mut [u8]) -> Result<usize, Error> {
+    if self.records.is_empty() {
+      return Err(Error::InvalidCount {
+        protocol: Protocol::V5,
+        count: 0,
+      });
+    }
+    let len = self.packet_len();
+    if data.len() < len {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::V5,
+        offset: 0,
+        expected: len,
+        actual: data.len(),
+      });
+    }
+
+    let since_epoch = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let count = self.records.len() as u16;
+    PacketHeader {
+      version: Header::VERSION,
+      count,
+      sys_uptime_msecs: self.sys_uptime_msecs(now),
+      unix_secs: since_epoch.as_secs() as u32,
+      unix_nsecs: since_epoch.subsec_nanos(),
+      sequence_number: self.sequence_number,
+      engine_type: self.engine_type,
+      engine_id: self.engine_id,
+      sampling: self.sampling,
+    }
+    .write(data);
+    for (i, record) in self.records.iter().enumerate() {
+      record.write(&mut data[Header::LEN + i * Record::LEN..]);
+    }
+
+    self.sequence_number = self.sequence_number.wrapping_add(u32::from(count));
+    self.records.clear();
+    Ok(len)
+  }
+
+  /// Builds the pending records into a packet exported at `now`, see `write`
+  pub fn build(&mut self, now: SystemTime) -> Result<Vec<u8>, Error> {
+    let mut data = vec![0; self.packet_len()];
+    self.write(now, &mut data)?;
+    Ok(data)
+  }
+
+  /// Time at which the export device booted
+  pub fn boot_time(&self) -> SystemTime {
+    self.boot_time
+  }
+
+  /// Moves the boot time of the export device, as after a restart, and resets
+  /// the sequence counter
+  pub fn reboot(&mut self, boot_time: SystemTime) {
+    self.boot_time = boot_time;
+    self.sequence_number = 0;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::FLOW_PACKET_1;
+  use super::*;
+  use std::net::Ipv4Addr;
+  use std::time::Duration;
+
+  /// Deterministic xorshift generator, so that failures can be reproduced
+  struct Rng(u64);
+
+  impl Rng {
+    fn next(&mut self) -> u64 {
+      self.0 ^= self.0 << 13;
+      self.0 ^= self.0 >> 7;
+      self.0 ^= self.0 << 17;
+      self.0
+    }
+
+    fn record(&mut self) -> FlowRecord {
+      FlowRecord {
+        source_addr: Ipv4Addr::from(self.next() as u32),
+        destination_addr: Ipv4Addr::from(self.next() as u32),
+        next_hop: Ipv4Addr::from(self.next() as u32),
+        input: self.next() as u16,
+        output: self.next() as u16,
+        packets: self.next() as u32,
+        bytes: self.next() as u32,
+        first_packet_sys_uptime: self.next() as u32,
+        last_packet_sys_uptime: self.next() as u32,
+        source_port: self.next() as u16,
+        destination_port: self.next() as u16,
+        tcp_flags: self.next() as u8,
+        protocol: self.next() as u8,
+        type_of_service: self.next() as u8,
+        source_as: self.next() as u16,
+        destination_as: self.next() as u16,
+        source_mask: self.next() as u8,
+        destination_mask: self.next() as u8,
+      }
+    }
+  }
+
+  fn boot_time() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(1544476581) - Duration::from_millis(51469784)
+  }
+
+  #[test]
+  fn builder_should_round_trip_records() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let mut builder = PacketBuilder::new(boot_time())
+      .engine(1, 2)
+      .sampling(0x4064);
+    let mut now = UNIX_EPOCH + Duration::from_secs(1544476581);
+
+    for _ in 0..200 {
+      let count = 1 + rng.next() as usize % FlowSet::MAX_COUNT as usize;
+      let records = (0..count).map(|_| rng.record()).collect::<Vec<_>>();
+      for record in records.iter() {
+        builder.push(*record).unwrap();
+      }
+      let sequence_number = builder.next_sequence_number();
+
+      let data = builder.build(now).unwrap();
+      let set = FlowSet::new(&data).unwrap();
+      let header = set.header();
+      assert_eq!(data.len(), Header::LEN + count * Record::LEN);
+      assert_eq!(header.count() as usize, count);
+      assert_eq!(header.sequence_number(), sequence_number);
+      assert_eq!(header.engine_type(), 1);
+      assert_eq!(header.engine_id(), 2);
+      assert_eq!(header.sampling(), 0x4064);
+      assert_eq!(
+        set
+          .records()
+          .map(|r| FlowRecord::from(&r))
+          .collect::<Vec<_>>(),
+        records
+      );
+      assert_eq!(
+        builder.next_sequence_number(),
+        sequence_number + count as u32
+      );
+
+      now += Duration::from_millis(rng.next() % 10_000);
+    }
+  }
+
+  #[test]
+  fn builder_should_reproduce_captured_packet() {
+    let set = FlowSet::new(&FLOW_PACKET_1).unwrap();
+    let mut builder = PacketBuilder::new(boot_time())
+      .sequence_number(873873830)
+      .sampling(1000);
+    for record in set.records() {
+      builder.push(FlowRecord::from(&record)).unwrap();
+    }
+
+    let data = builder
+      .build(UNIX_EPOCH + Duration::from_secs(1544476581))
+      .unwrap();
+    assert_eq!(&data[..], &FLOW_PACKET_1[..data.len()]);
+
+    let (header, _) = PacketHeader::parse(&data).unwrap();
+    assert_eq!(header.sys_uptime_msecs, 51469784);
+  }
+
+  #[test]
+  fn builder_push_should_fail_when_full() {
+    let mut builder = PacketBuilder::new(boot_time());
+    for _ in 0..FlowSet::MAX_COUNT {
+      builder.push(FlowRecord::default()).unwrap();
+    }
+
+    assert!(builder.is_full());
+    assert_eq!(
+      builder.push(FlowRecord::default()),
+      Err(Error::InvalidCount {
+        protocol: Protocol::V5,
+        count: 31,
+      })
+    );
+  }
+
+  #[test]
+  fn builder_write_should_fail_without_side_effect() {
+    let mut builder = PacketBuilder::new(boot_time());
+    let mut data = [0; Header::LEN + Record::LEN];
+
+    assert_eq!(
+      builder.write(SystemTime::now(), &mut data),
+      Err(Error::InvalidCount {
+        protocol: Protocol::V5,
+        count: 0,
+      })
+    );
+
+    builder.push(FlowRecord::default()).unwrap();
+    builder.push(FlowRecord::default()).unwrap();
+    assert_eq!(
+      builder.write(SystemTime::now(), &mut data),
+      Err(Error::NotEnoughData {
+        protocol: Protocol::V5,
+        offset: 0,
+        expected: Header::LEN + 2 * Record::LEN,
+        actual: Header::LEN + Record::LEN,
+      })
+    );
+    assert_eq!(builder.len(), 2);
+    assert_eq!(builder.next_sequence_number(), 0);
+  }
+
+  #[test]
+  fn builder_sys_uptime_should_wrap_around() {
+    let builder = PacketBuilder::new(UNIX_EPOCH);
+    let wrap = Duration::from_millis(1 << 32);
+
+    assert_eq!(builder.sys_uptime_msecs(UNIX_EPOCH + wrap), 0);
+    assert_eq!(
+      builder.sys_uptime_msecs(UNIX_EPOCH + wrap + Duration::from_millis(42)),
+      42
+    );
+    assert_eq!(builder.sys_uptime_msecs(UNIX_EPOCH - wrap), 0);
+  }
+}
@@ -0,0 +1,203 @@
+use super::raw::Header;
+use super::Sampling;
+use crate::Error;
+use byteorder::{ByteOrder, NetworkEndian};
+use std::time::SystemTime;
+
+/// An owned Netflow v5 header, the counterpart of the borrowed `raw::Header`
+///
+/// Conversions go both ways: `From<&raw::Header>` copies the fields out of a
+/// packet, and `to_bytes` or `write` produce the wire format that
+/// `raw::Header::new` reads back. Derived values such as `export_time` are
+/// computed by the borrowed view, so the two cannot disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PacketHeader {
+  /// NetFlow export format version number
+  pub version: u16,
+  /// Number of flows that are exported in this packet (1-30)
+  pub count: u16,
+  /// Current time in milliseconds since the export device started
+  pub sys_uptime_msecs: u32,
+  /// Current time in seconds since 0000 Coordinated Universal Time 1970
+  pub unix_secs: u32,
+  /// Residual nanoseconds since 0000 Coordinated Universal Time 1970
+  pub unix_nsecs: u32,
+  /// Sequence counter of total flows seen
+  pub sequence_number: u32,
+  /// Type of flow-switching engine
+  pub engine_type: u8,
+  /// Slot number of the flow-switching engine
+  pub engine_id: u8,
+  /// First two bits hold the sampling mode; remaining 14 bits hold value of sampling interval
+  pub sampling: u16,
+}
+
+impl PacketHeader {
+  /// Reads the header at the start of `data` with `raw::Header::new`,
+  /// returning it with the bytes that follow it
+  pub fn parse(data: &[u8]) -> Result<(PacketHeader, &[u8]), Error> {
+    let header = Header::new(data)?;
+    Ok((PacketHeader::from(&header), &data[Header::LEN..]))
+  }
+
+  /// Writes the header in its wire format into the first `raw::Header::LEN`
+  /// bytes of `data`
+  ///
+  /// # Panics
+  ///
+  /// If `data` is shorter than `raw::Header::LEN`
+  pub fn write(&self, data: &mut [u8]) {
+    let data = &mut data[..Header::LEN];
+    NetworkEndian::write_u16(&mut data[0..2], self.version);
+    NetworkEndian::write_u16(&mut data[2..4], self.count);
+    NetworkEndian::write_u32(&mut data[4..8], self.sys_uptime_msecs);
+    NetworkEndian::write_u32(&mut data[8..12], self.unix_secs);
+    NetworkEndian::write_u32(&mut data[12..16], self.unix_nsecs);
+    NetworkEndian::write_u32(&mut data[16..20], self.sequence_number);
+    data[20] = self.engine_type;
+    data[21] = self.engine_id;
+    NetworkEndian::write_u16(&mut data[22..24], self.sampling);
+  }
+
+  /// The header in its wire format, see `write`
+  pub fn to_bytes(&self) -> [u8; Header::LEN] {
+    let mut data = [0; Header::LEN];
+    self.write(&mut data);
+    data
+  }
+
+  /// Sampling mode and interval decoded from the sampling field
+  pub fn sampling_info(&self) -> Sampling {
+    Sampling::from(self.sampling)
+  }
+
+  /// Time at which the packet was exported, see `raw::Header::export_time`
+  pub fn export_time(&self) -> SystemTime {
+    let data = self.to_bytes();
+    Header { data: &data }.export_time()
+  }
+
+  /// Wall-clock time at which the SysUptime of the export device was
+  /// `sys_uptime_msecs`, see `raw::Header::system_time`
+  pub fn system_time(&self, sys_uptime_msecs: u32) -> SystemTime {
+    let data = self.to_bytes();
+    Header { data: &data }.system_time(sys_uptime_msecs)
+  }
+}
+
+impl<'a> From<&Header<'a>> for PacketHeader {
+  fn from(header: &Header<'a>) -> PacketHeader {
+    PacketHeader {
+      version: header.version(),
+      count: header.count(),
+      sys_uptime_msecs: header.sys_uptime_msecs(),
+      unix_secs: header.unix_secs(),
+      unix_nsecs: header.unix_nsecs(),
+      sequence_number: header.sequence_number(),
+      engine_type: header.engine_type(),
+      engine_id: header.engine_id(),
+      sampling: header.sampling(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::{get_flow_packet_header, FLOW_PACKET_1};
+  use super::*;
+  use crate::Protocol;
+
+  #[test]
+  fn packet_header_parsing_short() {
+    assert_eq!(
+      PacketHeader::parse(&FLOW_PACKET_1[..Header::LEN - 1]),
+      Err(Error::NotEnoughData {
+        protocol: Protocol::V5,
+        offset: 0,
+        expected: Header::LEN,
+        actual: Header::LEN - 1,
+      })
+    );
+  }
+
+  #[test]
+  fn packet_header_parsing_should_fail_with_invalid_version() {
+    let mut data = FLOW_PACKET_1.to_vec();
+    data[1] = 9;
+
+    assert_eq!(
+      PacketHeader::parse(&data),
+      Err(Error::InvalidVersion {
+        protocol: Protocol::V5,
+        version: 9,
+      })
+    );
+  }
+
+  #[test]
+  fn packet_header_from_header_matches_parse() {
+    let header = Header::new(&FLOW_PACKET_1).unwrap();
+    let (parsed, rest) = PacketHeader::parse(&FLOW_PACKET_1).unwrap();
+
+    assert_eq!(PacketHeader::from(&header), parsed);
+    assert_eq!(rest, &FLOW_PACKET_1[Header::LEN..]);
+  }
+
+  #[test]
+  fn packet_header_to_bytes_round_trips() {
+    let (header, _rest) = PacketHeader::parse(&FLOW_PACKET_1).unwrap();
+    let data = header.to_bytes();
+
+    assert_eq!(&data[..], get_flow_packet_header());
+    assert_eq!(PacketHeader::from(&Header::new(&data).unwrap()), header);
+  }
+
+  #[test]
+  fn packet_header_derived_values_match_header() {
+    let header = Header::new(&FLOW_PACKET_1).unwrap();
+    let owned = PacketHeader::from(&header);
+
+    assert_eq!(owned.sampling_info(), header.sampling_info());
+    assert_eq!(owned.export_time(), header.export_time());
+    assert_eq!(owned.system_time(51402145), header.system_time(51402145));
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn packet_header_serde_round_trip() {
+    let (header, _rest) = PacketHeader::parse(&FLOW_PACKET_1).unwrap();
+    let json = serde_json::to_string(&header).unwrap();
+
+    assert_eq!(
+      json,
+      "{\"version\":5,\"count\":29,\"sys_uptime_msecs\":51469784,\"unix_secs\":1544476581,\"unix_nsecs\":0,\"sequence_number\":873873830,\"engine_type\":0,\"engine_id\":0,\"sampling\":1000}"
+    );
+    assert_eq!(serde_json::from_str::<PacketHeader>(&json).unwrap(), header);
+  }
+
+  #[test]
+  fn packet_header_parsing() {
+    let (header, _rest) = PacketHeader::parse(&FLOW_PACKET_1).unwrap();
+
+    println!("Version: {:?}", header.version);
+    println!("Count: {:?}", header.count);
+    println!("Uptime: {:?}", header.sys_uptime_msecs);
+    println!("Unix (s): {:?}", header.unix_secs);
+    println!("Unix (ns): {:?}", header.unix_nsecs);
+    println!("Sequence: {:?}", header.sequence_number);
+    println!("Engine type: {:?}", header.engine_type);
+    println!("Engine id: {:?}", header.engine_id);
+    println!("Sampling: {:?}", header.sampling);
+
+    assert_eq!(header.version, 5);
+    assert_eq!(header.count, 0x1d);
+    assert_eq!(header.sys_uptime_msecs, 51469784);
+    assert_eq!(header.unix_secs, 1544476581);
+    assert_eq!(header.unix_nsecs, 0);
+    assert_eq!(header.sequence_number, 873873830);
+    assert_eq!(header.engine_type, 0);
+    assert_eq!(header.engine_id, 0);
+    assert_eq!(header.sampling, 1000);
+  }
+}
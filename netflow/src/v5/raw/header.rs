@@ -1,5 +1,7 @@
-use super::{Error, ERROR_INVALID_VERSION, ERROR_NOT_ENOUGH_DATA};
+use super::{Error, Protocol};
+use crate::v5::Sampling;
 use byteorder::{ByteOrder, NetworkEndian};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// A Netflow v5 header helper struct
 #[derive(PartialEq)]
@@ -21,7 +23,12 @@ impl<'a> Header<'a> {
 
   pub fn new(data: &'a [u8]) -> Result<Header<'a>, Error> {
     if data.len() < Self::LEN {
-      return Err(ERROR_NOT_ENOUGH_DATA);
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::V5,
+        offset: 0,
+        expected: Self::LEN,
+        actual: data.len(),
+      });
     }
     let version: u16 = unsafe {
       let mut version: u16 = 0;
@@ -29,10 +36,13 @@ impl<'a> Header<'a> {
       version
     };
     if version != Self::VERSION_NETWORK_ORDER {
-      return Err(ERROR_INVALID_VERSION);
+      return Err(Error::InvalidVersion {
+        protocol: Protocol::V5,
+        version: u16::from_be(version),
+      });
     }
 
-    Ok(Header { data: data })
+    Ok(Header { data })
   }
 
   /// NetFlow export format version number
@@ -79,6 +89,46 @@ impl<'a> Header<'a> {
   pub fn sampling(&self) -> u16 {
     NetworkEndian::read_u16(&(self.data[22..24]))
   }
+
+  /// Sampling mode and interval decoded from the sampling field
+  pub fn sampling_info(&self) -> Sampling {
+    Sampling::from(self.sampling())
+  }
+
+  /// Time at which the packet was exported, from `unix_secs` and `unix_nsecs`
+  pub fn export_time(&self) -> SystemTime {
+    UNIX_EPOCH + Duration::new(u64::from(self.unix_secs()), self.unix_nsecs())
+  }
+
+  /// Wall-clock time at which the SysUptime of the export device was `sys_uptime_msecs`
+  ///
+  /// SysUptime is a 32-bit counter of milliseconds that wraps around about
+  /// every 49.7 days. Its difference with the header uptime is taken modulo
+  /// 2^32 and read as signed, so that an uptime recorded before the counter
+  /// wrapped still resolves to the past, while one slightly ahead of the header
+  /// uptime resolves to just after the export time.
+  pub fn system_time(&self, sys_uptime_msecs: u32) -> SystemTime {
+    let elapsed = self.sys_uptime_msecs().wrapping_sub(sys_uptime_msecs) as i32;
+    let offset = Duration::from_millis(u64::from(elapsed.unsigned_abs()));
+    if elapsed >= 0 {
+      self.export_time() - offset
+    } else {
+      self.export_time() + offset
+    }
+  }
+
+  /// Time at which the packet was exported, see `export_time`
+  #[cfg(feature = "chrono")]
+  pub fn export_date_time(&self) -> chrono::DateTime<chrono::Utc> {
+    self.export_time().into()
+  }
+
+  /// Wall-clock time at which the SysUptime of the export device was
+  /// `sys_uptime_msecs`, see `system_time`
+  #[cfg(feature = "chrono")]
+  pub fn date_time(&self, sys_uptime_msecs: u32) -> chrono::DateTime<chrono::Utc> {
+    self.system_time(sys_uptime_msecs).into()
+  }
 }
 
 #[cfg(test)]
@@ -94,7 +144,12 @@ mod tests {
   fn header_new_should_fail_with_not_enough_data() {
     assert_eq!(
       Header::new(&get_flow_packet_header()[..Header::LEN - 1]),
-      Err(ERROR_NOT_ENOUGH_DATA)
+      Err(Error::NotEnoughData {
+        protocol: Protocol::V5,
+        offset: 0,
+        expected: Header::LEN,
+        actual: Header::LEN - 1,
+      })
     );
   }
 
@@ -109,7 +164,13 @@ mod tests {
     let res = Header::new(&data);
 
     assert!(res.is_err());
-    assert_eq!(res, Err(ERROR_INVALID_VERSION));
+    assert_eq!(
+      res,
+      Err(Error::InvalidVersion {
+        protocol: Protocol::V5,
+        version: 1,
+      })
+    );
   }
 
   #[test]
@@ -125,6 +186,64 @@ mod tests {
     assert_eq!(header.engine_type(), 0);
     assert_eq!(header.engine_id(), 0);
     assert_eq!(header.sampling(), 1000);
+    assert_eq!(header.sampling_info().interval, 1000);
+  }
+
+  fn with_times(sys_uptime_msecs: u32, unix_secs: u32, unix_nsecs: u32) -> Vec<u8> {
+    let mut data = get_flow_packet_header().to_vec();
+    NetworkEndian::write_u32(&mut data[4..8], sys_uptime_msecs);
+    NetworkEndian::write_u32(&mut data[8..12], unix_secs);
+    NetworkEndian::write_u32(&mut data[12..16], unix_nsecs);
+    data
+  }
+
+  #[test]
+  fn header_export_time_should_include_nanoseconds() {
+    let data = with_times(0, 1544476581, 250_000_000);
+    let header = Header::new(&data).unwrap();
+
+    assert_eq!(
+      header.export_time(),
+      UNIX_EPOCH + Duration::from_millis(1544476581250)
+    );
+  }
+
+  #[test]
+  fn header_system_time_should_count_back_from_export_time() {
+    let header = Header::new(get_flow_packet_header()).unwrap();
+
+    assert_eq!(
+      header.system_time(51402145),
+      UNIX_EPOCH + Duration::from_millis(1544476513361)
+    );
+    assert_eq!(header.system_time(51469784), header.export_time());
+    assert_eq!(
+      header.system_time(51469790),
+      header.export_time() + Duration::from_millis(6)
+    );
+  }
+
+  #[test]
+  fn header_system_time_should_handle_uptime_wraparound() {
+    let data = with_times(100, 1544476581, 0);
+    let header = Header::new(&data).unwrap();
+
+    assert_eq!(
+      header.system_time(u32::MAX - 899),
+      UNIX_EPOCH + Duration::from_millis(1544476580000)
+    );
+  }
+
+  #[cfg(feature = "chrono")]
+  #[test]
+  fn header_date_time_should_match_system_time() {
+    let header = Header::new(get_flow_packet_header()).unwrap();
+
+    assert_eq!(
+      header.date_time(51402145).to_rfc3339(),
+      "2018-12-10T21:15:13.361+00:00"
+    );
+    assert_eq!(header.export_date_time().timestamp(), 1544476581);
   }
 
   #[test]
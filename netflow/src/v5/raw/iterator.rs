@@ -1,27 +1,142 @@
-use super::{FlowSet, Header, Record};
+use super::{Record, UpscaledRecord};
+use crate::fixed::Records;
+use crate::v5::Sampling;
+use std::iter::FusedIterator;
 
+/// Iterator over the records of a packet, borrowing the packet data rather
+/// than its `FlowSet`
+#[derive(Clone)]
 pub struct RecordIterator<'a> {
-  pub(crate) set: &'a FlowSet<'a>,
-  pub(crate) pos: usize,
-  pub(crate) count: usize,
+  pub(crate) records: Records<'a>,
 }
 
 impl<'a> Iterator for RecordIterator<'a> {
   type Item = Record<'a>;
 
   fn next(&mut self) -> Option<Record<'a>> {
-    if self.pos == self.count {
-      return None;
-    }
+    let data = self.records.next()?;
+    Some(Record { data })
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.records.size_hint()
+  }
 
-    let start = Header::LEN + self.pos * Record::LEN;
-    let ref data = self.set.data[start..start + Record::LEN];
-    self.pos += 1;
-    Some(Record { data: data })
+  fn nth(&mut self, n: usize) -> Option<Record<'a>> {
+    let data = self.records.nth(n)?;
+    Some(Record { data })
+  }
+}
+
+impl<'a> DoubleEndedIterator for RecordIterator<'a> {
+  fn next_back(&mut self) -> Option<Record<'a>> {
+    let data = self.records.next_back()?;
+    Some(Record { data })
+  }
+}
+
+impl<'a> ExactSizeIterator for RecordIterator<'a> {}
+
+impl<'a> FusedIterator for RecordIterator<'a> {}
+
+/// Iterator over the records of a packet with counters upscaled by its sampling interval
+#[derive(Clone)]
+pub struct UpscaledRecordIterator<'a> {
+  pub(crate) records: RecordIterator<'a>,
+  pub(crate) sampling: Sampling,
+}
+
+impl<'a> Iterator for UpscaledRecordIterator<'a> {
+  type Item = UpscaledRecord<'a>;
+
+  fn next(&mut self) -> Option<UpscaledRecord<'a>> {
+    let record = self.records.next()?;
+    Some(UpscaledRecord {
+      record,
+      sampling: self.sampling,
+    })
   }
 
   fn size_hint(&self) -> (usize, Option<usize>) {
-    let remainder = self.count - self.pos;
-    (remainder, Some(remainder))
+    self.records.size_hint()
+  }
+}
+
+impl<'a> DoubleEndedIterator for UpscaledRecordIterator<'a> {
+  fn next_back(&mut self) -> Option<UpscaledRecord<'a>> {
+    let record = self.records.next_back()?;
+    Some(UpscaledRecord {
+      record,
+      sampling: self.sampling,
+    })
+  }
+}
+
+impl<'a> ExactSizeIterator for UpscaledRecordIterator<'a> {}
+
+impl<'a> FusedIterator for UpscaledRecordIterator<'a> {}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::FLOW_PACKET_1;
+  use super::super::FlowSet;
+  use super::*;
+
+  /// Records of a set built and dropped locally, which only the packet data
+  /// has to outlive
+  fn records(data: &[u8]) -> RecordIterator<'_> {
+    let set = FlowSet::new(data).unwrap();
+    set.records()
+  }
+
+  #[test]
+  fn records_should_outlive_their_set() {
+    let records = records(&FLOW_PACKET_1);
+
+    assert_eq!(records.len(), 29);
+    assert_eq!(records.count(), 29);
+  }
+
+  #[test]
+  fn records_should_iterate_from_both_ends() {
+    let set = FlowSet::new(&FLOW_PACKET_1).unwrap();
+    let forward = set.records().collect::<Vec<_>>();
+    let mut backward = set.records().rev().collect::<Vec<_>>();
+    backward.reverse();
+    assert_eq!(forward, backward);
+
+    let mut records = set.records();
+    assert_eq!(records.next(), set.record(0));
+    assert_eq!(records.next_back(), set.record(28));
+    assert_eq!(records.len(), 27);
+    assert_eq!(records.nth(26), set.record(27));
+    assert_eq!(records.len(), 0);
+    assert_eq!(records.next(), None);
+    assert_eq!(records.next_back(), None);
+    assert_eq!(records.next(), None);
+  }
+
+  #[test]
+  fn record_should_match_iteration() {
+    let set = FlowSet::new(&FLOW_PACKET_1).unwrap();
+
+    for (i, record) in (&set).into_iter().enumerate() {
+      assert_eq!(set.record(i), Some(record));
+    }
+    assert_eq!(set.record(29), None);
+    assert_eq!(set.records().nth(29), None);
+  }
+
+  #[test]
+  fn upscaled_records_should_iterate_from_both_ends() {
+    let set = FlowSet::new(&FLOW_PACKET_1).unwrap();
+    let mut records = set.upscaled_records();
+
+    assert_eq!(records.len(), 29);
+    assert_eq!(
+      records.next_back().unwrap().record,
+      set.record(28).unwrap()
+    );
+    assert_eq!(records.len(), 28);
   }
 }
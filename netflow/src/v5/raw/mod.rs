@@ -1,4 +1,4 @@
-use super::{Error, ERROR_INVALID_VERSION, ERROR_NOT_ENOUGH_DATA};
+use crate::{Error, Protocol};
 
 mod header;
 mod iterator;
@@ -6,8 +6,8 @@ mod record;
 mod set;
 
 pub use header::Header;
-pub use iterator::RecordIterator;
-pub use record::Record;
+pub use iterator::{RecordIterator, UpscaledRecordIterator};
+pub use record::{Record, UpscaledRecord};
 pub use set::FlowSet;
 
 #[cfg(test)]
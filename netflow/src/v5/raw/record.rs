@@ -1,5 +1,9 @@
-use super::{Error, ERROR_NOT_ENOUGH_DATA};
+use super::{Error, Header, Protocol};
+use crate::v5::Sampling;
+use crate::Flow;
 use byteorder::{ByteOrder, NetworkEndian};
+use std::net::IpAddr;
+use std::time::SystemTime;
 
 /// A Netflow v5 record helper struct
 #[derive(PartialEq)]
@@ -18,10 +22,15 @@ impl<'a> Record<'a> {
   pub const LEN: usize = 48;
   pub fn new(data: &'a [u8]) -> Result<Record<'a>, Error> {
     if data.len() < Self::LEN {
-      return Err(ERROR_NOT_ENOUGH_DATA);
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::V5,
+        offset: 0,
+        expected: Self::LEN,
+        actual: data.len(),
+      });
     }
 
-    Ok(Record { data: data })
+    Ok(Record { data })
   }
 
   /// Source IP address
@@ -94,6 +103,11 @@ impl<'a> Record<'a> {
     NetworkEndian::read_u16(&(self.data[34..36]))
   }
 
+  /// Unused (zero) byte following the ports
+  pub fn pad1(&self) -> u8 {
+    self.data[36]
+  }
+
   /// Cumulative OR of TCP flags
   pub fn tcp_flags(&self) -> u8 {
     self.data[37]
@@ -128,12 +142,202 @@ impl<'a> Record<'a> {
   pub fn destination_mask(&self) -> u8 {
     self.data[45]
   }
+
+  /// Unused (zero) bytes ending the record
+  pub fn pad2(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[46..48]))
+  }
+
+  /// Checks the record the way `FlowSet::validate` does, with offsets
+  /// relative to the start of the record
+  pub fn validate(&self) -> Result<(), Error> {
+    if self.pad1() != 0 {
+      return Err(Error::InvalidPadding {
+        protocol: Protocol::V5,
+        offset: 36,
+      });
+    }
+    if self.pad2() != 0 {
+      return Err(Error::InvalidPadding {
+        protocol: Protocol::V5,
+        offset: 46,
+      });
+    }
+    for &(offset, mask) in [(44, self.source_mask()), (45, self.destination_mask())].iter() {
+      if mask > 32 {
+        return Err(Error::InvalidMask {
+          protocol: Protocol::V5,
+          offset,
+          mask,
+        });
+      }
+    }
+    if self.first_packet_sys_uptime() > self.last_packet_sys_uptime() {
+      return Err(Error::InvalidUptime {
+        protocol: Protocol::V5,
+        offset: 24,
+        first: self.first_packet_sys_uptime(),
+        last: self.last_packet_sys_uptime(),
+      });
+    }
+
+    Ok(())
+  }
+
+  /// Wall-clock time at start of flow, given the header of the packet holding the record
+  pub fn first_packet_time(&self, header: &Header<'_>) -> SystemTime {
+    header.system_time(self.first_packet_sys_uptime())
+  }
+
+  /// Wall-clock time at which the last packet of the flow was received, given
+  /// the header of the packet holding the record
+  pub fn last_packet_time(&self, header: &Header<'_>) -> SystemTime {
+    header.system_time(self.last_packet_sys_uptime())
+  }
+
+  /// Wall-clock time at start of flow, see `first_packet_time`
+  #[cfg(feature = "chrono")]
+  pub fn first_packet_date_time(&self, header: &Header<'_>) -> chrono::DateTime<chrono::Utc> {
+    header.date_time(self.first_packet_sys_uptime())
+  }
+
+  /// Wall-clock time of the last packet of the flow, see `last_packet_time`
+  #[cfg(feature = "chrono")]
+  pub fn last_packet_date_time(&self, header: &Header<'_>) -> chrono::DateTime<chrono::Utc> {
+    header.date_time(self.last_packet_sys_uptime())
+  }
+}
+
+impl<'a> Flow for Record<'a> {
+  fn source_addr(&self) -> Option<IpAddr> {
+    Some(self.source_addr())
+  }
+
+  fn destination_addr(&self) -> Option<IpAddr> {
+    Some(self.destination_addr())
+  }
+
+  fn source_port(&self) -> Option<u16> {
+    Some(self.source_port())
+  }
+
+  fn destination_port(&self) -> Option<u16> {
+    Some(self.destination_port())
+  }
+
+  fn protocol(&self) -> Option<u8> {
+    Some(self.protocol())
+  }
+
+  fn packets(&self) -> Option<u64> {
+    Some(u64::from(self.packets()))
+  }
+
+  fn bytes(&self) -> Option<u64> {
+    Some(u64::from(self.bytes()))
+  }
+
+  fn input(&self) -> Option<u32> {
+    Some(u32::from(self.input()))
+  }
+
+  fn output(&self) -> Option<u32> {
+    Some(u32::from(self.output()))
+  }
+
+  fn next_hop(&self) -> Option<IpAddr> {
+    Some(self.next_hop())
+  }
+
+  fn source_as(&self) -> Option<u32> {
+    Some(u32::from(self.source_as()))
+  }
+
+  fn destination_as(&self) -> Option<u32> {
+    Some(u32::from(self.destination_as()))
+  }
+
+  fn source_mask(&self) -> Option<u8> {
+    Some(self.source_mask())
+  }
+
+  fn destination_mask(&self) -> Option<u8> {
+    Some(self.destination_mask())
+  }
+
+  fn type_of_service(&self) -> Option<u8> {
+    Some(self.type_of_service())
+  }
+
+  fn tcp_flags(&self) -> Option<u8> {
+    Some(self.tcp_flags())
+  }
+
+  fn first_packet_sys_uptime(&self) -> Option<u32> {
+    Some(self.first_packet_sys_uptime())
+  }
+
+  fn last_packet_sys_uptime(&self) -> Option<u32> {
+    Some(self.last_packet_sys_uptime())
+  }
+}
+
+/// A Netflow v5 record whose counters are upscaled by the sampling interval of its packet
+///
+/// Every accessor of `Record` is available through `Deref`, except `packets`
+/// and `bytes` which return the estimated totals instead of the sampled ones.
+#[derive(PartialEq)]
+pub struct UpscaledRecord<'a> {
+  pub(crate) record: Record<'a>,
+  pub(crate) sampling: Sampling,
+}
+
+impl<'a> std::fmt::Debug for UpscaledRecord<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("UpscaledRecord")
+      .field("record", &self.record)
+      .field("sampling", &self.sampling)
+      .field("packets", &self.packets())
+      .field("bytes", &self.bytes())
+      .finish()
+  }
+}
+
+impl<'a> std::ops::Deref for UpscaledRecord<'a> {
+  type Target = Record<'a>;
+
+  fn deref(&self) -> &Record<'a> {
+    &self.record
+  }
+}
+
+impl<'a> UpscaledRecord<'a> {
+  /// Record as exported, with sampled counters
+  pub fn record(&self) -> &Record<'a> {
+    &self.record
+  }
+
+  /// Sampling of the packet holding the record
+  pub fn sampling(&self) -> Sampling {
+    self.sampling
+  }
+
+  /// Estimated number of packets in the flow
+  pub fn packets(&self) -> u64 {
+    self.sampling.upscale(self.record.packets())
+  }
+
+  /// Estimated number of Layer 3 bytes in the packets of the flow
+  pub fn bytes(&self) -> u64 {
+    self.sampling.upscale(self.record.bytes())
+  }
 }
 
 #[cfg(test)]
 mod tests {
-  use super::super::tests::get_flow_packet_records;
+  use super::super::tests::{get_flow_packet_header, get_flow_packet_records};
   use super::*;
+  use std::time::{Duration, UNIX_EPOCH};
 
   #[test]
   fn record_new_should_succeed_with_valid_data() {
@@ -144,7 +348,12 @@ mod tests {
   fn record_new_should_fail_with_not_enough_data() {
     assert_eq!(
       Record::new(&get_flow_packet_records()[..Record::LEN - 1]),
-      Err(ERROR_NOT_ENOUGH_DATA)
+      Err(Error::NotEnoughData {
+        protocol: Protocol::V5,
+        offset: 0,
+        expected: Record::LEN,
+        actual: Record::LEN - 1,
+      })
     );
   }
 
@@ -176,6 +385,35 @@ mod tests {
     assert_eq!(record.destination_as(), 56030);
     assert_eq!(record.source_mask(), 20);
     assert_eq!(record.destination_mask(), 22);
+    assert_eq!(record.pad1(), 0);
+    assert_eq!(record.pad2(), 0);
+  }
+
+  #[test]
+  fn record_padding_accessors_expose_raw_bytes() {
+    let mut data = get_flow_packet_records()[..Record::LEN].to_vec();
+    data[36] = 0xaa;
+    data[46..48].copy_from_slice(&[0x12, 0x34]);
+    let record = Record::new(&data).unwrap();
+
+    assert_eq!(record.pad1(), 0xaa);
+    assert_eq!(record.pad2(), 0x1234);
+    assert_eq!(record.tcp_flags(), 0x10);
+  }
+
+  #[test]
+  fn record_packet_times_should_be_relative_to_header() {
+    let header = Header::new(get_flow_packet_header()).unwrap();
+    let record = Record::new(get_flow_packet_records()).unwrap();
+
+    assert_eq!(
+      record.first_packet_time(&header),
+      UNIX_EPOCH + Duration::from_millis(1544476513361)
+    );
+    assert_eq!(
+      record.last_packet_time(&header),
+      UNIX_EPOCH + Duration::from_millis(1544476544480)
+    );
   }
 
   #[test]
@@ -1,32 +1,145 @@
-use super::{Error, ERROR_NOT_ENOUGH_DATA};
+use super::{Error, Protocol};
+use crate::fixed::Layout;
 
-use super::{Header, RecordIterator};
+use super::{Header, Record, RecordIterator, UpscaledRecordIterator};
 
+/// A Netflow v5 packet helper struct
 #[derive(PartialEq)]
 pub struct FlowSet<'a> {
   pub(crate) data: &'a [u8],
+  pub(crate) count: usize,
+}
+
+impl<'a> std::fmt::Debug for FlowSet<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("FlowSet")
+      .field("header", &self.header())
+      .field("records", &self.records().collect::<Vec<_>>())
+      .finish()
+  }
 }
 
 impl<'a> FlowSet<'a> {
+  /// Minimum number of flows announced by a valid packet
+  pub const MIN_COUNT: u16 = 1;
+  /// Maximum number of flows announced by a valid packet
+  pub const MAX_COUNT: u16 = 30;
+
+  const LAYOUT: Layout = Layout {
+    protocol: Protocol::V5,
+    header_len: Header::LEN,
+    record_len: Record::LEN,
+    min_count: Self::MIN_COUNT,
+    max_count: Self::MAX_COUNT,
+  };
+
+  /// Parses a packet, checking that its header announces 1-30 flows and that
+  /// `data` is long enough to hold all of them
   pub fn new(data: &'a [u8]) -> Result<FlowSet<'a>, Error> {
-    if data.len() < Header::LEN {
-      return Err(ERROR_NOT_ENOUGH_DATA);
+    let count = Header::new(data)?.count();
+    Ok(FlowSet {
+      data,
+      count: Self::LAYOUT.check_count(data, count)?,
+    })
+  }
+
+  /// Parses a packet without trusting its flow count: only the complete
+  /// records that fit in `data` are exposed
+  pub fn new_lenient(data: &'a [u8]) -> Result<FlowSet<'a>, Error> {
+    let announced = Header::new(data)?.count();
+    Ok(FlowSet {
+      data,
+      count: Self::LAYOUT.lenient_count(data, announced),
+    })
+  }
+
+  /// Parses a packet like `new`, then rejects it if it fails `validate`
+  pub fn new_strict(data: &'a [u8]) -> Result<FlowSet<'a>, Error> {
+    let set = Self::new(data)?;
+    set.validate()?;
+    Ok(set)
+  }
+
+  /// Checks fields a conforming exporter always gets right, to detect buggy
+  /// or spoofed ones
+  ///
+  /// The header must announce 1-30 flows, all of them held by the packet with
+  /// nothing after the last one. Every record must have zeroed padding,
+  /// prefix masks of at most 32 bits and a first packet uptime no later than
+  /// its last packet uptime. Note that the latter also rejects a flow during
+  /// which the 32-bit SysUptime counter wrapped around.
+  pub fn validate(&self) -> Result<(), Error> {
+    let count = self.header().count();
+    Self::LAYOUT.check_count(self.data, count)?;
+    let expected = Self::LAYOUT.packet_len(count);
+
+    for (i, record) in self.records().enumerate() {
+      record
+        .validate()
+        .map_err(|error| error.offset_by(Header::LEN + i * Record::LEN))?;
     }
 
-    Ok(FlowSet { data: data })
+    if self.data.len() > expected {
+      return Err(Error::TrailingData {
+        protocol: Protocol::V5,
+        offset: expected,
+        length: self.data.len() - expected,
+      });
+    }
+
+    Ok(())
   }
 
-  pub fn header(&'a self) -> Header<'a> {
+  pub fn header(&self) -> Header<'a> {
     Header { data: self.data }
   }
 
-  pub fn records(&'a self) -> RecordIterator {
+  /// Records exposed by this set, borrowing the packet data rather than the
+  /// set itself
+  pub fn records(&self) -> RecordIterator<'a> {
     RecordIterator {
-      set: self,
-      pos: 0,
-      count: self.header().count() as usize,
+      records: Self::LAYOUT.records(self.data, self.count),
+    }
+  }
+
+  /// Record at `index`, if exposed by this set
+  pub fn record(&self, index: usize) -> Option<Record<'a>> {
+    let data = Self::LAYOUT.record(self.data, self.count, index)?;
+    Some(Record { data })
+  }
+
+  /// Records with their packet and byte counters upscaled by the sampling
+  /// interval announced in the header
+  pub fn upscaled_records(&self) -> UpscaledRecordIterator<'a> {
+    UpscaledRecordIterator {
+      records: self.records(),
+      sampling: self.header().sampling_info(),
     }
   }
+
+  /// Number of records exposed by this set
+  pub fn len(&self) -> usize {
+    self.count
+  }
+
+  /// Whether this set exposes no record at all
+  pub fn is_empty(&self) -> bool {
+    self.count == 0
+  }
+
+  /// Whether the header announces more records than the packet holds
+  pub fn is_truncated(&self) -> bool {
+    self.count < self.header().count() as usize
+  }
+}
+
+impl<'a> IntoIterator for &FlowSet<'a> {
+  type Item = Record<'a>;
+  type IntoIter = RecordIterator<'a>;
+
+  fn into_iter(self) -> RecordIterator<'a> {
+    self.records()
+  }
 }
 
 #[cfg(test)]
@@ -34,6 +147,12 @@ mod tests {
   use super::super::tests::FLOW_PACKET_1;
   use super::*;
 
+  fn with_count(count: u16) -> Vec<u8> {
+    let mut data = FLOW_PACKET_1.to_vec();
+    data[2..4].copy_from_slice(&count.to_be_bytes());
+    data
+  }
+
   #[test]
   fn using_set_iterator() {
     let set = FlowSet::new(&FLOW_PACKET_1).unwrap();
@@ -43,4 +162,213 @@ mod tests {
       println!("{:?}", record);
     }
   }
+
+  #[test]
+  fn set_new_should_expose_every_record() {
+    let set = FlowSet::new(&FLOW_PACKET_1).unwrap();
+
+    assert_eq!(set.len(), 29);
+    assert_eq!(set.records().count(), 29);
+    assert!(!set.is_truncated());
+  }
+
+  #[test]
+  fn set_upscaled_records_should_apply_sampling_interval() {
+    let set = FlowSet::new(&FLOW_PACKET_1).unwrap();
+    let record = set.upscaled_records().next().unwrap();
+
+    assert_eq!(set.upscaled_records().count(), 29);
+    assert_eq!(record.sampling().interval, 1000);
+    assert_eq!(record.record().packets(), 4);
+    assert_eq!(record.packets(), 4000);
+    assert_eq!(record.bytes(), 1_708_000);
+    assert_eq!(record.destination_port(), 443);
+  }
+
+  #[test]
+  fn set_new_should_fail_with_not_enough_data() {
+    assert_eq!(
+      FlowSet::new(&FLOW_PACKET_1[..Header::LEN - 1]),
+      Err(Error::NotEnoughData {
+        protocol: Protocol::V5,
+        offset: 0,
+        expected: Header::LEN,
+        actual: Header::LEN - 1,
+      })
+    );
+  }
+
+  #[test]
+  fn set_new_should_fail_with_invalid_version() {
+    let mut data = FLOW_PACKET_1.to_vec();
+    data[1] = 9;
+
+    assert_eq!(
+      FlowSet::new(&data),
+      Err(Error::InvalidVersion {
+        protocol: Protocol::V5,
+        version: 9,
+      })
+    );
+  }
+
+  #[test]
+  fn set_new_should_fail_with_invalid_count() {
+    for count in [0, 31].iter().cloned() {
+      assert_eq!(
+        FlowSet::new(&with_count(count)),
+        Err(Error::InvalidCount {
+          protocol: Protocol::V5,
+          count,
+        })
+      );
+    }
+  }
+
+  #[test]
+  fn set_new_should_fail_with_truncated_data() {
+    let data = &FLOW_PACKET_1[..Header::LEN + 2 * Record::LEN + 10];
+
+    assert_eq!(
+      FlowSet::new(data),
+      Err(Error::Truncated {
+        protocol: Protocol::V5,
+        count: 29,
+        expected: Header::LEN + 29 * Record::LEN,
+        actual: data.len(),
+      })
+    );
+  }
+
+  #[test]
+  fn set_new_lenient_should_recover_complete_records() {
+    let data = &FLOW_PACKET_1[..Header::LEN + 2 * Record::LEN + 10];
+    let set = FlowSet::new_lenient(data).unwrap();
+
+    assert_eq!(set.len(), 2);
+    assert!(set.is_truncated());
+    assert_eq!(
+      set.records().collect::<Vec<_>>(),
+      FlowSet::new(&FLOW_PACKET_1)
+        .unwrap()
+        .records()
+        .take(2)
+        .collect::<Vec<_>>()
+    );
+  }
+
+  #[test]
+  fn set_new_lenient_should_accept_header_only() {
+    let set = FlowSet::new_lenient(&FLOW_PACKET_1[..Header::LEN]).unwrap();
+
+    assert!(set.is_empty());
+    assert_eq!(set.records().next(), None);
+  }
+
+  #[test]
+  fn set_new_lenient_should_accept_invalid_count() {
+    let data = with_count(31);
+    assert_eq!(FlowSet::new_lenient(&data).unwrap().len(), 29);
+
+    let data = with_count(0);
+    assert!(FlowSet::new_lenient(&data).unwrap().is_empty());
+  }
+
+  /// `FLOW_PACKET_1` without the 6 bytes captured after its last record
+  fn conforming() -> Vec<u8> {
+    FLOW_PACKET_1[..Header::LEN + 29 * Record::LEN].to_vec()
+  }
+
+  #[test]
+  fn set_new_strict_should_accept_conforming_packet() {
+    let data = conforming();
+
+    assert_eq!(FlowSet::new_strict(&data), FlowSet::new(&data));
+  }
+
+  #[test]
+  fn set_new_strict_should_fail_with_trailing_data() {
+    assert_eq!(
+      FlowSet::new_strict(&FLOW_PACKET_1),
+      Err(Error::TrailingData {
+        protocol: Protocol::V5,
+        offset: Header::LEN + 29 * Record::LEN,
+        length: 6,
+      })
+    );
+  }
+
+  #[test]
+  fn set_new_strict_should_fail_with_non_zero_padding() {
+    let offset = Header::LEN + 2 * Record::LEN;
+    for &pad in [36, 46, 47].iter() {
+      let mut data = conforming();
+      data[offset + pad] = 1;
+
+      assert_eq!(
+        FlowSet::new_strict(&data),
+        Err(Error::InvalidPadding {
+          protocol: Protocol::V5,
+          offset: offset + pad.min(46),
+        })
+      );
+      assert!(FlowSet::new(&data).is_ok());
+    }
+  }
+
+  #[test]
+  fn set_new_strict_should_fail_with_invalid_mask() {
+    let offset = Header::LEN + Record::LEN;
+    let mut data = conforming();
+    data[offset + 45] = 33;
+
+    assert_eq!(
+      FlowSet::new_strict(&data),
+      Err(Error::InvalidMask {
+        protocol: Protocol::V5,
+        offset: offset + 45,
+        mask: 33,
+      })
+    );
+  }
+
+  #[test]
+  fn set_new_strict_should_fail_with_flow_ending_before_it_starts() {
+    let offset = Header::LEN;
+    let mut data = conforming();
+    data[offset + 24..offset + 28].copy_from_slice(&51433265u32.to_be_bytes());
+
+    assert_eq!(
+      FlowSet::new_strict(&data),
+      Err(Error::InvalidUptime {
+        protocol: Protocol::V5,
+        offset: offset + 24,
+        first: 51433265,
+        last: 51433264,
+      })
+    );
+  }
+
+  #[test]
+  fn set_validate_should_reject_lenient_sets() {
+    let data = &FLOW_PACKET_1[..Header::LEN + 2 * Record::LEN];
+    assert_eq!(
+      FlowSet::new_lenient(data).unwrap().validate(),
+      Err(Error::Truncated {
+        protocol: Protocol::V5,
+        count: 29,
+        expected: Header::LEN + 29 * Record::LEN,
+        actual: data.len(),
+      })
+    );
+
+    let data = with_count(31);
+    assert_eq!(
+      FlowSet::new_lenient(&data).unwrap().validate(),
+      Err(Error::InvalidCount {
+        protocol: Protocol::V5,
+        count: 31,
+      })
+    );
+  }
 }
@@ -0,0 +1,262 @@
+use super::raw::Record;
+use crate::Flow;
+use byteorder::{ByteOrder, NetworkEndian};
+use std::net::{IpAddr, Ipv4Addr};
+
+/// An owned Netflow v5 record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlowRecord {
+  /// Source IP address
+  pub source_addr: Ipv4Addr,
+  /// Destination IP address
+  pub destination_addr: Ipv4Addr,
+  /// IP address of next hop router
+  pub next_hop: Ipv4Addr,
+  /// SNMP index of input interface
+  pub input: u16,
+  /// SNMP index of output interface
+  pub output: u16,
+  /// Packets in the flow
+  pub packets: u32,
+  /// Total number of Layer 3 bytes in the packets of the flow
+  pub bytes: u32,
+  /// SysUptime at start of flow
+  pub first_packet_sys_uptime: u32,
+  /// SysUptime at the time the last packet of the flow was received
+  pub last_packet_sys_uptime: u32,
+  /// TCP or UDP source port number or equivalent
+  pub source_port: u16,
+  /// TCP or UDP destination port number or equivalent
+  pub destination_port: u16,
+  /// Cumulative OR of TCP flags
+  pub tcp_flags: u8,
+  /// IP protocol type (for example, TCP = 6, UDP = 17, ...)
+  pub protocol: u8,
+  /// IP type of service (ToS)
+  pub type_of_service: u8,
+  /// Autonomous system number of the source, either origin or peer
+  pub source_as: u16,
+  /// Autonomous system number of the destination, either origin or peer
+  pub destination_as: u16,
+  /// Source address prefix mask bits
+  pub source_mask: u8,
+  /// Destination address prefix mask bits
+  pub destination_mask: u8,
+}
+
+impl Default for FlowRecord {
+  fn default() -> FlowRecord {
+    FlowRecord {
+      source_addr: Ipv4Addr::UNSPECIFIED,
+      destination_addr: Ipv4Addr::UNSPECIFIED,
+      next_hop: Ipv4Addr::UNSPECIFIED,
+      input: 0,
+      output: 0,
+      packets: 0,
+      bytes: 0,
+      first_packet_sys_uptime: 0,
+      last_packet_sys_uptime: 0,
+      source_port: 0,
+      destination_port: 0,
+      tcp_flags: 0,
+      protocol: 0,
+      type_of_service: 0,
+      source_as: 0,
+      destination_as: 0,
+      source_mask: 0,
+      destination_mask: 0,
+    }
+  }
+}
+
+impl FlowRecord {
+  /// Writes the record in its wire format into the first `Record::LEN` bytes
+  /// of `data`, with zeroed padding
+  ///
+  /// # Panics
+  ///
+  /// If `data` is shorter than `Record::LEN`
+  pub fn write(&self, data: &mut [u8]) {
+    let data = &mut data[..Record::LEN];
+    data[0..4].copy_from_slice(&self.source_addr.octets());
+    data[4..8].copy_from_slice(&self.destination_addr.octets());
+    data[8..12].copy_from_slice(&self.next_hop.octets());
+    NetworkEndian::write_u16(&mut data[12..14], self.input);
+    NetworkEndian::write_u16(&mut data[14..16], self.output);
+    NetworkEndian::write_u32(&mut data[16..20], self.packets);
+    NetworkEndian::write_u32(&mut data[20..24], self.bytes);
+    NetworkEndian::write_u32(&mut data[24..28], self.first_packet_sys_uptime);
+    NetworkEndian::write_u32(&mut data[28..32], self.last_packet_sys_uptime);
+    NetworkEndian::write_u16(&mut data[32..34], self.source_port);
+    NetworkEndian::write_u16(&mut data[34..36], self.destination_port);
+    data[36] = 0;
+    data[37] = self.tcp_flags;
+    data[38] = self.protocol;
+    data[39] = self.type_of_service;
+    NetworkEndian::write_u16(&mut data[40..42], self.source_as);
+    NetworkEndian::write_u16(&mut data[42..44], self.destination_as);
+    data[44] = self.source_mask;
+    data[45] = self.destination_mask;
+    data[46] = 0;
+    data[47] = 0;
+  }
+}
+
+impl<'a> From<&Record<'a>> for FlowRecord {
+  fn from(record: &Record<'a>) -> FlowRecord {
+    let data = record.data;
+    FlowRecord {
+      source_addr: Ipv4Addr::new(data[0], data[1], data[2], data[3]),
+      destination_addr: Ipv4Addr::new(data[4], data[5], data[6], data[7]),
+      next_hop: Ipv4Addr::new(data[8], data[9], data[10], data[11]),
+      input: record.input(),
+      output: record.output(),
+      packets: record.packets(),
+      bytes: record.bytes(),
+      first_packet_sys_uptime: record.first_packet_sys_uptime(),
+      last_packet_sys_uptime: record.last_packet_sys_uptime(),
+      source_port: record.source_port(),
+      destination_port: record.destination_port(),
+      tcp_flags: record.tcp_flags(),
+      protocol: record.protocol(),
+      type_of_service: record.type_of_service(),
+      source_as: record.source_as(),
+      destination_as: record.destination_as(),
+      source_mask: record.source_mask(),
+      destination_mask: record.destination_mask(),
+    }
+  }
+}
+
+impl Flow for FlowRecord {
+  fn source_addr(&self) -> Option<IpAddr> {
+    Some(IpAddr::V4(self.source_addr))
+  }
+
+  fn destination_addr(&self) -> Option<IpAddr> {
+    Some(IpAddr::V4(self.destination_addr))
+  }
+
+  fn source_port(&self) -> Option<u16> {
+    Some(self.source_port)
+  }
+
+  fn destination_port(&self) -> Option<u16> {
+    Some(self.destination_port)
+  }
+
+  fn protocol(&self) -> Option<u8> {
+    Some(self.protocol)
+  }
+
+  fn packets(&self) -> Option<u64> {
+    Some(u64::from(self.packets))
+  }
+
+  fn bytes(&self) -> Option<u64> {
+    Some(u64::from(self.bytes))
+  }
+
+  fn input(&self) -> Option<u32> {
+    Some(u32::from(self.input))
+  }
+
+  fn output(&self) -> Option<u32> {
+    Some(u32::from(self.output))
+  }
+
+  fn next_hop(&self) -> Option<IpAddr> {
+    Some(IpAddr::V4(self.next_hop))
+  }
+
+  fn source_as(&self) -> Option<u32> {
+    Some(u32::from(self.source_as))
+  }
+
+  fn destination_as(&self) -> Option<u32> {
+    Some(u32::from(self.destination_as))
+  }
+
+  fn source_mask(&self) -> Option<u8> {
+    Some(self.source_mask)
+  }
+
+  fn destination_mask(&self) -> Option<u8> {
+    Some(self.destination_mask)
+  }
+
+  fn type_of_service(&self) -> Option<u8> {
+    Some(self.type_of_service)
+  }
+
+  fn tcp_flags(&self) -> Option<u8> {
+    Some(self.tcp_flags)
+  }
+
+  fn first_packet_sys_uptime(&self) -> Option<u32> {
+    Some(self.first_packet_sys_uptime)
+  }
+
+  fn last_packet_sys_uptime(&self) -> Option<u32> {
+    Some(self.last_packet_sys_uptime)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::get_flow_packet_records;
+  use super::*;
+
+  #[test]
+  fn flow_record_from_record_copies_fields() {
+    let record = Record::new(get_flow_packet_records()).unwrap();
+    let flow = FlowRecord::from(&record);
+
+    assert_eq!(flow.source_addr, Ipv4Addr::new(125, 238, 46, 48));
+    assert_eq!(flow.destination_addr, Ipv4Addr::new(114, 23, 236, 96));
+    assert_eq!(flow.next_hop, Ipv4Addr::new(114, 23, 3, 231));
+    assert_eq!(flow.packets, 4);
+    assert_eq!(flow.bytes, 1708);
+    assert_eq!(flow.destination_port, 443);
+    assert_eq!(flow.source_mask, 20);
+    assert_eq!(flow.destination_mask, 22);
+  }
+
+  #[test]
+  fn flow_record_write_matches_wire_format() {
+    let data = &get_flow_packet_records()[..Record::LEN];
+    let mut written = [0xff; Record::LEN];
+    FlowRecord::from(&Record::new(data).unwrap()).write(&mut written);
+
+    assert_eq!(&written[..], data);
+  }
+
+  #[test]
+  fn flow_record_should_outlive_datagram() {
+    let flows = {
+      let data = get_flow_packet_records().to_vec();
+      let record = Record::new(&data).unwrap();
+      std::thread::spawn({
+        let flow = FlowRecord::from(&record);
+        move || vec![flow]
+      })
+      .join()
+      .unwrap()
+    };
+
+    assert_eq!(flows[0].source_addr, Ipv4Addr::new(125, 238, 46, 48));
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn flow_record_serde_round_trip() {
+    let flow = FlowRecord::from(&Record::new(get_flow_packet_records()).unwrap());
+    let json = serde_json::to_value(flow).unwrap();
+
+    assert_eq!(json["source_addr"], "125.238.46.48");
+    assert_eq!(json["destination_port"], 443);
+    assert_eq!(json["bytes"], 1708);
+    assert_eq!(serde_json::from_value::<FlowRecord>(json).unwrap(), flow);
+  }
+}
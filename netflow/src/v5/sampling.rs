@@ -0,0 +1,118 @@
+/// Sampling algorithm announced in the first two bits of the header sampling field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SamplingMode {
+  /// No sampling mode configured, although some exporters still fill the interval
+  Unspecified,
+  /// One packet out of every `interval` packets is sampled
+  Deterministic,
+  /// One packet out of `interval` packets on average is sampled at random
+  Random,
+  /// Mode `0b11`, not assigned
+  Reserved,
+}
+
+/// Decoded sampling field of a Netflow v5 header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sampling {
+  pub mode: SamplingMode,
+  /// Sampling interval, on 14 bits
+  pub interval: u16,
+}
+
+impl Sampling {
+  /// Largest interval the 14 bits of the field can hold
+  pub const MAX_INTERVAL: u16 = 0x3fff;
+
+  /// Whether counters of the records are estimated from a sample of the packets
+  pub fn is_sampled(&self) -> bool {
+    self.interval > 1
+  }
+
+  /// Factor to apply to the counters of the records to estimate the actual traffic
+  ///
+  /// The interval is honoured whatever the mode, since many exporters set it
+  /// while leaving the mode unspecified. An interval of 0 means no sampling.
+  pub fn factor(&self) -> u64 {
+    u64::from(self.interval.max(1))
+  }
+
+  /// Estimates the actual value of a sampled packet or byte counter
+  pub fn upscale(&self, count: u32) -> u64 {
+    u64::from(count) * self.factor()
+  }
+}
+
+impl From<u16> for Sampling {
+  fn from(sampling: u16) -> Sampling {
+    let mode = match sampling >> 14 {
+      0 => SamplingMode::Unspecified,
+      1 => SamplingMode::Deterministic,
+      2 => SamplingMode::Random,
+      _ => SamplingMode::Reserved,
+    };
+    Sampling {
+      mode,
+      interval: sampling & Self::MAX_INTERVAL,
+    }
+  }
+}
+
+impl From<Sampling> for u16 {
+  fn from(sampling: Sampling) -> u16 {
+    let mode = match sampling.mode {
+      SamplingMode::Unspecified => 0,
+      SamplingMode::Deterministic => 1,
+      SamplingMode::Random => 2,
+      SamplingMode::Reserved => 3,
+    };
+    mode << 14 | sampling.interval & Sampling::MAX_INTERVAL
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sampling_from_u16_should_split_mode_and_interval() {
+    assert_eq!(
+      Sampling::from(1000),
+      Sampling {
+        mode: SamplingMode::Unspecified,
+        interval: 1000,
+      }
+    );
+    assert_eq!(
+      Sampling::from(0x4064),
+      Sampling {
+        mode: SamplingMode::Deterministic,
+        interval: 100,
+      }
+    );
+    assert_eq!(Sampling::from(0x8000).mode, SamplingMode::Random);
+    assert_eq!(Sampling::from(0xffff).mode, SamplingMode::Reserved);
+    assert_eq!(Sampling::from(0xffff).interval, Sampling::MAX_INTERVAL);
+  }
+
+  #[test]
+  fn sampling_should_round_trip_to_u16() {
+    for &raw in [0, 1, 1000, 0x4064, 0x8001, 0xffff].iter() {
+      assert_eq!(u16::from(Sampling::from(raw)), raw);
+    }
+  }
+
+  #[test]
+  fn sampling_upscale_should_multiply_by_interval() {
+    assert_eq!(Sampling::from(0).upscale(42), 42);
+    assert_eq!(Sampling::from(1).upscale(42), 42);
+    assert_eq!(Sampling::from(1000).upscale(1708), 1_708_000);
+    assert_eq!(
+      Sampling::from(0x7fff).upscale(u32::MAX),
+      u64::from(u32::MAX) * 0x3fff
+    );
+    assert!(!Sampling::from(0x4001).is_sampled());
+    assert!(Sampling::from(0x4002).is_sampled());
+  }
+}
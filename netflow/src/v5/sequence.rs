@@ -0,0 +1,437 @@
+use super::raw::Header;
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime};
+
+/// Packets arriving later than this after the previous one, or an uptime
+/// drifting further than this from the wall clock, count as a restart
+const MAX_REORDER_MSECS: u32 = 60_000;
+/// Number of recent sequence numbers remembered to tell duplicates from late packets
+const RECENT_LEN: usize = 64;
+/// Packets further behind than the recent ones, of up to 30 flows each,
+/// count as a restart of the sequence
+const MAX_REORDER_FLOWS: u32 = RECENT_LEN as u32 * 30;
+/// Most flows an exporter is assumed to send per second: packets further
+/// ahead than it could have sent since the latest one count as a restart
+const MAX_FLOWS_PER_SEC: u64 = 1_000_000;
+
+/// Key of the sequence counter of an exporter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SequenceKey {
+  /// Address the exporter sends its packets from
+  pub exporter: IpAddr,
+  /// Type of flow-switching engine from the packet header
+  pub engine_type: u8,
+  /// Slot number of the flow-switching engine from the packet header
+  pub engine_id: u8,
+}
+
+/// How a packet relates to the previous packets of the same exporter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceEvent {
+  /// First packet seen from the exporter
+  First,
+  /// The packet follows the previous one
+  InOrder,
+  /// Packets carrying `lost` flows are missing before this one
+  Gap { lost: u32 },
+  /// The packet was already received
+  Duplicate,
+  /// The packet arrived after packets that follow it, and fills part of an earlier gap
+  Reordered,
+  /// The exporter restarted: its uptime went backwards or jumped ahead of
+  /// the wall clock, or its sequence number jumped further back than a late
+  /// packet or further ahead than the flows it could have sent in between, as
+  /// when only the export process restarts
+  Restart,
+}
+
+/// Counters of the packets received from one exporter
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceStats {
+  /// Packets received, including duplicates
+  pub packets: u64,
+  /// Flows received, including duplicates
+  pub flows: u64,
+  /// Flows announced by the sequence numbers that were never received
+  pub lost: u64,
+  pub duplicates: u64,
+  pub reorderings: u64,
+  pub restarts: u64,
+}
+
+#[derive(Debug)]
+struct SequenceState {
+  stats: SequenceStats,
+  /// Sequence number the next packet should carry
+  expected: u32,
+  /// Uptime and export time of the latest packet in sequence
+  sys_uptime_msecs: u32,
+  export_time: SystemTime,
+  recent: VecDeque<u32>,
+}
+
+impl SequenceState {
+  fn new(header: &Header<'_>) -> SequenceState {
+    let mut state = SequenceState {
+      stats: SequenceStats::default(),
+      expected: 0,
+      sys_uptime_msecs: 0,
+      export_time: header.export_time(),
+      recent: VecDeque::with_capacity(RECENT_LEN),
+    };
+    state.restart(header);
+    state
+  }
+
+  fn restart(&mut self, header: &Header<'_>) {
+    self.recent.clear();
+    self.advance(header);
+  }
+
+  fn advance(&mut self, header: &Header<'_>) {
+    self.expected = header
+      .sequence_number()
+      .wrapping_add(u32::from(header.count()));
+    self.sys_uptime_msecs = header.sys_uptime_msecs();
+    self.export_time = header.export_time();
+    self.remember(header.sequence_number());
+  }
+
+  fn remember(&mut self, sequence_number: u32) {
+    if self.recent.len() == RECENT_LEN {
+      self.recent.pop_front();
+    }
+    self.recent.push_back(sequence_number);
+  }
+
+  /// Whether the uptime of `header` cannot follow the one of the latest packet
+  fn is_restart(&self, header: &Header<'_>) -> bool {
+    let uptime = header
+      .sys_uptime_msecs()
+      .wrapping_sub(self.sys_uptime_msecs) as i32;
+    if uptime < 0 {
+      return uptime.unsigned_abs() > MAX_REORDER_MSECS;
+    }
+    // Past 24.8 days of uptime, a reset to 0 reads as a jump forward
+    let elapsed = header
+      .export_time()
+      .duration_since(self.export_time)
+      .unwrap_or_default();
+    Duration::from_millis(u64::from(uptime as u32))
+      > elapsed + Duration::from_millis(u64::from(MAX_REORDER_MSECS))
+  }
+
+  /// Most flows the exporter could have sent between the latest packet and
+  /// `header`, with a second of slack for the resolution of export times
+  fn max_gap(&self, header: &Header<'_>) -> u64 {
+    let elapsed = header
+      .export_time()
+      .duration_since(self.export_time)
+      .unwrap_or_default()
+      + Duration::from_secs(1);
+    (elapsed.as_millis() as u64).saturating_mul(MAX_FLOWS_PER_SEC) / 1000
+  }
+
+  fn observe(&mut self, header: &Header<'_>) -> SequenceEvent {
+    self.stats.packets += 1;
+    self.stats.flows += u64::from(header.count());
+
+    let sequence_number = header.sequence_number();
+    let ahead = sequence_number.wrapping_sub(self.expected) as i32;
+    let jumped = if ahead < 0 {
+      ahead.unsigned_abs() > MAX_REORDER_FLOWS
+    } else {
+      u64::from(ahead as u32) > self.max_gap(header)
+    };
+    if jumped || self.is_restart(header) {
+      self.stats.restarts += 1;
+      self.restart(header);
+      return SequenceEvent::Restart;
+    }
+
+    if ahead < 0 {
+      if self.recent.contains(&sequence_number) {
+        self.stats.duplicates += 1;
+        return SequenceEvent::Duplicate;
+      }
+      self.stats.reorderings += 1;
+      self.stats.lost = self.stats.lost.saturating_sub(u64::from(header.count()));
+      self.remember(sequence_number);
+      return SequenceEvent::Reordered;
+    }
+
+    self.advance(header);
+    if ahead == 0 {
+      return SequenceEvent::InOrder;
+    }
+    self.stats.lost += ahead as u64;
+    SequenceEvent::Gap { lost: ahead as u32 }
+  }
+}
+
+/// Follows the sequence numbers of every exporter to account for lost flows
+///
+/// Each exporter is identified by its address and the engine type and ID of
+/// its packets, as a device with several flow-switching engines keeps one
+/// sequence counter per engine.
+#[derive(Debug, Default)]
+pub struct SequenceTracker {
+  exporters: HashMap<SequenceKey, SequenceState>,
+}
+
+impl SequenceTracker {
+  pub fn new() -> SequenceTracker {
+    SequenceTracker::default()
+  }
+
+  /// Accounts for a packet received from `exporter` and tells how it relates
+  /// to the previous ones
+  pub fn observe(&mut self, exporter: IpAddr, header: &Header<'_>) -> SequenceEvent {
+    let key = SequenceKey {
+      exporter,
+      engine_type: header.engine_type(),
+      engine_id: header.engine_id(),
+    };
+    match self.exporters.get_mut(&key) {
+      Some(state) => state.observe(header),
+      None => {
+        let mut state = SequenceState::new(header);
+        state.stats.packets += 1;
+        state.stats.flows += u64::from(header.count());
+        self.exporters.insert(key, state);
+        SequenceEvent::First
+      }
+    }
+  }
+
+  pub fn get(&self, exporter: IpAddr, engine_type: u8, engine_id: u8) -> Option<&SequenceStats> {
+    let key = SequenceKey {
+      exporter,
+      engine_type,
+      engine_id,
+    };
+    self.exporters.get(&key).map(|state| &state.stats)
+  }
+
+  /// Counters of every exporter seen
+  pub fn iter(&self) -> impl Iterator<Item = (&SequenceKey, &SequenceStats)> {
+    self
+      .exporters
+      .iter()
+      .map(|(key, state)| (key, &state.stats))
+  }
+
+  /// Forgets every engine of `exporter`
+  pub fn remove_exporter(&mut self, exporter: IpAddr) {
+    self.exporters.retain(|key, _| key.exporter != exporter);
+  }
+
+  pub fn len(&self) -> usize {
+    self.exporters.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.exporters.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::get_flow_packet_header;
+  use super::*;
+  use byteorder::{ByteOrder, NetworkEndian};
+  use std::net::Ipv4Addr;
+
+  const EXPORTER: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+  /// Header of a packet of `count` flows sent `msecs` after the device booted
+  fn header(sequence_number: u32, count: u16, msecs: u64) -> Vec<u8> {
+    let mut data = get_flow_packet_header().to_vec();
+    NetworkEndian::write_u16(&mut data[2..4], count);
+    NetworkEndian::write_u32(&mut data[4..8], msecs as u32);
+    NetworkEndian::write_u32(&mut data[8..12], 1_500_000_000 + (msecs / 1000) as u32);
+    NetworkEndian::write_u32(&mut data[12..16], (msecs % 1000) as u32 * 1_000_000);
+    NetworkEndian::write_u32(&mut data[16..20], sequence_number);
+    data
+  }
+
+  fn observe(tracker: &mut SequenceTracker, data: &[u8]) -> SequenceEvent {
+    tracker.observe(EXPORTER, &Header::new(data).unwrap())
+  }
+
+  #[test]
+  fn tracker_should_count_lost_flows() {
+    let mut tracker = SequenceTracker::new();
+
+    assert_eq!(
+      observe(&mut tracker, &header(100, 30, 1000)),
+      SequenceEvent::First
+    );
+    assert_eq!(
+      observe(&mut tracker, &header(130, 30, 2000)),
+      SequenceEvent::InOrder
+    );
+    assert_eq!(
+      observe(&mut tracker, &header(190, 10, 3000)),
+      SequenceEvent::Gap { lost: 30 }
+    );
+    assert_eq!(
+      observe(&mut tracker, &header(200, 5, 4000)),
+      SequenceEvent::InOrder
+    );
+
+    let stats = tracker.get(EXPORTER, 0, 0).unwrap();
+    assert_eq!(stats.packets, 4);
+    assert_eq!(stats.flows, 75);
+    assert_eq!(stats.lost, 30);
+  }
+
+  #[test]
+  fn tracker_should_tell_duplicates_from_reorderings() {
+    let mut tracker = SequenceTracker::new();
+
+    observe(&mut tracker, &header(0, 10, 1000));
+    assert_eq!(
+      observe(&mut tracker, &header(20, 10, 1200)),
+      SequenceEvent::Gap { lost: 10 }
+    );
+    assert_eq!(
+      observe(&mut tracker, &header(10, 10, 1100)),
+      SequenceEvent::Reordered
+    );
+    assert_eq!(
+      observe(&mut tracker, &header(10, 10, 1100)),
+      SequenceEvent::Duplicate
+    );
+    assert_eq!(
+      observe(&mut tracker, &header(20, 10, 1200)),
+      SequenceEvent::Duplicate
+    );
+    assert_eq!(
+      observe(&mut tracker, &header(30, 10, 1300)),
+      SequenceEvent::InOrder
+    );
+
+    let stats = tracker.get(EXPORTER, 0, 0).unwrap();
+    assert_eq!(stats.lost, 0);
+    assert_eq!(stats.reorderings, 1);
+    assert_eq!(stats.duplicates, 2);
+  }
+
+  #[test]
+  fn tracker_should_detect_restart_when_uptime_goes_backwards() {
+    let mut tracker = SequenceTracker::new();
+
+    observe(&mut tracker, &header(5000, 10, 3_600_000));
+    assert_eq!(
+      observe(&mut tracker, &header(0, 10, 500)),
+      SequenceEvent::Restart
+    );
+    assert_eq!(
+      observe(&mut tracker, &header(10, 10, 1500)),
+      SequenceEvent::InOrder
+    );
+
+    let stats = tracker.get(EXPORTER, 0, 0).unwrap();
+    assert_eq!(stats.restarts, 1);
+    assert_eq!(stats.lost, 0);
+  }
+
+  #[test]
+  fn tracker_should_detect_restart_after_long_uptime() {
+    let mut tracker = SequenceTracker::new();
+    let day = 24 * 3_600_000;
+
+    observe(&mut tracker, &header(5000, 10, 40 * day));
+    let mut data = header(0, 10, 500);
+    NetworkEndian::write_u32(
+      &mut data[8..12],
+      1_500_000_000 + (40 * day / 1000) as u32 + 1,
+    );
+
+    assert_eq!(observe(&mut tracker, &data), SequenceEvent::Restart);
+  }
+
+  #[test]
+  fn tracker_should_detect_sequence_reset_with_continuous_uptime() {
+    let mut tracker = SequenceTracker::new();
+
+    observe(&mut tracker, &header(5_000_000, 1, 1000));
+    observe(&mut tracker, &header(5_000_001, 1, 1100));
+    observe(&mut tracker, &header(5_000_002, 1, 1200));
+    assert_eq!(
+      observe(&mut tracker, &header(0, 1, 1300)),
+      SequenceEvent::Restart
+    );
+    for sequence_number in 1..5 {
+      assert_eq!(
+        observe(
+          &mut tracker,
+          &header(sequence_number, 1, 1300 + u64::from(sequence_number))
+        ),
+        SequenceEvent::InOrder
+      );
+    }
+    assert_eq!(
+      observe(&mut tracker, &header(3_000_000, 1, 1400)),
+      SequenceEvent::Restart
+    );
+
+    let stats = tracker.get(EXPORTER, 0, 0).unwrap();
+    assert_eq!(stats.restarts, 2);
+    assert_eq!(stats.reorderings, 0);
+    assert_eq!(stats.lost, 0);
+  }
+
+  #[test]
+  fn tracker_should_count_large_gaps_with_continuous_uptime() {
+    let mut tracker = SequenceTracker::new();
+
+    // 5 seconds of loss at 200k flows/s
+    observe(&mut tracker, &header(0, 30, 1000));
+    assert_eq!(
+      observe(&mut tracker, &header(1_000_030, 30, 6000)),
+      SequenceEvent::Gap { lost: 1_000_000 }
+    );
+    assert_eq!(
+      observe(&mut tracker, &header(1_000_060, 30, 6010)),
+      SequenceEvent::InOrder
+    );
+
+    let stats = tracker.get(EXPORTER, 0, 0).unwrap();
+    assert_eq!(stats.restarts, 0);
+    assert_eq!(stats.lost, 1_000_000);
+  }
+
+  #[test]
+  fn tracker_should_follow_uptime_wraparound() {
+    let mut tracker = SequenceTracker::new();
+    let wrap = 1 << 32;
+
+    observe(&mut tracker, &header(u32::MAX - 4, 5, wrap - 100));
+    assert_eq!(
+      observe(&mut tracker, &header(0, 5, wrap + 100)),
+      SequenceEvent::InOrder
+    );
+    assert_eq!(tracker.get(EXPORTER, 0, 0).unwrap().restarts, 0);
+  }
+
+  #[test]
+  fn tracker_should_key_by_engine() {
+    let mut tracker = SequenceTracker::new();
+    let mut other_engine = header(0, 10, 1000);
+    other_engine[21] = 1;
+
+    observe(&mut tracker, &header(1000, 10, 1000));
+    assert_eq!(observe(&mut tracker, &other_engine), SequenceEvent::First);
+    assert_eq!(
+      observe(&mut tracker, &header(1010, 10, 2000)),
+      SequenceEvent::InOrder
+    );
+    assert_eq!(tracker.len(), 2);
+
+    tracker.remove_exporter(EXPORTER);
+    assert!(tracker.is_empty());
+  }
+}
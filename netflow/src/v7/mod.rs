@@ -0,0 +1,23 @@
+pub mod raw;
+
+// Based on https://www.cisco.com/c/en/us/td/docs/net_mgmt/netflow_collection_engine/3-6/user/guide/format.html#wp1006186
+
+#[cfg(test)]
+pub(crate) mod tests {
+  pub const FLOW_PACKET_1: [u8; 128] = [
+    0x00, 0x07, 0x00, 0x02, 0x00, 0x2e, 0x1c, 0x6b, 0x5c, 0x0e, 0xe5, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x01, 0xcc, 0x55, 0x00, 0x00, 0x00, 0x00, 0xac, 0x10, 0x05, 0x0a, 0xac, 0x10, 0x09, 0x2c,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x0e, 0x00, 0x16, 0x00, 0x00, 0x00, 0x39, 0x00, 0x01, 0x3d, 0x58,
+    0x00, 0x2d, 0x31, 0xf6, 0x00, 0x2e, 0x1b, 0x99, 0x01, 0xbb, 0xea, 0xd0, 0x00, 0x1a, 0x06, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00, 0xac, 0x10, 0x00, 0x02, 0xac, 0x10, 0x09, 0x2c,
+    0xac, 0x10, 0x05, 0x0a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x16, 0x00, 0x0e, 0x00, 0x00, 0x00, 0x29,
+    0x00, 0x00, 0x0c, 0xf8, 0x00, 0x2d, 0x32, 0x0b, 0x00, 0x2e, 0x1b, 0x94, 0xea, 0xd0, 0x01, 0xbb,
+    0x00, 0x1b, 0x06, 0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00, 0xac, 0x10, 0x00, 0x02,
+  ];
+  pub fn get_flow_packet_header() -> &'static [u8] {
+    &FLOW_PACKET_1[0..24]
+  }
+  pub fn get_flow_packet_records() -> &'static [u8] {
+    &FLOW_PACKET_1[24..]
+  }
+}
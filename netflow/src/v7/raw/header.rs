@@ -0,0 +1,125 @@
+use super::{Error, Protocol};
+use byteorder::{ByteOrder, NetworkEndian};
+
+/// A Netflow v7 header helper struct
+#[derive(PartialEq)]
+pub struct Header<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for Header<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Header {{ version: {}, count: {}, sys_uptime_msecs: {}, unix_secs: {}, unix_nsecs: {}, sequence_number: {} }}",
+        self.version(), self.count(), self.sys_uptime_msecs(), self.unix_secs(), self.unix_nsecs(), self.sequence_number())
+  }
+}
+
+impl<'a> Header<'a> {
+  pub const LEN: usize = 24;
+  pub const VERSION: u16 = 7;
+
+  pub fn new(data: &'a [u8]) -> Result<Header<'a>, Error> {
+    if data.len() < Self::LEN {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::V7,
+        offset: 0,
+        expected: Self::LEN,
+        actual: data.len(),
+      });
+    }
+    let version = NetworkEndian::read_u16(&data[0..2]);
+    if version != Self::VERSION {
+      return Err(Error::InvalidVersion {
+        protocol: Protocol::V7,
+        version,
+      });
+    }
+
+    Ok(Header { data })
+  }
+
+  /// NetFlow export format version number
+  pub fn version(&self) -> u16 {
+    Self::VERSION
+  }
+
+  /// Number of flows that are exported in this packet (1-27)
+  pub fn count(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[2..4]))
+  }
+
+  /// Current time in milliseconds since the export device started
+  pub fn sys_uptime_msecs(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[4..8]))
+  }
+
+  /// Current time in seconds since 0000 Coordinated Universal Time 1970
+  pub fn unix_secs(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[8..12]))
+  }
+
+  /// Residual nanoseconds since 0000 Coordinated Universal Time 1970
+  pub fn unix_nsecs(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[12..16]))
+  }
+
+  /// Sequence counter of total flows seen
+  pub fn sequence_number(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[16..20]))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::get_flow_packet_header;
+  use super::*;
+
+  #[test]
+  fn header_new_should_succeed_with_valid_data() {
+    assert!(Header::new(get_flow_packet_header()).is_ok());
+  }
+
+  #[test]
+  fn header_new_should_fail_with_not_enough_data() {
+    assert_eq!(
+      Header::new(&get_flow_packet_header()[..Header::LEN - 1]),
+      Err(Error::NotEnoughData {
+        protocol: Protocol::V7,
+        offset: 0,
+        expected: Header::LEN,
+        actual: Header::LEN - 1,
+      })
+    );
+  }
+
+  #[test]
+  fn header_new_should_fail_with_invalid_version() {
+    let mut data = get_flow_packet_header().to_vec();
+    data[1] = 5;
+
+    assert_eq!(
+      Header::new(&data),
+      Err(Error::InvalidVersion {
+        protocol: Protocol::V7,
+        version: 5,
+      })
+    );
+  }
+
+  #[test]
+  fn header_accessors_expose_fields() {
+    let header = Header::new(get_flow_packet_header()).unwrap();
+
+    assert_eq!(header.version(), 7);
+    assert_eq!(header.count(), 2);
+    assert_eq!(header.sys_uptime_msecs(), 3021931);
+    assert_eq!(header.unix_secs(), 1544480000);
+    assert_eq!(header.unix_nsecs(), 0);
+    assert_eq!(header.sequence_number(), 117845);
+  }
+
+  #[test]
+  fn header_implements_debug() {
+    println!("{:?}", Header::new(get_flow_packet_header()).unwrap());
+  }
+}
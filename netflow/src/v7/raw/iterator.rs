@@ -0,0 +1,39 @@
+use super::Record;
+use crate::fixed::Records;
+use std::iter::FusedIterator;
+
+/// Iterator over the records of a packet, borrowing the packet data rather
+/// than its `FlowSet`
+#[derive(Clone)]
+pub struct RecordIterator<'a> {
+  pub(crate) records: Records<'a>,
+}
+
+impl<'a> Iterator for RecordIterator<'a> {
+  type Item = Record<'a>;
+
+  fn next(&mut self) -> Option<Record<'a>> {
+    let data = self.records.next()?;
+    Some(Record { data })
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.records.size_hint()
+  }
+
+  fn nth(&mut self, n: usize) -> Option<Record<'a>> {
+    let data = self.records.nth(n)?;
+    Some(Record { data })
+  }
+}
+
+impl<'a> DoubleEndedIterator for RecordIterator<'a> {
+  fn next_back(&mut self) -> Option<Record<'a>> {
+    let data = self.records.next_back()?;
+    Some(Record { data })
+  }
+}
+
+impl<'a> ExactSizeIterator for RecordIterator<'a> {}
+
+impl<'a> FusedIterator for RecordIterator<'a> {}
@@ -0,0 +1,288 @@
+use super::{Error, Protocol};
+use crate::Flow;
+use byteorder::{ByteOrder, NetworkEndian};
+use std::net::IpAddr;
+
+/// A Netflow v7 record helper struct
+#[derive(PartialEq)]
+pub struct Record<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for Record<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Record {{ source_addr: {}, destination_addr: {}, next_hop: {}, input: {}, output: {}, packets: {}, bytes: {}, first_packet_sys_uptime: {}, last_packet_sys_uptime: {}, source_port: {}, destination_port: {}, flags: {}, tcp_flags: {}, protocol: {}, type_of_service: {}, source_as: {}, destination_as: {}, source_mask: {}, destination_mask: {}, router_sc: {} }}",
+        self.source_addr(), self.destination_addr(), self.next_hop(), self.input(), self.output(), self.packets(), self.bytes(), self.first_packet_sys_uptime(), self.last_packet_sys_uptime(), self.source_port(), self.destination_port(), self.flags(), self.tcp_flags(), self.protocol(), self.type_of_service(), self.source_as(), self.destination_as(), self.source_mask(), self.destination_mask(), self.router_sc())
+  }
+}
+
+impl<'a> Record<'a> {
+  pub const LEN: usize = 52;
+  pub fn new(data: &'a [u8]) -> Result<Record<'a>, Error> {
+    if data.len() < Self::LEN {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::V7,
+        offset: 0,
+        expected: Self::LEN,
+        actual: data.len(),
+      });
+    }
+
+    Ok(Record { data })
+  }
+
+  /// Source IP address
+  pub fn source_addr(&self) -> std::net::IpAddr {
+    std::net::IpAddr::V4(std::net::Ipv4Addr::new(
+      self.data[0],
+      self.data[1],
+      self.data[2],
+      self.data[3],
+    ))
+  }
+
+  /// Destination IP address
+  pub fn destination_addr(&self) -> std::net::IpAddr {
+    std::net::IpAddr::V4(std::net::Ipv4Addr::new(
+      self.data[4],
+      self.data[5],
+      self.data[6],
+      self.data[7],
+    ))
+  }
+
+  /// IP address of next hop router
+  pub fn next_hop(&self) -> std::net::IpAddr {
+    std::net::IpAddr::V4(std::net::Ipv4Addr::new(
+      self.data[8],
+      self.data[9],
+      self.data[10],
+      self.data[11],
+    ))
+  }
+
+  /// SNMP index of input interface
+  pub fn input(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[12..14]))
+  }
+
+  /// SNMP index of output interface
+  pub fn output(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[14..16]))
+  }
+
+  /// Packets in the flow
+  pub fn packets(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[16..20]))
+  }
+
+  /// Total number of Layer 3 bytes in the packets of the flow
+  pub fn bytes(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[20..24]))
+  }
+
+  /// SysUptime at start of flow
+  pub fn first_packet_sys_uptime(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[24..28]))
+  }
+
+  /// SysUptime at the time the last packet of the flow was received
+  pub fn last_packet_sys_uptime(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[28..32]))
+  }
+
+  /// TCP or UDP source port number or equivalent
+  pub fn source_port(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[32..34]))
+  }
+
+  /// TCP or UDP destination port number or equivalent
+  pub fn destination_port(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[34..36]))
+  }
+
+  /// Flags indicating, among other things, what flows are invalid
+  pub fn flags(&self) -> u8 {
+    self.data[36]
+  }
+
+  /// Cumulative OR of TCP flags
+  pub fn tcp_flags(&self) -> u8 {
+    self.data[37]
+  }
+
+  /// IP protocol type (for example, TCP = 6, UDP = 17, ...)
+  pub fn protocol(&self) -> u8 {
+    self.data[38]
+  }
+
+  /// IP type of service (ToS)
+  pub fn type_of_service(&self) -> u8 {
+    self.data[39]
+  }
+
+  /// Autonomous system number of the source, either origin or peer
+  pub fn source_as(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[40..42]))
+  }
+
+  /// Autonomous system number of the destination, either origin or peer
+  pub fn destination_as(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[42..44]))
+  }
+
+  /// Source address prefix mask bits
+  pub fn source_mask(&self) -> u8 {
+    self.data[44]
+  }
+
+  /// Destination address prefix mask bits
+  pub fn destination_mask(&self) -> u8 {
+    self.data[45]
+  }
+
+  /// IP address of the router that is bypassed by the Catalyst 5000 series switch
+  pub fn router_sc(&self) -> std::net::IpAddr {
+    std::net::IpAddr::V4(std::net::Ipv4Addr::new(
+      self.data[48],
+      self.data[49],
+      self.data[50],
+      self.data[51],
+    ))
+  }
+}
+
+impl<'a> Flow for Record<'a> {
+  fn source_addr(&self) -> Option<IpAddr> {
+    Some(self.source_addr())
+  }
+
+  fn destination_addr(&self) -> Option<IpAddr> {
+    Some(self.destination_addr())
+  }
+
+  fn source_port(&self) -> Option<u16> {
+    Some(self.source_port())
+  }
+
+  fn destination_port(&self) -> Option<u16> {
+    Some(self.destination_port())
+  }
+
+  fn protocol(&self) -> Option<u8> {
+    Some(self.protocol())
+  }
+
+  fn packets(&self) -> Option<u64> {
+    Some(u64::from(self.packets()))
+  }
+
+  fn bytes(&self) -> Option<u64> {
+    Some(u64::from(self.bytes()))
+  }
+
+  fn input(&self) -> Option<u32> {
+    Some(u32::from(self.input()))
+  }
+
+  fn output(&self) -> Option<u32> {
+    Some(u32::from(self.output()))
+  }
+
+  fn next_hop(&self) -> Option<IpAddr> {
+    Some(self.next_hop())
+  }
+
+  fn source_as(&self) -> Option<u32> {
+    Some(u32::from(self.source_as()))
+  }
+
+  fn destination_as(&self) -> Option<u32> {
+    Some(u32::from(self.destination_as()))
+  }
+
+  fn source_mask(&self) -> Option<u8> {
+    Some(self.source_mask())
+  }
+
+  fn destination_mask(&self) -> Option<u8> {
+    Some(self.destination_mask())
+  }
+
+  fn type_of_service(&self) -> Option<u8> {
+    Some(self.type_of_service())
+  }
+
+  fn tcp_flags(&self) -> Option<u8> {
+    Some(self.tcp_flags())
+  }
+
+  fn first_packet_sys_uptime(&self) -> Option<u32> {
+    Some(self.first_packet_sys_uptime())
+  }
+
+  fn last_packet_sys_uptime(&self) -> Option<u32> {
+    Some(self.last_packet_sys_uptime())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::get_flow_packet_records;
+  use super::*;
+
+  #[test]
+  fn record_new_should_succeed_with_valid_data() {
+    assert!(Record::new(get_flow_packet_records()).is_ok());
+  }
+
+  #[test]
+  fn record_new_should_fail_with_not_enough_data() {
+    assert_eq!(
+      Record::new(&get_flow_packet_records()[..Record::LEN - 1]),
+      Err(Error::NotEnoughData {
+        protocol: Protocol::V7,
+        offset: 0,
+        expected: Record::LEN,
+        actual: Record::LEN - 1,
+      })
+    );
+  }
+
+  #[test]
+  fn record_accessors_expose_fields() {
+    let record = Record::new(get_flow_packet_records()).unwrap();
+
+    assert_eq!(
+      record.source_addr(),
+      std::net::Ipv4Addr::new(172, 16, 5, 10)
+    );
+    assert_eq!(
+      record.destination_addr(),
+      std::net::Ipv4Addr::new(172, 16, 9, 44)
+    );
+    assert_eq!(record.next_hop(), std::net::Ipv4Addr::new(0, 0, 0, 0));
+    assert_eq!(record.input(), 14);
+    assert_eq!(record.output(), 22);
+    assert_eq!(record.packets(), 57);
+    assert_eq!(record.bytes(), 81240);
+    assert_eq!(record.first_packet_sys_uptime(), 2961910);
+    assert_eq!(record.last_packet_sys_uptime(), 3021721);
+    assert_eq!(record.source_port(), 443);
+    assert_eq!(record.destination_port(), 60112);
+    assert_eq!(record.flags(), 0);
+    assert_eq!(record.tcp_flags(), 0x1a);
+    assert_eq!(record.protocol(), 6);
+    assert_eq!(record.type_of_service(), 0);
+    assert_eq!(record.source_as(), 0);
+    assert_eq!(record.destination_as(), 0);
+    assert_eq!(record.source_mask(), 24);
+    assert_eq!(record.destination_mask(), 24);
+    assert_eq!(record.router_sc(), std::net::Ipv4Addr::new(172, 16, 0, 2));
+  }
+
+  #[test]
+  fn record_implements_debug() {
+    println!("{:?}", Record::new(get_flow_packet_records()).unwrap());
+  }
+}
@@ -0,0 +1,168 @@
+use super::{Error, Protocol};
+use crate::fixed::Layout;
+
+use super::{Header, Record, RecordIterator};
+
+/// A Netflow v7 packet helper struct
+#[derive(PartialEq)]
+pub struct FlowSet<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) count: usize,
+}
+
+impl<'a> std::fmt::Debug for FlowSet<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("FlowSet")
+      .field("header", &self.header())
+      .field("records", &self.records().collect::<Vec<_>>())
+      .finish()
+  }
+}
+
+impl<'a> FlowSet<'a> {
+  /// Minimum number of flows announced by a valid packet
+  pub const MIN_COUNT: u16 = 1;
+  /// Maximum number of flows announced by a valid packet
+  pub const MAX_COUNT: u16 = 27;
+
+  const LAYOUT: Layout = Layout {
+    protocol: Protocol::V7,
+    header_len: Header::LEN,
+    record_len: Record::LEN,
+    min_count: Self::MIN_COUNT,
+    max_count: Self::MAX_COUNT,
+  };
+
+  /// Parses a packet, checking that its header announces 1-27 flows and that
+  /// `data` is long enough to hold all of them
+  pub fn new(data: &'a [u8]) -> Result<FlowSet<'a>, Error> {
+    let count = Header::new(data)?.count();
+    Ok(FlowSet {
+      data,
+      count: Self::LAYOUT.check_count(data, count)?,
+    })
+  }
+
+  /// Parses a packet without trusting its flow count: only the complete
+  /// records that fit in `data` are exposed
+  pub fn new_lenient(data: &'a [u8]) -> Result<FlowSet<'a>, Error> {
+    let announced = Header::new(data)?.count();
+    Ok(FlowSet {
+      data,
+      count: Self::LAYOUT.lenient_count(data, announced),
+    })
+  }
+
+  pub fn header(&self) -> Header<'a> {
+    Header { data: self.data }
+  }
+
+  /// Records exposed by this set, borrowing the packet data rather than the
+  /// set itself
+  pub fn records(&self) -> RecordIterator<'a> {
+    RecordIterator {
+      records: Self::LAYOUT.records(self.data, self.count),
+    }
+  }
+
+  /// Record at `index`, if exposed by this set
+  pub fn record(&self, index: usize) -> Option<Record<'a>> {
+    let data = Self::LAYOUT.record(self.data, self.count, index)?;
+    Some(Record { data })
+  }
+
+  /// Number of records exposed by this set
+  pub fn len(&self) -> usize {
+    self.count
+  }
+
+  /// Whether this set exposes no record at all
+  pub fn is_empty(&self) -> bool {
+    self.count == 0
+  }
+
+  /// Whether the header announces more records than the packet holds
+  pub fn is_truncated(&self) -> bool {
+    self.count < self.header().count() as usize
+  }
+}
+
+impl<'a> IntoIterator for &FlowSet<'a> {
+  type Item = Record<'a>;
+  type IntoIter = RecordIterator<'a>;
+
+  fn into_iter(self) -> RecordIterator<'a> {
+    self.records()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::FLOW_PACKET_1;
+  use super::*;
+
+  fn with_count(count: u16) -> Vec<u8> {
+    let mut data = FLOW_PACKET_1.to_vec();
+    data[2..4].copy_from_slice(&count.to_be_bytes());
+    data
+  }
+
+  #[test]
+  fn set_new_should_expose_every_record() {
+    let set = FlowSet::new(&FLOW_PACKET_1).unwrap();
+
+    assert_eq!(set.len(), 2);
+    assert_eq!(set.records().count(), 2);
+    assert_eq!(set.records().len(), 2);
+    assert_eq!(set.records().next_back(), set.record(1));
+    assert_eq!(set.record(2), None);
+    assert_eq!((&set).into_iter().count(), 2);
+    assert!(!set.is_truncated());
+    println!("{:?}", set);
+  }
+
+  #[test]
+  fn set_new_should_fail_with_invalid_count() {
+    for count in [0, 28].iter().cloned() {
+      assert_eq!(
+        FlowSet::new(&with_count(count)),
+        Err(Error::InvalidCount {
+          protocol: Protocol::V7,
+          count,
+        })
+      );
+    }
+  }
+
+  #[test]
+  fn set_new_should_fail_with_truncated_data() {
+    let data = &FLOW_PACKET_1[..Header::LEN + Record::LEN + 10];
+
+    assert_eq!(
+      FlowSet::new(data),
+      Err(Error::Truncated {
+        protocol: Protocol::V7,
+        count: 2,
+        expected: Header::LEN + 2 * Record::LEN,
+        actual: data.len(),
+      })
+    );
+  }
+
+  #[test]
+  fn set_new_lenient_should_recover_complete_records() {
+    let data = &FLOW_PACKET_1[..Header::LEN + Record::LEN + 10];
+    let set = FlowSet::new_lenient(data).unwrap();
+
+    assert_eq!(set.len(), 1);
+    assert!(set.is_truncated());
+    assert_eq!(
+      set.records().collect::<Vec<_>>(),
+      FlowSet::new(&FLOW_PACKET_1)
+        .unwrap()
+        .records()
+        .take(1)
+        .collect::<Vec<_>>()
+    );
+  }
+}
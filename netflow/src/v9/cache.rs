@@ -0,0 +1,110 @@
+use super::raw::Packet;
+use super::Template;
+use crate::Error;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+/// Key of a template announced by an exporter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TemplateKey {
+  /// Address the exporter sends its packets from
+  pub exporter: IpAddr,
+  /// Source ID from the packet header
+  pub source_id: u32,
+  /// ID of the template
+  pub template_id: u16,
+}
+
+/// Templates announced by every exporter, needed to decode their data flowsets
+#[derive(Debug, Default)]
+pub struct TemplateCache {
+  templates: HashMap<TemplateKey, Template>,
+}
+
+impl TemplateCache {
+  pub fn new() -> TemplateCache {
+    TemplateCache::default()
+  }
+
+  /// Stores a template, returning the one it replaces if any
+  pub fn insert(
+    &mut self,
+    exporter: IpAddr,
+    source_id: u32,
+    template: Template,
+  ) -> Option<Template> {
+    let key = TemplateKey {
+      exporter,
+      source_id,
+      template_id: template.id,
+    };
+    self.templates.insert(key, template)
+  }
+
+  pub fn get(&self, exporter: IpAddr, source_id: u32, template_id: u16) -> Option<&Template> {
+    self.templates.get(&TemplateKey {
+      exporter,
+      source_id,
+      template_id,
+    })
+  }
+
+  /// Stores every template and options template of a packet sent by `exporter`,
+  /// returning how many were stored
+  ///
+  /// Templates found before an invalid flowset or record are kept.
+  pub fn learn(&mut self, exporter: IpAddr, packet: &Packet) -> Result<usize, Error> {
+    let source_id = packet.header().source_id();
+    let mut learnt = 0;
+    for set in packet.flowsets() {
+      let set = set?;
+      for template in set.templates() {
+        self.insert(exporter, source_id, Template::from(&template?));
+        learnt += 1;
+      }
+      for template in set.options_templates() {
+        self.insert(exporter, source_id, Template::from(&template?));
+        learnt += 1;
+      }
+    }
+    Ok(learnt)
+  }
+
+  /// Forgets every template announced by `exporter`, for example after it restarted
+  pub fn remove_exporter(&mut self, exporter: IpAddr) {
+    self.templates.retain(|key, _| key.exporter != exporter);
+  }
+
+  pub fn len(&self) -> usize {
+    self.templates.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.templates.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::FLOW_PACKET_1;
+  use super::*;
+  use std::net::Ipv4Addr;
+
+  const EXPORTER: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+  #[test]
+  fn cache_learn_should_store_templates_per_exporter() {
+    let mut cache = TemplateCache::new();
+    let packet = Packet::new(&FLOW_PACKET_1).unwrap();
+
+    assert_eq!(cache.learn(EXPORTER, &packet), Ok(2));
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(EXPORTER, 1, 256).unwrap().fields.len(), 10);
+    assert!(cache.get(EXPORTER, 1, 258).unwrap().is_options());
+    assert_eq!(cache.get(EXPORTER, 2, 256), None);
+    assert_eq!(cache.get(IpAddr::V4(Ipv4Addr::LOCALHOST), 1, 256), None);
+
+    cache.remove_exporter(EXPORTER);
+    assert!(cache.is_empty());
+  }
+}
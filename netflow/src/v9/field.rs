@@ -0,0 +1,203 @@
+use crate::value;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+pub use crate::value::MplsLabel;
+
+/// Field type values defined by RFC 3954
+pub mod field_type {
+  /// Incoming counter for the number of bytes associated with an IP flow
+  pub const IN_BYTES: u16 = 1;
+  /// Incoming counter for the number of packets associated with an IP flow
+  pub const IN_PKTS: u16 = 2;
+  /// Number of flows that were aggregated
+  pub const FLOWS: u16 = 3;
+  /// IP protocol byte
+  pub const PROTOCOL: u16 = 4;
+  /// Type of Service byte setting when entering the incoming interface
+  pub const SRC_TOS: u16 = 5;
+  /// Cumulative of all the TCP flags seen for this flow
+  pub const TCP_FLAGS: u16 = 6;
+  /// TCP/UDP source port number
+  pub const L4_SRC_PORT: u16 = 7;
+  /// IPv4 source address
+  pub const IPV4_SRC_ADDR: u16 = 8;
+  /// The number of contiguous bits in the source address subnet mask
+  pub const SRC_MASK: u16 = 9;
+  /// Input interface index
+  pub const INPUT_SNMP: u16 = 10;
+  /// TCP/UDP destination port number
+  pub const L4_DST_PORT: u16 = 11;
+  /// IPv4 destination address
+  pub const IPV4_DST_ADDR: u16 = 12;
+  /// The number of contiguous bits in the destination address subnet mask
+  pub const DST_MASK: u16 = 13;
+  /// Output interface index
+  pub const OUTPUT_SNMP: u16 = 14;
+  /// IPv4 address of next-hop router
+  pub const IPV4_NEXT_HOP: u16 = 15;
+  /// Source BGP autonomous system number
+  pub const SRC_AS: u16 = 16;
+  /// Destination BGP autonomous system number
+  pub const DST_AS: u16 = 17;
+  /// Next-hop router's IP in the BGP domain
+  pub const BGP_IPV4_NEXT_HOP: u16 = 18;
+  /// System uptime at which the last packet of this flow was switched
+  pub const LAST_SWITCHED: u16 = 21;
+  /// System uptime at which the first packet of this flow was switched
+  pub const FIRST_SWITCHED: u16 = 22;
+  /// Outgoing counter for the number of bytes associated with an IP flow
+  pub const OUT_BYTES: u16 = 23;
+  /// Outgoing counter for the number of packets associated with an IP flow
+  pub const OUT_PKTS: u16 = 24;
+  /// IPv6 source address
+  pub const IPV6_SRC_ADDR: u16 = 27;
+  /// IPv6 destination address
+  pub const IPV6_DST_ADDR: u16 = 28;
+  /// Length of the IPv6 source mask in contiguous bits
+  pub const IPV6_SRC_MASK: u16 = 29;
+  /// Length of the IPv6 destination mask in contiguous bits
+  pub const IPV6_DST_MASK: u16 = 30;
+  /// IPv6 flow label
+  pub const IPV6_FLOW_LABEL: u16 = 31;
+  /// Internet Control Message Protocol packet type, coded as (type * 256 + code)
+  pub const ICMP_TYPE: u16 = 32;
+  /// When using sampled NetFlow, the rate at which packets are sampled
+  pub const SAMPLING_INTERVAL: u16 = 34;
+  /// The type of algorithm used for sampled NetFlow
+  pub const SAMPLING_ALGORITHM: u16 = 35;
+  /// Incoming source MAC address
+  pub const IN_SRC_MAC: u16 = 56;
+  /// Outgoing destination MAC address
+  pub const OUT_DST_MAC: u16 = 57;
+  /// Virtual LAN identifier associated with ingress interface
+  pub const SRC_VLAN: u16 = 58;
+  /// Virtual LAN identifier associated with egress interface
+  pub const DST_VLAN: u16 = 59;
+  /// Internet Protocol Version
+  pub const IP_PROTOCOL_VERSION: u16 = 60;
+  /// Flow direction: 0 - ingress flow, 1 - egress flow
+  pub const DIRECTION: u16 = 61;
+  /// IPv6 address of the next-hop router
+  pub const IPV6_NEXT_HOP: u16 = 62;
+  /// First MPLS label in the label stack
+  pub const MPLS_LABEL_1: u16 = 70;
+  /// Tenth MPLS label in the label stack
+  pub const MPLS_LABEL_10: u16 = 79;
+}
+
+/// Scope field type values of options templates defined by RFC 3954
+pub mod scope_type {
+  pub const SYSTEM: u16 = 1;
+  pub const INTERFACE: u16 = 2;
+  pub const LINE_CARD: u16 = 3;
+  pub const CACHE: u16 = 4;
+  pub const TEMPLATE: u16 = 5;
+}
+
+/// Type and length of a field, as announced by a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldSpec {
+  pub field_type: u16,
+  pub length: u16,
+}
+
+impl FieldSpec {
+  /// Length of a field specifier in a template record
+  pub const LEN: usize = 4;
+}
+
+/// A field value of a data record helper struct
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Field<'a> {
+  pub(crate) field_type: u16,
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> Field<'a> {
+  /// Type of the field, see `field_type` and `scope_type`
+  pub fn field_type(&self) -> u16 {
+    self.field_type
+  }
+
+  /// Raw bytes of the field
+  pub fn data(&self) -> &'a [u8] {
+    self.data
+  }
+
+  /// Value of an unsigned integer field (counters, ports, indexes, timestamps...) of 1 to 8 bytes
+  pub fn as_u64(&self) -> Option<u64> {
+    value::read_u64(self.data)
+  }
+
+  /// Value of a 4-byte address field
+  pub fn as_ipv4(&self) -> Option<Ipv4Addr> {
+    value::read_ipv4(self.data)
+  }
+
+  /// Value of a 16-byte address field
+  pub fn as_ipv6(&self) -> Option<Ipv6Addr> {
+    value::read_ipv6(self.data)
+  }
+
+  /// Value of a 4-byte or 16-byte address field
+  pub fn as_ip_addr(&self) -> Option<IpAddr> {
+    value::read_ip_addr(self.data)
+  }
+
+  /// Value of a 6-byte MAC address field
+  pub fn as_mac_addr(&self) -> Option<[u8; 6]> {
+    value::read_mac_addr(self.data)
+  }
+
+  /// Value of a 3-byte MPLS label stack entry field
+  pub fn as_mpls_label(&self) -> Option<MplsLabel> {
+    value::read_mpls_label(self.data)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn field(data: &[u8]) -> Field<'_> {
+    Field {
+      field_type: 0,
+      data,
+    }
+  }
+
+  #[test]
+  fn field_as_u64_should_read_any_length_up_to_8() {
+    assert_eq!(field(&[0x2a]).as_u64(), Some(42));
+    assert_eq!(field(&[0x01, 0x00, 0x00]).as_u64(), Some(65536));
+    assert_eq!(field(&[0xff; 8]).as_u64(), Some(u64::MAX));
+    assert_eq!(field(&[]).as_u64(), None);
+    assert_eq!(field(&[0; 9]).as_u64(), None);
+  }
+
+  #[test]
+  fn field_as_ip_addr_should_depend_on_length() {
+    assert_eq!(
+      field(&[10, 0, 0, 1]).as_ip_addr(),
+      Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)))
+    );
+    assert_eq!(
+      field(&Ipv6Addr::LOCALHOST.octets()).as_ip_addr(),
+      Some(IpAddr::V6(Ipv6Addr::LOCALHOST))
+    );
+    assert_eq!(field(&[10, 0, 0]).as_ip_addr(), None);
+  }
+
+  #[test]
+  fn field_as_mpls_label_should_split_entry() {
+    assert_eq!(
+      field(&[0x00, 0x3e, 0x8b]).as_mpls_label(),
+      Some(MplsLabel {
+        label: 1000,
+        experimental: 5,
+        bottom_of_stack: true,
+      })
+    );
+    assert_eq!(field(&[0x00, 0x3e]).as_mpls_label(), None);
+  }
+}
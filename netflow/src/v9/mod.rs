@@ -0,0 +1,134 @@
+pub mod raw;
+
+mod cache;
+mod field;
+mod template;
+
+pub use cache::{TemplateCache, TemplateKey};
+pub use field::{field_type, scope_type, Field, FieldSpec, MplsLabel};
+pub use template::Template;
+
+// Based on RFC 3954: https://tools.ietf.org/html/rfc3954
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use super::raw::Packet;
+  use super::*;
+  use std::net::{IpAddr, Ipv4Addr};
+
+  #[test]
+  fn decoding_data_records_with_learnt_templates() {
+    let exporter = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+    let packet = Packet::new(&FLOW_PACKET_1).unwrap();
+    let mut cache = TemplateCache::new();
+    cache.learn(exporter, &packet).unwrap();
+
+    let mut records = Vec::new();
+    for set in packet.flowsets() {
+      let set = set.unwrap();
+      if let Some(template) = cache.get(exporter, packet.header().source_id(), set.id()) {
+        records.extend(set.records(template).map(|record| {
+          (
+            record.source_addr(),
+            record.destination_addr(),
+            record.source_port(),
+            record.destination_port(),
+            record.protocol(),
+            record.packets(),
+            record.bytes(),
+            record.first_switched(),
+            record.last_switched(),
+            record.mpls_labels(),
+          )
+        }));
+      }
+    }
+
+    assert_eq!(
+      records,
+      vec![
+        (
+          Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))),
+          Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))),
+          Some(50000),
+          Some(443),
+          Some(6),
+          Some(10),
+          Some(1500),
+          Some(60000),
+          Some(65000),
+          vec![MplsLabel {
+            label: 1000,
+            experimental: 0,
+            bottom_of_stack: true,
+          }],
+        ),
+        (
+          Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))),
+          Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2))),
+          Some(53),
+          Some(54321),
+          Some(17),
+          Some(1),
+          Some(72),
+          Some(62000),
+          Some(62000),
+          vec![MplsLabel {
+            label: 16,
+            experimental: 0,
+            bottom_of_stack: true,
+          }],
+        ),
+        (None, None, None, None, None, None, None, None, None, vec![]),
+      ]
+    );
+  }
+
+  #[test]
+  fn decoding_options_data_records() {
+    let exporter = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+    let packet = Packet::new(&FLOW_PACKET_1).unwrap();
+    let mut cache = TemplateCache::new();
+    cache.learn(exporter, &packet).unwrap();
+    let template = cache.get(exporter, 1, 258).unwrap();
+    let set = packet.flowsets().last().unwrap().unwrap();
+
+    let records = set.records(template).collect::<Vec<_>>();
+    assert_eq!(records.len(), 1);
+    println!("{:?}", records[0]);
+    assert_eq!(
+      records[0]
+        .scope(scope_type::SYSTEM)
+        .and_then(|f| f.as_u64()),
+      Some(1)
+    );
+    assert_eq!(
+      records[0]
+        .get(field_type::SAMPLING_INTERVAL)
+        .and_then(|f| f.as_u64()),
+      Some(1000)
+    );
+    assert_eq!(
+      records[0]
+        .get(field_type::SAMPLING_ALGORITHM)
+        .and_then(|f| f.as_u64()),
+      Some(2)
+    );
+  }
+
+  /// Header, template flowset (template 256), options template flowset
+  /// (template 258), data flowset with two records, options data flowset
+  pub const FLOW_PACKET_1: [u8; 176] = [
+    0x00, 0x09, 0x00, 0x05, 0x00, 0x01, 0x00, 0x00, 0x5c, 0x0e, 0xd7, 0xa5, 0x00, 0x00, 0x00, 0x2a,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x30, 0x01, 0x00, 0x00, 0x0a, 0x00, 0x08, 0x00, 0x04,
+    0x00, 0x0c, 0x00, 0x04, 0x00, 0x07, 0x00, 0x02, 0x00, 0x0b, 0x00, 0x02, 0x00, 0x04, 0x00, 0x01,
+    0x00, 0x02, 0x00, 0x04, 0x00, 0x01, 0x00, 0x04, 0x00, 0x16, 0x00, 0x04, 0x00, 0x15, 0x00, 0x04,
+    0x00, 0x46, 0x00, 0x03, 0x00, 0x01, 0x00, 0x18, 0x01, 0x02, 0x00, 0x04, 0x00, 0x08, 0x00, 0x01,
+    0x00, 0x04, 0x00, 0x22, 0x00, 0x04, 0x00, 0x23, 0x00, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x44,
+    0xc0, 0xa8, 0x01, 0x01, 0x0a, 0x00, 0x00, 0x01, 0xc3, 0x50, 0x01, 0xbb, 0x06, 0x00, 0x00, 0x00,
+    0x0a, 0x00, 0x00, 0x05, 0xdc, 0x00, 0x00, 0xea, 0x60, 0x00, 0x00, 0xfd, 0xe8, 0x00, 0x3e, 0x81,
+    0x0a, 0x00, 0x00, 0x02, 0xc0, 0xa8, 0x01, 0x02, 0x00, 0x35, 0xd4, 0x31, 0x11, 0x00, 0x00, 0x00,
+    0x01, 0x00, 0x00, 0x00, 0x48, 0x00, 0x00, 0xf2, 0x30, 0x00, 0x00, 0xf2, 0x30, 0x00, 0x01, 0x01,
+    0x01, 0x02, 0x00, 0x10, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x03, 0xe8, 0x02, 0x00, 0x00, 0x00,
+  ];
+}
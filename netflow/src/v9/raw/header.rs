@@ -0,0 +1,115 @@
+use super::{Error, Protocol};
+use byteorder::{ByteOrder, NetworkEndian};
+
+/// A Netflow v9 packet header helper struct
+#[derive(PartialEq)]
+pub struct Header<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for Header<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Header {{ version: {}, count: {}, sys_uptime_msecs: {}, unix_secs: {}, sequence_number: {}, source_id: {} }}",
+        self.version(), self.count(), self.sys_uptime_msecs(), self.unix_secs(), self.sequence_number(), self.source_id())
+  }
+}
+
+impl<'a> Header<'a> {
+  pub const LEN: usize = 20;
+  pub const VERSION: u16 = 9;
+
+  pub fn new(data: &'a [u8]) -> Result<Header<'a>, Error> {
+    if data.len() < Self::LEN {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::V9,
+        offset: 0,
+        expected: Self::LEN,
+        actual: data.len(),
+      });
+    }
+    let version = NetworkEndian::read_u16(&data[0..2]);
+    if version != Self::VERSION {
+      return Err(Error::InvalidVersion {
+        protocol: Protocol::V9,
+        version,
+      });
+    }
+
+    Ok(Header { data })
+  }
+
+  /// NetFlow export format version number
+  pub fn version(&self) -> u16 {
+    Self::VERSION
+  }
+
+  /// Total number of records (template and data) exported in this packet
+  pub fn count(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[2..4]))
+  }
+
+  /// Current time in milliseconds since the export device started
+  pub fn sys_uptime_msecs(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[4..8]))
+  }
+
+  /// Current time in seconds since 0000 Coordinated Universal Time 1970
+  pub fn unix_secs(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[8..12]))
+  }
+
+  /// Incremental sequence counter of all export packets sent by the export device
+  pub fn sequence_number(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[12..16]))
+  }
+
+  /// Identifier of the observation domain of the export device
+  pub fn source_id(&self) -> u32 {
+    NetworkEndian::read_u32(&(self.data[16..20]))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::FLOW_PACKET_1;
+  use super::*;
+
+  #[test]
+  fn header_new_should_fail_with_not_enough_data() {
+    assert_eq!(
+      Header::new(&FLOW_PACKET_1[..Header::LEN - 1]),
+      Err(Error::NotEnoughData {
+        protocol: Protocol::V9,
+        offset: 0,
+        expected: Header::LEN,
+        actual: Header::LEN - 1,
+      })
+    );
+  }
+
+  #[test]
+  fn header_new_should_fail_with_invalid_version() {
+    let mut data = FLOW_PACKET_1.to_vec();
+    data[1] = 5;
+
+    assert_eq!(
+      Header::new(&data),
+      Err(Error::InvalidVersion {
+        protocol: Protocol::V9,
+        version: 5,
+      })
+    );
+  }
+
+  #[test]
+  fn header_accessors_expose_fields() {
+    let header = Header::new(&FLOW_PACKET_1).unwrap();
+
+    assert_eq!(header.version(), 9);
+    assert_eq!(header.count(), 5);
+    assert_eq!(header.sys_uptime_msecs(), 65536);
+    assert_eq!(header.unix_secs(), 1544476581);
+    assert_eq!(header.sequence_number(), 42);
+    assert_eq!(header.source_id(), 1);
+  }
+}
@@ -0,0 +1,122 @@
+use super::{DataRecord, Error, FlowSet, OptionsTemplate, Template};
+
+/// Iterator over the flowsets of a packet, stopping after the first invalid one
+pub struct FlowSetIterator<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+}
+
+impl<'a> Iterator for FlowSetIterator<'a> {
+  type Item = Result<FlowSet<'a>, Error>;
+
+  fn next(&mut self) -> Option<Result<FlowSet<'a>, Error>> {
+    let data = &self.data[self.offset..];
+    if data.len() < FlowSet::HEADER_LEN {
+      return None;
+    }
+
+    match FlowSet::parse(data, self.offset) {
+      Ok(set) => {
+        self.offset += set.data.len();
+        Some(Ok(set))
+      }
+      Err(error) => {
+        self.offset = self.data.len();
+        Some(Err(error))
+      }
+    }
+  }
+}
+
+/// Iterator over the template records of a flowset, stopping after the first invalid one
+pub struct TemplateIterator<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+}
+
+impl<'a> Iterator for TemplateIterator<'a> {
+  type Item = Result<Template<'a>, Error>;
+
+  fn next(&mut self) -> Option<Result<Template<'a>, Error>> {
+    // Anything shorter than a template header is padding
+    if self.data.len() < Template::HEADER_LEN {
+      return None;
+    }
+
+    match Template::new(self.data) {
+      Ok(template) => {
+        let len = template.data.len();
+        self.data = &self.data[len..];
+        self.offset += len;
+        Some(Ok(template))
+      }
+      Err(error) => {
+        self.data = &[];
+        Some(Err(error.offset_by(self.offset)))
+      }
+    }
+  }
+}
+
+/// Iterator over the options template records of a flowset, stopping after the first invalid one
+pub struct OptionsTemplateIterator<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+}
+
+impl<'a> Iterator for OptionsTemplateIterator<'a> {
+  type Item = Result<OptionsTemplate<'a>, Error>;
+
+  fn next(&mut self) -> Option<Result<OptionsTemplate<'a>, Error>> {
+    // Anything shorter than an options template header is padding
+    if self.data.len() < OptionsTemplate::HEADER_LEN {
+      return None;
+    }
+
+    match OptionsTemplate::new(self.data) {
+      Ok(template) => {
+        let len = template.data.len();
+        self.data = &self.data[len..];
+        self.offset += len;
+        Some(Ok(template))
+      }
+      Err(error) => {
+        self.data = &[];
+        Some(Err(error.offset_by(self.offset)))
+      }
+    }
+  }
+}
+
+/// Iterator over the data records of a flowset
+pub struct DataRecordIterator<'a, 't> {
+  pub(crate) data: &'a [u8],
+  pub(crate) template: &'t crate::v9::Template,
+  pub(crate) record_length: usize,
+}
+
+impl<'a, 't> Iterator for DataRecordIterator<'a, 't> {
+  type Item = DataRecord<'a, 't>;
+
+  fn next(&mut self) -> Option<DataRecord<'a, 't>> {
+    // Anything shorter than a record is padding
+    if self.record_length == 0 || self.data.len() < self.record_length {
+      return None;
+    }
+
+    let (data, rest) = self.data.split_at(self.record_length);
+    self.data = rest;
+    Some(DataRecord {
+      template: self.template,
+      data,
+    })
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let remainder = match self.record_length {
+      0 => 0,
+      len => self.data.len() / len,
+    };
+    (remainder, Some(remainder))
+  }
+}
@@ -0,0 +1,23 @@
+use super::{field_type, Field, FieldSpec, MplsLabel};
+use crate::{Error, Protocol};
+
+mod header;
+mod iterator;
+mod packet;
+mod record;
+mod set;
+mod template;
+
+pub use header::Header;
+pub use iterator::{
+  DataRecordIterator, FlowSetIterator, OptionsTemplateIterator, TemplateIterator,
+};
+pub use packet::Packet;
+pub use record::{DataRecord, FieldIterator};
+pub use set::FlowSet;
+pub use template::{FieldSpecIterator, OptionsTemplate, Template};
+
+#[cfg(test)]
+mod tests {
+  pub use super::super::tests::FLOW_PACKET_1;
+}
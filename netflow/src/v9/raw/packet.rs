@@ -0,0 +1,103 @@
+use super::Error;
+
+use super::{FlowSetIterator, Header};
+
+/// A Netflow v9 packet helper struct
+#[derive(PartialEq)]
+pub struct Packet<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for Packet<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Packet")
+      .field("header", &self.header())
+      .field("flowsets", &self.flowsets().collect::<Vec<_>>())
+      .finish()
+  }
+}
+
+impl<'a> Packet<'a> {
+  pub fn new(data: &'a [u8]) -> Result<Packet<'a>, Error> {
+    Header::new(data)?;
+
+    Ok(Packet { data })
+  }
+
+  pub fn header(&self) -> Header<'a> {
+    Header { data: self.data }
+  }
+
+  /// Flowsets following the header, up to the first invalid one
+  pub fn flowsets(&self) -> FlowSetIterator<'a> {
+    FlowSetIterator {
+      data: self.data,
+      offset: Header::LEN,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::FLOW_PACKET_1;
+  use super::super::{FlowSet, Protocol};
+  use super::*;
+
+  #[test]
+  fn packet_flowsets_should_split_packet() {
+    let packet = Packet::new(&FLOW_PACKET_1).unwrap();
+    let sets = packet
+      .flowsets()
+      .map(|set| set.map(|set| (set.id(), set.length())))
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+
+    assert_eq!(sets, vec![(0, 48), (1, 24), (256, 68), (258, 16)]);
+  }
+
+  #[test]
+  fn packet_flowsets_should_stop_after_truncated_flowset() {
+    let data = &FLOW_PACKET_1[..FLOW_PACKET_1.len() - 1];
+    let packet = Packet::new(data).unwrap();
+    let sets = packet.flowsets().collect::<Vec<_>>();
+
+    assert_eq!(sets.len(), 4);
+    assert_eq!(
+      sets[3],
+      Err(Error::NotEnoughData {
+        protocol: Protocol::V9,
+        offset: 160,
+        expected: 16,
+        actual: 15,
+      })
+    );
+  }
+
+  #[test]
+  fn packet_templates_should_expose_fields() {
+    let packet = Packet::new(&FLOW_PACKET_1).unwrap();
+    let sets = packet
+      .flowsets()
+      .collect::<Result<Vec<FlowSet>, _>>()
+      .unwrap();
+
+    let templates = sets[0].templates().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(templates.len(), 1);
+    assert_eq!(templates[0].id(), 256);
+    assert_eq!(templates[0].field_count(), 10);
+
+    let options = sets[1]
+      .options_templates()
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+    assert_eq!(options.len(), 1);
+    assert_eq!(options[0].id(), 258);
+    assert_eq!(options[0].scope_fields().count(), 1);
+    assert_eq!(options[0].fields().count(), 2);
+  }
+
+  #[test]
+  fn packet_implements_debug() {
+    println!("{:?}", Packet::new(&FLOW_PACKET_1).unwrap());
+  }
+}
@@ -0,0 +1,274 @@
+use super::{field_type, Field, FieldSpec, MplsLabel};
+use crate::v9::Template;
+use crate::Flow;
+use std::net::IpAddr;
+
+/// Iterator over the fields of a data record
+pub struct FieldIterator<'a, 't> {
+  pub(crate) specs: std::slice::Iter<'t, FieldSpec>,
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a, 't> Iterator for FieldIterator<'a, 't> {
+  type Item = Field<'a>;
+
+  fn next(&mut self) -> Option<Field<'a>> {
+    let spec = self.specs.next()?;
+    let (data, rest) = self.data.split_at(spec.length as usize);
+    self.data = rest;
+    Some(Field {
+      field_type: spec.field_type,
+      data,
+    })
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.specs.size_hint()
+  }
+}
+
+/// A Netflow v9 data record helper struct
+#[derive(PartialEq)]
+pub struct DataRecord<'a, 't> {
+  pub(crate) template: &'t Template,
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a, 't> std::fmt::Debug for DataRecord<'a, 't> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("DataRecord")
+      .field("template_id", &self.template.id)
+      .field("scope_fields", &self.scope_fields().collect::<Vec<_>>())
+      .field("fields", &self.fields().collect::<Vec<_>>())
+      .finish()
+  }
+}
+
+impl<'a, 't> DataRecord<'a, 't> {
+  /// Template this record was decoded with
+  pub fn template(&self) -> &'t Template {
+    self.template
+  }
+
+  /// Scope fields of an options data record, see `scope_type`
+  pub fn scope_fields(&self) -> FieldIterator<'a, 't> {
+    FieldIterator {
+      specs: self.template.scope_fields.iter(),
+      data: self.data,
+    }
+  }
+
+  /// Fields of the record, following the scope fields if any, see `field_type`
+  pub fn fields(&self) -> FieldIterator<'a, 't> {
+    let scope_length = self
+      .template
+      .scope_fields
+      .iter()
+      .map(|spec| spec.length as usize)
+      .sum();
+    FieldIterator {
+      specs: self.template.fields.iter(),
+      data: &self.data[scope_length..],
+    }
+  }
+
+  /// First field of the given type
+  pub fn get(&self, field_type: u16) -> Option<Field<'a>> {
+    self.fields().find(|field| field.field_type == field_type)
+  }
+
+  /// First scope field of the given type
+  pub fn scope(&self, scope_type: u16) -> Option<Field<'a>> {
+    self
+      .scope_fields()
+      .find(|field| field.field_type == scope_type)
+  }
+
+  fn get_u64(&self, field_type: u16) -> Option<u64> {
+    self.get(field_type).and_then(|field| field.as_u64())
+  }
+
+  fn get_ip_addr(&self, v4_type: u16, v6_type: u16) -> Option<IpAddr> {
+    self
+      .get(v4_type)
+      .or_else(|| self.get(v6_type))
+      .and_then(|field| field.as_ip_addr())
+  }
+
+  /// Source IPv4 or IPv6 address
+  pub fn source_addr(&self) -> Option<IpAddr> {
+    self.get_ip_addr(field_type::IPV4_SRC_ADDR, field_type::IPV6_SRC_ADDR)
+  }
+
+  /// Destination IPv4 or IPv6 address
+  pub fn destination_addr(&self) -> Option<IpAddr> {
+    self.get_ip_addr(field_type::IPV4_DST_ADDR, field_type::IPV6_DST_ADDR)
+  }
+
+  /// IPv4 or IPv6 address of next hop router
+  pub fn next_hop(&self) -> Option<IpAddr> {
+    self.get_ip_addr(field_type::IPV4_NEXT_HOP, field_type::IPV6_NEXT_HOP)
+  }
+
+  /// TCP or UDP source port number
+  pub fn source_port(&self) -> Option<u16> {
+    self.get_u64(field_type::L4_SRC_PORT).map(|v| v as u16)
+  }
+
+  /// TCP or UDP destination port number
+  pub fn destination_port(&self) -> Option<u16> {
+    self.get_u64(field_type::L4_DST_PORT).map(|v| v as u16)
+  }
+
+  /// IP protocol type (for example, TCP = 6, UDP = 17, ...)
+  pub fn protocol(&self) -> Option<u8> {
+    self.get_u64(field_type::PROTOCOL).map(|v| v as u8)
+  }
+
+  /// SNMP index of input interface
+  pub fn input(&self) -> Option<u32> {
+    self.get_u64(field_type::INPUT_SNMP).map(|v| v as u32)
+  }
+
+  /// SNMP index of output interface
+  pub fn output(&self) -> Option<u32> {
+    self.get_u64(field_type::OUTPUT_SNMP).map(|v| v as u32)
+  }
+
+  /// Incoming packets in the flow
+  pub fn packets(&self) -> Option<u64> {
+    self.get_u64(field_type::IN_PKTS)
+  }
+
+  /// Incoming bytes in the flow
+  pub fn bytes(&self) -> Option<u64> {
+    self.get_u64(field_type::IN_BYTES)
+  }
+
+  /// IP type of service (ToS) of incoming packets
+  pub fn type_of_service(&self) -> Option<u8> {
+    self.get_u64(field_type::SRC_TOS).map(|v| v as u8)
+  }
+
+  /// Cumulative OR of TCP flags
+  pub fn tcp_flags(&self) -> Option<u8> {
+    self.get_u64(field_type::TCP_FLAGS).map(|v| v as u8)
+  }
+
+  /// Autonomous system number of the source, either origin or peer
+  pub fn source_as(&self) -> Option<u32> {
+    self.get_u64(field_type::SRC_AS).map(|v| v as u32)
+  }
+
+  /// Autonomous system number of the destination, either origin or peer
+  pub fn destination_as(&self) -> Option<u32> {
+    self.get_u64(field_type::DST_AS).map(|v| v as u32)
+  }
+
+  /// Source IPv4 or IPv6 address prefix mask bits
+  pub fn source_mask(&self) -> Option<u8> {
+    self
+      .get_u64(field_type::SRC_MASK)
+      .or_else(|| self.get_u64(field_type::IPV6_SRC_MASK))
+      .map(|v| v as u8)
+  }
+
+  /// Destination IPv4 or IPv6 address prefix mask bits
+  pub fn destination_mask(&self) -> Option<u8> {
+    self
+      .get_u64(field_type::DST_MASK)
+      .or_else(|| self.get_u64(field_type::IPV6_DST_MASK))
+      .map(|v| v as u8)
+  }
+
+  /// SysUptime in milliseconds at start of flow
+  pub fn first_switched(&self) -> Option<u32> {
+    self.get_u64(field_type::FIRST_SWITCHED).map(|v| v as u32)
+  }
+
+  /// SysUptime in milliseconds at the time the last packet of the flow was switched
+  pub fn last_switched(&self) -> Option<u32> {
+    self.get_u64(field_type::LAST_SWITCHED).map(|v| v as u32)
+  }
+
+  /// MPLS label stack, from `MPLS_LABEL_1` to `MPLS_LABEL_10`
+  pub fn mpls_labels(&self) -> Vec<MplsLabel> {
+    (field_type::MPLS_LABEL_1..=field_type::MPLS_LABEL_10)
+      .filter_map(|t| self.get(t).and_then(|field| field.as_mpls_label()))
+      .collect()
+  }
+}
+
+impl<'a, 't> Flow for DataRecord<'a, 't> {
+  fn source_addr(&self) -> Option<IpAddr> {
+    self.source_addr()
+  }
+
+  fn destination_addr(&self) -> Option<IpAddr> {
+    self.destination_addr()
+  }
+
+  fn source_port(&self) -> Option<u16> {
+    self.source_port()
+  }
+
+  fn destination_port(&self) -> Option<u16> {
+    self.destination_port()
+  }
+
+  fn protocol(&self) -> Option<u8> {
+    self.protocol()
+  }
+
+  fn packets(&self) -> Option<u64> {
+    self.packets()
+  }
+
+  fn bytes(&self) -> Option<u64> {
+    self.bytes()
+  }
+
+  fn input(&self) -> Option<u32> {
+    self.input()
+  }
+
+  fn output(&self) -> Option<u32> {
+    self.output()
+  }
+
+  fn next_hop(&self) -> Option<IpAddr> {
+    self.next_hop()
+  }
+
+  fn source_as(&self) -> Option<u32> {
+    self.source_as()
+  }
+
+  fn destination_as(&self) -> Option<u32> {
+    self.destination_as()
+  }
+
+  fn source_mask(&self) -> Option<u8> {
+    self.source_mask()
+  }
+
+  fn destination_mask(&self) -> Option<u8> {
+    self.destination_mask()
+  }
+
+  fn type_of_service(&self) -> Option<u8> {
+    self.type_of_service()
+  }
+
+  fn tcp_flags(&self) -> Option<u8> {
+    self.tcp_flags()
+  }
+
+  fn first_packet_sys_uptime(&self) -> Option<u32> {
+    self.first_switched()
+  }
+
+  fn last_packet_sys_uptime(&self) -> Option<u32> {
+    self.last_switched()
+  }
+}
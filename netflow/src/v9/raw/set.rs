@@ -0,0 +1,174 @@
+use super::{Error, Protocol};
+use byteorder::{ByteOrder, NetworkEndian};
+
+use super::{DataRecordIterator, OptionsTemplateIterator, TemplateIterator};
+
+/// A Netflow v9 flowset helper struct
+#[derive(PartialEq)]
+pub struct FlowSet<'a> {
+  pub(crate) data: &'a [u8],
+  pub(crate) offset: usize,
+}
+
+impl<'a> std::fmt::Debug for FlowSet<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "FlowSet {{ id: {}, length: {} }}",
+      self.id(),
+      self.length()
+    )
+  }
+}
+
+impl<'a> FlowSet<'a> {
+  pub const HEADER_LEN: usize = 4;
+  /// ID of the flowsets holding template records
+  pub const TEMPLATE_ID: u16 = 0;
+  /// ID of the flowsets holding options template records
+  pub const OPTIONS_TEMPLATE_ID: u16 = 1;
+  /// Lowest ID of the flowsets holding data records, lower IDs are reserved
+  pub const MIN_DATA_ID: u16 = 256;
+
+  /// Parses the flowset at the start of `data`, ignoring any trailing bytes
+  pub fn new(data: &'a [u8]) -> Result<FlowSet<'a>, Error> {
+    Self::parse(data, 0)
+  }
+
+  /// Parses the flowset at the start of `data`, found at `offset` in its packet
+  pub(crate) fn parse(data: &'a [u8], offset: usize) -> Result<FlowSet<'a>, Error> {
+    if data.len() < Self::HEADER_LEN {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::V9,
+        offset,
+        expected: Self::HEADER_LEN,
+        actual: data.len(),
+      });
+    }
+    let length = NetworkEndian::read_u16(&data[2..4]) as usize;
+    if length < Self::HEADER_LEN {
+      return Err(Error::InvalidLength {
+        protocol: Protocol::V9,
+        offset,
+        length,
+      });
+    }
+    if data.len() < length {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::V9,
+        offset,
+        expected: length,
+        actual: data.len(),
+      });
+    }
+
+    Ok(FlowSet {
+      data: &data[..length],
+      offset,
+    })
+  }
+
+  /// Flowset ID: 0 for templates, 1 for options templates, a template ID for data
+  pub fn id(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[0..2]))
+  }
+
+  /// Total length of the flowset in bytes, including its header and padding
+  pub fn length(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[2..4]))
+  }
+
+  /// Content of the flowset, following its header
+  pub fn body(&self) -> &'a [u8] {
+    &self.data[Self::HEADER_LEN..]
+  }
+
+  pub fn is_template(&self) -> bool {
+    self.id() == Self::TEMPLATE_ID
+  }
+
+  pub fn is_options_template(&self) -> bool {
+    self.id() == Self::OPTIONS_TEMPLATE_ID
+  }
+
+  pub fn is_data(&self) -> bool {
+    self.id() >= Self::MIN_DATA_ID
+  }
+
+  /// Template records of a template flowset, nothing for other flowsets
+  pub fn templates(&self) -> TemplateIterator<'a> {
+    TemplateIterator {
+      data: if self.is_template() { self.body() } else { &[] },
+      offset: self.offset + Self::HEADER_LEN,
+    }
+  }
+
+  /// Options template records of an options template flowset, nothing for other flowsets
+  pub fn options_templates(&self) -> OptionsTemplateIterator<'a> {
+    OptionsTemplateIterator {
+      data: if self.is_options_template() {
+        self.body()
+      } else {
+        &[]
+      },
+      offset: self.offset + Self::HEADER_LEN,
+    }
+  }
+
+  /// Data records of a data flowset decoded with `template`, nothing if the
+  /// template ID does not match the flowset ID
+  pub fn records<'t>(&self, template: &'t crate::v9::Template) -> DataRecordIterator<'a, 't> {
+    DataRecordIterator {
+      data: if self.is_data() && self.id() == template.id {
+        self.body()
+      } else {
+        &[]
+      },
+      template,
+      record_length: template.record_length(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::tests::FLOW_PACKET_1;
+  use super::super::Header;
+  use super::*;
+
+  #[test]
+  fn set_new_should_fail_with_invalid_length() {
+    assert_eq!(
+      FlowSet::new(&[0x01, 0x00, 0x00, 0x02]),
+      Err(Error::InvalidLength {
+        protocol: Protocol::V9,
+        offset: 0,
+        length: 2,
+      })
+    );
+  }
+
+  #[test]
+  fn set_new_should_fail_with_not_enough_data() {
+    let data = &FLOW_PACKET_1[Header::LEN..Header::LEN + 10];
+
+    assert_eq!(
+      FlowSet::new(data),
+      Err(Error::NotEnoughData {
+        protocol: Protocol::V9,
+        offset: 0,
+        expected: 48,
+        actual: 10,
+      })
+    );
+  }
+
+  #[test]
+  fn set_templates_should_be_empty_for_data_flowsets() {
+    let set = FlowSet::new(&[0x01, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00]).unwrap();
+
+    assert!(set.is_data());
+    assert_eq!(set.templates().count(), 0);
+    assert_eq!(set.options_templates().count(), 0);
+  }
+}
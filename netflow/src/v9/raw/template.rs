@@ -0,0 +1,206 @@
+use super::{Error, FieldSpec, Protocol};
+use byteorder::{ByteOrder, NetworkEndian};
+
+/// Iterator over the field specifiers of a template
+pub struct FieldSpecIterator<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> Iterator for FieldSpecIterator<'a> {
+  type Item = FieldSpec;
+
+  fn next(&mut self) -> Option<FieldSpec> {
+    if self.data.len() < FieldSpec::LEN {
+      return None;
+    }
+
+    let spec = FieldSpec {
+      field_type: NetworkEndian::read_u16(&self.data[0..2]),
+      length: NetworkEndian::read_u16(&self.data[2..4]),
+    };
+    self.data = &self.data[FieldSpec::LEN..];
+    Some(spec)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let remainder = self.data.len() / FieldSpec::LEN;
+    (remainder, Some(remainder))
+  }
+}
+
+/// A Netflow v9 template record helper struct
+#[derive(PartialEq)]
+pub struct Template<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for Template<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Template")
+      .field("id", &self.id())
+      .field("fields", &self.fields().collect::<Vec<_>>())
+      .finish()
+  }
+}
+
+impl<'a> Template<'a> {
+  pub const HEADER_LEN: usize = 4;
+
+  /// Parses the template record at the start of `data`, ignoring any trailing bytes
+  pub fn new(data: &'a [u8]) -> Result<Template<'a>, Error> {
+    if data.len() < Self::HEADER_LEN {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::V9,
+        offset: 0,
+        expected: Self::HEADER_LEN,
+        actual: data.len(),
+      });
+    }
+    let len = Self::HEADER_LEN + NetworkEndian::read_u16(&data[2..4]) as usize * FieldSpec::LEN;
+    if data.len() < len {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::V9,
+        offset: 0,
+        expected: len,
+        actual: data.len(),
+      });
+    }
+
+    Ok(Template { data: &data[..len] })
+  }
+
+  /// ID of the template, matching the ID of the data flowsets it describes
+  pub fn id(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[0..2]))
+  }
+
+  /// Number of fields in the template
+  pub fn field_count(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[2..4]))
+  }
+
+  /// Type and length of each field, in data record order
+  pub fn fields(&self) -> FieldSpecIterator<'a> {
+    FieldSpecIterator {
+      data: &self.data[Self::HEADER_LEN..],
+    }
+  }
+}
+
+/// A Netflow v9 options template record helper struct
+#[derive(PartialEq)]
+pub struct OptionsTemplate<'a> {
+  pub(crate) data: &'a [u8],
+}
+
+impl<'a> std::fmt::Debug for OptionsTemplate<'a> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("OptionsTemplate")
+      .field("id", &self.id())
+      .field("scope_fields", &self.scope_fields().collect::<Vec<_>>())
+      .field("fields", &self.fields().collect::<Vec<_>>())
+      .finish()
+  }
+}
+
+impl<'a> OptionsTemplate<'a> {
+  pub const HEADER_LEN: usize = 6;
+
+  /// Parses the options template record at the start of `data`, ignoring any trailing bytes
+  pub fn new(data: &'a [u8]) -> Result<OptionsTemplate<'a>, Error> {
+    if data.len() < Self::HEADER_LEN {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::V9,
+        offset: 0,
+        expected: Self::HEADER_LEN,
+        actual: data.len(),
+      });
+    }
+    let scope_length = NetworkEndian::read_u16(&data[2..4]) as usize;
+    let option_length = NetworkEndian::read_u16(&data[4..6]) as usize;
+    if !scope_length.is_multiple_of(FieldSpec::LEN) || !option_length.is_multiple_of(FieldSpec::LEN)
+    {
+      return Err(Error::InvalidLength {
+        protocol: Protocol::V9,
+        offset: 0,
+        length: scope_length + option_length,
+      });
+    }
+    let len = Self::HEADER_LEN + scope_length + option_length;
+    if data.len() < len {
+      return Err(Error::NotEnoughData {
+        protocol: Protocol::V9,
+        offset: 0,
+        expected: len,
+        actual: data.len(),
+      });
+    }
+
+    Ok(OptionsTemplate { data: &data[..len] })
+  }
+
+  /// ID of the template, matching the ID of the data flowsets it describes
+  pub fn id(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[0..2]))
+  }
+
+  /// Length in bytes of the scope field specifiers
+  pub fn scope_length(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[2..4]))
+  }
+
+  /// Length in bytes of the option field specifiers
+  pub fn option_length(&self) -> u16 {
+    NetworkEndian::read_u16(&(self.data[4..6]))
+  }
+
+  /// Type and length of each scope field, see `scope_type`
+  pub fn scope_fields(&self) -> FieldSpecIterator<'a> {
+    let start = Self::HEADER_LEN;
+    FieldSpecIterator {
+      data: &self.data[start..start + self.scope_length() as usize],
+    }
+  }
+
+  /// Type and length of each option field, following the scope fields in data records
+  pub fn fields(&self) -> FieldSpecIterator<'a> {
+    let start = Self::HEADER_LEN + self.scope_length() as usize;
+    FieldSpecIterator {
+      data: &self.data[start..],
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn template_new_should_fail_with_not_enough_data() {
+    let data = [0x01, 0x00, 0x00, 0x02, 0x00, 0x08, 0x00, 0x04];
+
+    assert_eq!(
+      Template::new(&data),
+      Err(Error::NotEnoughData {
+        protocol: Protocol::V9,
+        offset: 0,
+        expected: 12,
+        actual: 8,
+      })
+    );
+  }
+
+  #[test]
+  fn options_template_new_should_fail_with_invalid_length() {
+    let data = [0x01, 0x02, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01];
+
+    assert_eq!(
+      OptionsTemplate::new(&data),
+      Err(Error::InvalidLength {
+        protocol: Protocol::V9,
+        offset: 0,
+        length: 2,
+      })
+    );
+  }
+}
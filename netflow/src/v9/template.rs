@@ -0,0 +1,50 @@
+use super::raw;
+use super::FieldSpec;
+
+/// An owned Netflow v9 template or options template
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Template {
+  /// ID of the template, matching the ID of the data flowsets it describes
+  pub id: u16,
+  /// Scope fields, only announced by options templates
+  pub scope_fields: Vec<FieldSpec>,
+  /// Fields, following the scope fields in data records
+  pub fields: Vec<FieldSpec>,
+}
+
+impl Template {
+  /// Length in bytes of the data records described by this template
+  pub fn record_length(&self) -> usize {
+    self
+      .scope_fields
+      .iter()
+      .chain(self.fields.iter())
+      .map(|spec| spec.length as usize)
+      .sum()
+  }
+
+  /// Whether this template describes options data records, which start with scope fields
+  pub fn is_options(&self) -> bool {
+    !self.scope_fields.is_empty()
+  }
+}
+
+impl<'a> From<&raw::Template<'a>> for Template {
+  fn from(template: &raw::Template<'a>) -> Template {
+    Template {
+      id: template.id(),
+      scope_fields: Vec::new(),
+      fields: template.fields().collect(),
+    }
+  }
+}
+
+impl<'a> From<&raw::OptionsTemplate<'a>> for Template {
+  fn from(template: &raw::OptionsTemplate<'a>) -> Template {
+    Template {
+      id: template.id(),
+      scope_fields: template.scope_fields().collect(),
+      fields: template.fields().collect(),
+    }
+  }
+}
@@ -0,0 +1,65 @@
+//! Decoding of the field values shared by the template-based protocols
+
+use byteorder::{ByteOrder, NetworkEndian};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// An MPLS label stack entry, as exported in MPLS label fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MplsLabel {
+  /// 20-bit label value
+  pub label: u32,
+  /// 3-bit experimental (traffic class) field
+  pub experimental: u8,
+  /// Whether this entry is the bottom of the label stack
+  pub bottom_of_stack: bool,
+}
+
+pub(crate) fn read_u64(data: &[u8]) -> Option<u64> {
+  match data.len() {
+    1..=8 => Some(NetworkEndian::read_uint(data, data.len())),
+    _ => None,
+  }
+}
+
+pub(crate) fn read_ipv4(data: &[u8]) -> Option<Ipv4Addr> {
+  match *data {
+    [a, b, c, d] => Some(Ipv4Addr::new(a, b, c, d)),
+    _ => None,
+  }
+}
+
+pub(crate) fn read_ipv6(data: &[u8]) -> Option<Ipv6Addr> {
+  if data.len() != 16 {
+    return None;
+  }
+  let mut octets = [0u8; 16];
+  octets.copy_from_slice(data);
+  Some(Ipv6Addr::from(octets))
+}
+
+pub(crate) fn read_ip_addr(data: &[u8]) -> Option<IpAddr> {
+  read_ipv4(data)
+    .map(IpAddr::V4)
+    .or_else(|| read_ipv6(data).map(IpAddr::V6))
+}
+
+pub(crate) fn read_mac_addr(data: &[u8]) -> Option<[u8; 6]> {
+  if data.len() != 6 {
+    return None;
+  }
+  let mut octets = [0u8; 6];
+  octets.copy_from_slice(data);
+  Some(octets)
+}
+
+pub(crate) fn read_mpls_label(data: &[u8]) -> Option<MplsLabel> {
+  if data.len() != 3 {
+    return None;
+  }
+  let entry = NetworkEndian::read_u24(data);
+  Some(MplsLabel {
+    label: entry >> 4,
+    experimental: ((entry >> 1) & 0x7) as u8,
+    bottom_of_stack: entry & 0x1 == 1,
+  })
+}